@@ -0,0 +1,1999 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use console::style;
+use contract_verification_migrator_core::{
+    api_url_with_chain_id, discover_api_url, keyring_api_key, read_api_key_file,
+    resolve_explorer_preset, AdditionalTarget, Contract, ContractPath, ContractReport, CopyOptions,
+    CopyOutcome, EventHandler, ExplorerHttpOptions, ExplorerVerificationStatus, FailureReason,
+    LinkedLibrary, MigrationReport, Migrator, PollOptions, RelatedContractsOptions, ReportFormat,
+    ReportOutcome, RetryPolicy, ShutdownSignal, SourcifySource, StageTimings, StorageReader,
+    TargetType, VerificationResult, DEFAULT_SOURCIFY_URL,
+};
+use eyre::WrapErr;
+use foundry_block_explorers::verify::VerifyContract;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Copy contract verification from one block-explorer to another
+#[derive(Parser, Debug)]
+#[clap(name = "contract_verfication_migrator")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Read the api keys not passed on the command line from the OS keyring (requires the default
+    /// `keyring` feature), stored under the service `contract-verification-migrator` with the
+    /// host of the api url as account
+    #[clap(long, global = true)]
+    use_keyring: bool,
+    /// HTTP(S) or SOCKS5 proxy all requests are sent through (e.g. `http://proxy.internal:3128`
+    /// or `socks5h://localhost:1080`), without it the `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY`
+    /// environment variables are honored. Solc is downloaded for `--compile-locally` through the
+    /// proxy of the environment
+    #[clap(long, global = true)]
+    proxy: Option<String>,
+    /// PEM file with CA certificates trusted in addition to the system's root certificates (e.g.
+    /// the internal CA of a self-hosted Blockscout), can be repeated
+    #[clap(long, global = true)]
+    ca_cert: Vec<PathBuf>,
+    /// Skip validating the TLS certificate of the source or target block-explorer (e.g. a
+    /// self-signed certificate of a staging instance), can be repeated
+    #[clap(long, global = true, value_name = "EXPLORER")]
+    insecure: Vec<Explorer>,
+    /// Credentials sent via HTTP basic auth with every request to the source block-explorer (e.g.
+    /// a private Blockscout behind a reverse proxy)
+    #[clap(long, global = true, value_name = "USER:PASSWORD", value_parser = parse_basic_auth)]
+    source_basic_auth: Option<(String, String)>,
+    /// Credentials sent via HTTP basic auth with every request to the target block-explorer
+    #[clap(long, global = true, value_name = "USER:PASSWORD", value_parser = parse_basic_auth)]
+    target_basic_auth: Option<(String, String)>,
+    /// Header sent with every request to the source block-explorer (e.g. a Cloudflare Access
+    /// token), can be repeated
+    #[clap(long, global = true, value_name = "KEY=VALUE", value_parser = parse_header)]
+    source_header: Vec<(String, String)>,
+    /// Header sent with every request to the target block-explorer, can be repeated
+    #[clap(long, global = true, value_name = "KEY=VALUE", value_parser = parse_header)]
+    target_header: Vec<(String, String)>,
+}
+
+/// Block-explorer a command connects to
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Explorer {
+    /// The block-explorer the verifications are read from
+    Source,
+    /// The block-explorer the verifications are submitted to
+    Target,
+}
+
+impl Cli {
+    /// Settings of the http clients connecting to the given block-explorer
+    fn explorer_http_options(&self, explorer: Explorer) -> ExplorerHttpOptions {
+        let (basic_auth, headers) = match explorer {
+            Explorer::Source => (&self.source_basic_auth, &self.source_header),
+            Explorer::Target => (&self.target_basic_auth, &self.target_header),
+        };
+        ExplorerHttpOptions {
+            insecure: self.insecure.contains(&explorer),
+            basic_auth: basic_auth.clone(),
+            headers: headers.clone(),
+            proxy: self.proxy.clone(),
+            ca_certificates: self.ca_cert.clone(),
+        }
+    }
+
+    /// Attach the settings of the source and target block-explorer to the arguments of the
+    /// command, so they only apply to the clients connecting to that block-explorer
+    fn configure_explorers(&mut self) {
+        let source_http = self.explorer_http_options(Explorer::Source);
+        let target_http = self.explorer_http_options(Explorer::Target);
+        let (source, target) = match &mut self.command {
+            Command::Copy { source, target, .. } | Command::RetryFailed { source, target, .. } => {
+                (Some(&mut source.http), Some(&mut target.http))
+            }
+            Command::Check { source, target, .. }
+            | Command::Diff { source, target, .. }
+            | Command::Watch { source, target, .. } => {
+                (Some(&mut source.http), Some(&mut target.http))
+            }
+            Command::Export { source, .. } | Command::Fetch { source, .. } => {
+                (Some(&mut source.http), None)
+            }
+            Command::Resume { target, .. } | Command::Import { target, .. } => {
+                (None, Some(&mut target.http))
+            }
+        };
+        for (name, http, options) in [
+            ("source", source, source_http),
+            ("target", target, target_http),
+        ] {
+            let Some(http) = http else {
+                continue;
+            };
+            if options.insecure {
+                eprintln!(
+                    "{}",
+                    style(format!(
+                        "WARNING: The TLS certificate of the {} block-explorer is not validated, \
+                         the connection is open to man-in-the-middle attacks",
+                        name
+                    ))
+                    .red()
+                    .bold()
+                );
+            }
+            *http = options;
+        }
+    }
+}
+
+impl Command {
+    /// Resolve the api keys of the block-explorers the command uses that are not passed directly
+    async fn resolve_api_keys(&mut self, use_keyring: bool) -> eyre::Result<()> {
+        match self {
+            Command::Copy { source, target, .. } | Command::RetryFailed { source, target, .. } => {
+                source.resolve_api_key(use_keyring).await?;
+                target.resolve_api_key(use_keyring).await
+            }
+            Command::Check { source, target, .. }
+            | Command::Diff { source, target, .. }
+            | Command::Watch { source, target, .. } => {
+                source.resolve_api_key(use_keyring).await?;
+                target.resolve_api_key(use_keyring).await
+            }
+            Command::Export { source, .. } | Command::Fetch { source, .. } => {
+                source.resolve_api_key(use_keyring).await
+            }
+            Command::Resume { target, .. } | Command::Import { target, .. } => {
+                target.resolve_api_key(use_keyring).await
+            }
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Copy the verification of the given contracts from the source to the target block-explorer
+    Copy {
+        #[command(flatten)]
+        contracts: ContractArgs,
+        #[command(flatten)]
+        source: CopySourceArgs,
+        #[command(flatten)]
+        target: TargetArgs,
+        #[command(flatten)]
+        options: Box<CopyArgs>,
+    },
+    /// Copy the verification of all contracts that failed in a previous `copy` run
+    RetryFailed {
+        /// Json report written by the previous run via `--report`
+        #[clap(long)]
+        previous_report: PathBuf,
+        #[command(flatten)]
+        source: CopySourceArgs,
+        #[command(flatten)]
+        target: TargetArgs,
+        #[command(flatten)]
+        options: Box<CopyArgs>,
+    },
+    /// Continue polling the verifications recorded in a GUID file by a previous `copy` run
+    Resume {
+        /// GUID file written by the `copy` command
+        #[clap(long)]
+        guid_file: PathBuf,
+        #[command(flatten)]
+        target: TargetArgs,
+        /// Api of the target block-explorer the verifications were submitted to (detected from
+        /// `--target-url` if not set)
+        #[clap(long)]
+        target_type: Option<TargetType>,
+        #[command(flatten)]
+        retry: RetryArgs,
+        #[command(flatten)]
+        poll: PollArgs,
+        #[command(flatten)]
+        tenderly: TenderlyArgs,
+    },
+    /// Report whether the given contracts are verified on the source and target block-explorer
+    /// without submitting anything
+    Check {
+        #[command(flatten)]
+        contracts: ContractArgs,
+        #[command(flatten)]
+        source: SourceArgs,
+        #[command(flatten)]
+        target: TargetArgs,
+    },
+    /// Compare the verified sources and compiler settings of the given contracts on the source
+    /// and target block-explorer
+    Diff {
+        #[command(flatten)]
+        contracts: ContractArgs,
+        #[command(flatten)]
+        source: SourceArgs,
+        #[command(flatten)]
+        target: TargetArgs,
+    },
+    /// Write the generated standard-json-input and a verification manifest for the given
+    /// contracts to disk instead of submitting them
+    Export {
+        #[command(flatten)]
+        contracts: ContractArgs,
+        #[command(flatten)]
+        source: SourceArgs,
+        /// Directory to write the exported files to (one subdirectory per contract)
+        #[clap(long)]
+        output_dir: PathBuf,
+    },
+    /// Continuously copy the verification of the given contracts (and optionally of newly
+    /// verified contracts on the source) to the target block-explorer
+    Watch {
+        #[command(flatten)]
+        contracts: ContractArgs,
+        #[command(flatten)]
+        source: SourceArgs,
+        #[command(flatten)]
+        target: TargetArgs,
+        /// Seconds to wait between two checks
+        #[clap(long, default_value_t = 300)]
+        interval: u64,
+        /// File to persist the already copied contracts to, so they are not resubmitted after a
+        /// restart
+        #[clap(long)]
+        state: Option<PathBuf>,
+        /// Also copy contracts newly verified on the source explorer (requires a Blockscout
+        /// compatible `listcontracts` endpoint)
+        #[clap(long)]
+        discover_verified: bool,
+        /// Do not display a progress bar for every check (e.g. when the output is logged)
+        #[clap(long)]
+        no_progress_bar: bool,
+    },
+    /// Download the verified source files of the given contracts from the source block-explorer
+    Fetch {
+        #[command(flatten)]
+        contracts: ContractArgs,
+        #[command(flatten)]
+        source: SourceArgs,
+        /// Directory to write the source files to (one subdirectory per contract)
+        #[clap(long)]
+        output_dir: PathBuf,
+    },
+    /// Verify a contract on the target block-explorer from local artifacts
+    Import {
+        #[command(flatten)]
+        artifact: ImportArgs,
+        #[command(flatten)]
+        target: TargetArgs,
+        /// Api of the target block-explorer (detected from `--target-url` if not set)
+        #[clap(long)]
+        target_type: Option<TargetType>,
+        #[command(flatten)]
+        tenderly: TenderlyArgs,
+    },
+}
+
+/// Arguments selecting the local artifacts to import
+#[derive(Args, Debug)]
+struct ImportArgs {
+    /// The address of the deployed contract (taken from the manifest when using `--export-dir`)
+    #[clap(long, required_unless_present = "export_dir")]
+    address: Option<String>,
+
+    /// solidity-standard-json-input file the contract was compiled from
+    #[clap(
+        long,
+        requires_all = ["contract_name", "compiler_version"],
+        conflicts_with_all = ["foundry_artifact", "export_dir"]
+    )]
+    standard_json: Option<PathBuf>,
+
+    /// Foundry build artifact of the contract (e.g. `out/Vault.sol/Vault.json`)
+    #[clap(long, conflicts_with = "export_dir")]
+    foundry_artifact: Option<PathBuf>,
+
+    /// Root of the Foundry project, used to read the sources referenced by `--foundry-artifact`
+    #[clap(long, default_value = ".")]
+    project_root: PathBuf,
+
+    /// Directory of a single contract written by the `export` command
+    #[clap(long, required_unless_present_any = ["standard_json", "foundry_artifact"])]
+    export_dir: Option<PathBuf>,
+
+    /// Fully qualified contract name (e.g. `src/Vault.sol:Vault`)
+    #[clap(long)]
+    contract_name: Option<String>,
+
+    /// Compiler version (e.g. `v0.8.19+commit.7dd6d404`)
+    #[clap(long)]
+    compiler_version: Option<String>,
+
+    /// Hex encoded constructor arguments
+    #[clap(long)]
+    constructor_args: Option<String>,
+}
+
+/// Arguments selecting the contracts to process
+#[derive(Args, Debug)]
+struct ContractArgs {
+    /// The contract's address. Pass `-` to read addresses from stdin.
+    addresses: Vec<String>,
+
+    /// Read additional contract addresses from stdin (one per line)
+    #[clap(long)]
+    stdin: bool,
+
+    /// File containing additional contract addresses (one per line, `#` starts a comment)
+    #[clap(long)]
+    addresses_file: Option<PathBuf>,
+
+    /// CSV file with `address,label` rows, labels are shown next to the address in the output
+    #[clap(long)]
+    addresses_csv: Option<PathBuf>,
+
+    /// Foundry broadcast file (`run-latest.json`) or directory of broadcast files whose deployed
+    /// contracts should be migrated
+    #[clap(long)]
+    broadcast: Option<PathBuf>,
+
+    /// hardhat-deploy deployments directory of a single network (e.g. `deployments/mainnet`)
+    /// whose contracts should be migrated
+    #[clap(long)]
+    hardhat_deployments: Option<PathBuf>,
+
+    /// Uniswap-style token list whose tokens on `--chain-id` should be migrated
+    #[clap(long, requires = "chain_id")]
+    token_list: Option<PathBuf>,
+
+    /// Chain id used to select the tokens from the `--token-list`
+    #[clap(long, requires = "token_list")]
+    chain_id: Option<u64>,
+
+    /// Copy verification of every contract deployed by this address on the source chain
+    #[clap(long)]
+    deployer: Option<String>,
+
+    /// Copy verification of every child contract deployed by this factory on the source chain
+    #[clap(long)]
+    factory: Option<String>,
+
+    /// Signature of the event the factory emits for each deployment, e.g.
+    /// `PoolCreated(address indexed token0,address indexed token1,uint24 indexed fee,int24 tickSpacing,address pool)`.
+    /// If omitted, contracts created in the factory's internal transactions are used.
+    #[clap(long, requires = "factory")]
+    factory_event: Option<String>,
+
+    /// Name or index of the event parameter holding the child address (defaults to the last
+    /// address parameter)
+    #[clap(long, requires = "factory_event")]
+    factory_event_param: Option<String>,
+
+    /// Also process the implementation of every EIP-1967 proxy among the contracts, read from the
+    /// proxy's implementation slot (for beacon proxies the beacon and its implementation)
+    #[clap(long)]
+    follow_proxies: bool,
+
+    /// Also process every implementation the proxies (and beacons) were upgraded to in the past,
+    /// read from their `Upgraded` events, so the target has the full history
+    #[clap(long, requires = "follow_proxies")]
+    include_past_implementations: bool,
+
+    /// Also process the facets of every EIP-2535 diamond among the contracts, read via the
+    /// diamond's `facetAddresses()` loupe function
+    #[clap(long)]
+    diamond_facets: bool,
+
+    /// JSON-RPC endpoint of the source chain the implementation slots and diamond facets are read
+    /// from (read via the source block-explorer's api if not set)
+    #[clap(long)]
+    rpc_url: Option<String>,
+}
+
+/// Arguments configuring how requests failing with a transient error are retried
+#[derive(Args, Debug)]
+struct RetryArgs {
+    /// Number of times a request failing with a network error or 5xx response is retried
+    #[clap(long, default_value_t = 3)]
+    max_retries: u32,
+
+    /// Delay in milliseconds before the first retry, doubled for every subsequent retry
+    #[clap(long, default_value_t = 1000)]
+    retry_base_delay: u64,
+
+    /// Maximum random delay in milliseconds added to every retry
+    #[clap(long, default_value_t = 500)]
+    retry_jitter: u64,
+
+    /// Number of times a request is retried after the block-explorer reported that its rate
+    /// limit was reached, slowing down all requests to that block-explorer each time
+    #[clap(long, default_value_t = 10)]
+    max_rate_limit_retries: u32,
+}
+
+impl RetryArgs {
+    fn policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.max_retries,
+            base_delay: Duration::from_millis(self.retry_base_delay),
+            jitter: Duration::from_millis(self.retry_jitter),
+            max_rate_limit_retries: self.max_rate_limit_retries,
+        }
+    }
+}
+
+/// Arguments configuring how the outcome of submitted verifications is polled
+#[derive(Args, Debug)]
+struct PollArgs {
+    /// Seconds to wait between the first two verification status requests [default: 2, 5 on
+    /// zkSync]
+    #[clap(long)]
+    poll_interval: Option<u64>,
+
+    /// Factor the poll interval is multiplied with after every status request (1 polls at a
+    /// fixed interval) [default: 2]
+    #[clap(long)]
+    poll_backoff: Option<f64>,
+
+    /// Maximum seconds to wait between two verification status requests [default: 30]
+    #[clap(long)]
+    poll_max_interval: Option<u64>,
+
+    /// Number of verification status requests before a verification is considered timed out
+    /// [default: 10, 15 on Blockscout v2, 20 on zkSync]
+    #[clap(long)]
+    poll_retries: Option<u32>,
+
+    /// Seconds to wait after submission before the first verification status request
+    /// [default: 0, 5 on Blockscout v2]
+    #[clap(long)]
+    poll_initial_delay: Option<u64>,
+}
+
+impl PollArgs {
+    /// Options given on the command line, the others suited to the api of the target
+    fn options(&self, target_type: TargetType) -> PollOptions {
+        let defaults = PollOptions::for_target(target_type);
+        PollOptions {
+            interval: self
+                .poll_interval
+                .map_or(defaults.interval, Duration::from_secs),
+            max_attempts: self.poll_retries.unwrap_or(defaults.max_attempts),
+            initial_delay: self
+                .poll_initial_delay
+                .map_or(defaults.initial_delay, Duration::from_secs),
+            backoff: self.poll_backoff.unwrap_or(defaults.backoff),
+            max_interval: self
+                .poll_max_interval
+                .map_or(defaults.max_interval, Duration::from_secs),
+        }
+    }
+}
+
+/// Arguments configuring a Tenderly project the verifications are submitted to in addition to
+/// the target block-explorer
+#[derive(Args, Debug)]
+struct TenderlyArgs {
+    /// Slug of the Tenderly account owning the `--tenderly-project`
+    #[clap(long, requires_all = ["tenderly_project", "tenderly_access_key", "tenderly_chain_id"])]
+    tenderly_account: Option<String>,
+
+    /// Slug of the Tenderly project to also verify the contracts in
+    #[clap(long, requires = "tenderly_account")]
+    tenderly_project: Option<String>,
+
+    /// Access key of the Tenderly account
+    #[clap(long, requires = "tenderly_account")]
+    tenderly_access_key: Option<String>,
+
+    /// Chain id of the network the contracts are deployed on
+    #[clap(long, requires = "tenderly_account")]
+    tenderly_chain_id: Option<u64>,
+}
+
+impl TenderlyArgs {
+    /// The Tenderly project, if configured
+    fn target(&self) -> Option<AdditionalTarget> {
+        Some(AdditionalTarget::tenderly(
+            self.tenderly_access_key.clone()?,
+            self.tenderly_account.as_deref()?,
+            self.tenderly_project.as_deref()?,
+            self.tenderly_chain_id?,
+        ))
+    }
+}
+
+/// Arguments configuring how verifications are copied
+#[derive(Args, Debug)]
+struct CopyArgs {
+    #[command(flatten)]
+    retry: RetryArgs,
+
+    #[command(flatten)]
+    poll: PollArgs,
+
+    #[command(flatten)]
+    tenderly: TenderlyArgs,
+
+    /// File to record the GUIDs of submitted verifications in, so polling can be continued
+    /// with the `resume` command if the run is interrupted
+    #[clap(long)]
+    guid_file: Option<PathBuf>,
+
+    /// Write a report with the outcome, error, timing, compiler version and target explorer
+    /// link of each contract to this file
+    #[clap(long)]
+    report: Option<PathBuf>,
+
+    /// Format of the report: `json` (can be passed to `retry-failed`), `csv`, `markdown` or `html`
+    #[clap(long, default_value = "json", requires = "report")]
+    report_format: ReportFormat,
+
+    /// Progress output: `text` shows a spinner per contract, `jsonl` prints one json event per
+    /// state transition to stdout
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// File recording the contracts completed so far (as json report), contracts found in it are
+    /// skipped so an aborted run continues where it left off
+    #[clap(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// File recording the outcome of every completed contract (one json line each, appended as
+    /// soon as it completes), contracts found in it are skipped so an interrupted run continues
+    /// where it left off
+    #[clap(long)]
+    state: Option<PathBuf>,
+
+    /// Number of contracts processed between two updates of the `--checkpoint` (or claimed at a
+    /// time from the `--queue`)
+    #[clap(long, default_value_t = 100)]
+    chunk_size: usize,
+
+    /// SQLite database used as persistent job queue, the given contracts are added to it and all
+    /// pending contracts in it are processed (multiple workers can share one queue)
+    #[cfg(feature = "sqlite")]
+    #[clap(long, conflicts_with_all = ["checkpoint", "state"])]
+    queue: Option<PathBuf>,
+
+    /// Only submit the first of each group of contracts with identical runtime bytecode (e.g.
+    /// clones deployed by a factory) and let the target explorer match the others to it
+    #[clap(long)]
+    dedupe_bytecode: bool,
+
+    /// Maximum number of contracts processed concurrently [default: 5]
+    #[clap(long)]
+    concurrency: Option<usize>,
+
+    /// Maximum number of requests per second sent to each block-explorer, shared across all
+    /// contracts (e.g. 5 for Etherscan's free tier)
+    #[clap(long)]
+    max_requests_per_second: Option<f64>,
+
+    /// Maximum number of requests per second sent to the source block-explorer, overrides
+    /// `--max-requests-per-second`
+    #[clap(long)]
+    source_max_requests_per_second: Option<f64>,
+
+    /// Maximum number of requests per second sent to the target block-explorer, overrides
+    /// `--max-requests-per-second`
+    #[clap(long)]
+    target_max_requests_per_second: Option<f64>,
+
+    /// Maximum duration spent on a single contract (fetching, submitting and polling) before it
+    /// is reported as timed out, in seconds or with a unit (e.g. `90s`, `5m`)
+    #[clap(long, value_parser = parse_duration)]
+    contract_timeout: Option<Duration>,
+
+    /// Maximum duration of the whole run (e.g. `90s`, `30m` or `2h`), contracts that are not
+    /// done by then are cancelled and reported as skipped
+    #[clap(long, value_parser = parse_duration)]
+    deadline: Option<Duration>,
+
+    /// Include the average time spent fetching, submitting and polling in the summary
+    #[clap(long)]
+    timings: bool,
+
+    /// Url of the target block-explorer's website used for the links to verified contracts
+    /// (derived from `--target-url` if not set)
+    #[clap(long)]
+    target_browser_url: Option<String>,
+
+    /// Api of the target block-explorer: `etherscan`, `blockscout-v2`, `routescan`, `zksync` or
+    /// `oklink`
+    /// (detected from `--target-url` if not set, urls ending in `/api/v2` use the Blockscout v2
+    /// REST api)
+    #[clap(long)]
+    target_type: Option<TargetType>,
+
+    /// External library linked into every contract as `[<path>:]<name>:<address>` (like forge's
+    /// `--libraries`), in addition to the libraries reported by the source block-explorer; can be
+    /// passed multiple times
+    #[clap(long = "library", value_name = "LIBRARY")]
+    libraries: Vec<LinkedLibrary>,
+
+    /// Remapping (e.g. `@openzeppelin/=lib/openzeppelin-contracts/`) used for contracts whose
+    /// compiler settings have none, the remappings of standard-json sources are kept; can be
+    /// passed multiple times
+    #[clap(long = "remapping", value_name = "REMAPPING")]
+    remappings: Vec<String>,
+
+    /// Path of the source file declaring the contract as `<path>[:<name>]` (e.g.
+    /// `contracts/core/Vault.sol:Vault`), overriding the path derived from the sources; applies to
+    /// every contract if the name is omitted; can be passed multiple times
+    #[clap(long = "contract-path", value_name = "PATH")]
+    contract_paths: Vec<ContractPath>,
+
+    /// Mark contracts the source block-explorer reports as proxies as proxies of their
+    /// implementation on the target once verified (`verifyproxycontract` on Etherscan compatible
+    /// targets, Blockscout v2 is only checked to detect them)
+    #[clap(long)]
+    mark_proxies: bool,
+
+    /// Fetch and submit every contract instead of skipping the ones the target reports as
+    /// verified already
+    #[clap(long)]
+    skip_verified_check: bool,
+
+    /// JSON-RPC endpoint of the target chain, contracts without bytecode there fail as not
+    /// deployed before their sources are fetched
+    #[clap(long)]
+    target_rpc_url: Option<String>,
+
+    /// Maximum number of requests per second sent to `--target-rpc-url`, shared across all
+    /// contracts
+    #[clap(long, requires = "target_rpc_url")]
+    target_rpc_max_requests_per_second: Option<f64>,
+
+    /// Fail contracts whose bytecode on the target chain differs from the source chain (ignoring
+    /// the metadata hash and immutables) before submitting them
+    #[clap(long, requires = "target_rpc_url")]
+    compare_bytecode: bool,
+
+    /// Compile every Solidity contract with the matching solc (installed into `~/.svm` if
+    /// missing) and fail contracts whose bytecode differs from the deployed one before submitting
+    /// them, turning rejections of the target into solc's diagnostics
+    #[clap(long)]
+    compile_locally: bool,
+
+    /// Check that the constructor arguments match the end of the transaction that created each
+    /// contract before submitting it (contracts created by factories are not checked)
+    #[clap(long)]
+    check_constructor_args: bool,
+
+    /// Report contracts that are not verified on the source as skipped, they do not fail the
+    /// run (their reason stays `SourceNotVerified`)
+    #[clap(long)]
+    skip_unverified: bool,
+
+    /// Compile every contract with the IR pipeline (`viaIR`), e.g. if the source block-explorer
+    /// does not report the compiler settings
+    #[clap(long)]
+    via_ir: bool,
+
+    /// Hash appended to the bytecode of every contract (`metadata.bytecodeHash`)
+    #[clap(long, value_parser = ["ipfs", "bzzr1", "none"])]
+    bytecode_hash: Option<String>,
+
+    /// EVM version every contract is compiled for (`evmVersion`), e.g. if the source
+    /// block-explorer reports `Default` or a version the target chain does not support
+    #[clap(long, value_parser = [
+        "homestead", "tangerineWhistle", "spuriousDragon", "byzantium", "constantinople",
+        "petersburg", "istanbul", "berlin", "london", "paris", "shanghai", "cancun", "prague",
+    ])]
+    evm_version: Option<String>,
+
+    /// Compiler settings as json (e.g. `{"optimizer": {"details": {"yul": true}}}`) merged into
+    /// the settings of every contract, applied before `--via-ir`, `--bytecode-hash` and
+    /// `--evm-version`
+    #[clap(long, value_name = "JSON", value_parser = parse_json_object)]
+    compiler_settings: Option<serde_json::Value>,
+
+    /// Only print the verification requests that would be submitted without submitting them
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Write the dry-run verification requests to this directory (one `<address>.json` per
+    /// contract) instead of printing them
+    #[clap(long, requires = "dry_run")]
+    dry_run_output: Option<PathBuf>,
+}
+
+impl CopyArgs {
+    /// Concurrency, rate limit and retry policy of reading the proxies and diamonds among the
+    /// contracts, shared with copying them
+    fn related_contracts_options(&self) -> RelatedContractsOptions {
+        let defaults = RelatedContractsOptions::default();
+        RelatedContractsOptions {
+            concurrency: self.concurrency.unwrap_or(defaults.concurrency),
+            max_requests_per_second: self
+                .source_max_requests_per_second
+                .or(self.max_requests_per_second),
+            retry: self.retry.policy(),
+        }
+    }
+
+    /// The compiler settings passed via `--compiler-settings`, `--via-ir`, `--bytecode-hash` and
+    /// `--evm-version`
+    fn settings_overrides(&self) -> Option<serde_json::Value> {
+        let mut overrides = self.compiler_settings.clone();
+        if self.via_ir {
+            overrides.get_or_insert_with(|| serde_json::json!({}))["viaIR"] = true.into();
+        }
+        if let Some(bytecode_hash) = &self.bytecode_hash {
+            overrides.get_or_insert_with(|| serde_json::json!({}))["metadata"]["bytecodeHash"] =
+                bytecode_hash.clone().into();
+        }
+        if let Some(evm_version) = &self.evm_version {
+            overrides.get_or_insert_with(|| serde_json::json!({}))["evmVersion"] =
+                evm_version.clone().into();
+        }
+        overrides
+    }
+}
+
+/// Format of the progress output of the `copy` and `retry-failed` commands
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Spinner per contract
+    Text,
+    /// One json event per line
+    Jsonl,
+}
+
+/// Arguments configuring the source block-explorer
+#[derive(Args, Debug, Clone)]
+struct SourceArgs {
+    #[clap(long, required_unless_present_any = ["source_chain_id", "source_preset"])]
+    source_url: Option<String>,
+    /// The api key for the source block-explorer's api, see `--source-api-key-file` and
+    /// `--use-keyring` to keep it out of the process list. When copying, a comma separated pool
+    /// of keys is rotated between to multiply the rate limit
+    #[clap(long, default_value = "", hide_default_value = true)]
+    source_api_key: String,
+    /// File containing the api key for the source block-explorer's api
+    #[clap(long, conflicts_with = "source_api_key")]
+    source_api_key_file: Option<PathBuf>,
+    /// Chain of the multichain api given as `--source-url` (e.g. Etherscan's V2 api), without
+    /// `--source-url` the block-explorer of the chain is discovered
+    #[clap(long)]
+    source_chain_id: Option<u64>,
+    /// Known block-explorer as `<explorer>:<network>` (e.g. `etherscan:mainnet`) instead of
+    /// `--source-url`
+    #[clap(
+        long = "source",
+        value_name = "PRESET",
+        conflicts_with_all = ["source_url", "source_chain_id"]
+    )]
+    source_preset: Option<String>,
+    /// The api url once resolved, so the block-explorer of a chain is discovered only once
+    #[clap(skip)]
+    resolved_url: Arc<tokio::sync::OnceCell<String>>,
+    /// Settings of the http clients connecting to the block-explorer, see
+    /// [`Cli::configure_explorers`]
+    #[clap(skip)]
+    http: ExplorerHttpOptions,
+}
+
+impl SourceArgs {
+    /// The url of the source block-explorer's api, resolving the `--source` preset and selecting
+    /// the `--source-chain-id` if set
+    async fn url(&self) -> eyre::Result<String> {
+        self.resolved_url
+            .get_or_try_init(|| self.resolve_url())
+            .await
+            .cloned()
+    }
+
+    /// Resolve the url returned by [`SourceArgs::url`]
+    async fn resolve_url(&self) -> eyre::Result<String> {
+        if let Some(preset) = &self.source_preset {
+            return Ok(resolve_explorer_preset(preset)?.api_url.to_string());
+        }
+        match (&self.source_url, self.source_chain_id) {
+            (Some(url), Some(chain_id)) => api_url_with_chain_id(url, chain_id),
+            (None, Some(chain_id)) => discover_api_url(chain_id, &self.http).await.wrap_err(
+                "Failed to discover the block-explorer of the --source-chain-id, pass its --source-url",
+            ),
+            (url, None) => Ok(url.clone().unwrap_or_default()),
+        }
+    }
+
+    /// Read the api key from `--source-api-key-file` or the OS keyring unless passed directly
+    async fn resolve_api_key(&mut self, use_keyring: bool) -> eyre::Result<()> {
+        self.source_api_key = resolve_api_key(
+            &self.source_api_key,
+            self.source_api_key_file.as_deref(),
+            use_keyring,
+            self.url(),
+            "--source-api-key",
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Arguments configuring where the `copy` commands read the verified metadata from
+#[derive(Args, Debug, Clone)]
+struct CopySourceArgs {
+    #[clap(
+        long,
+        required_unless_present_any = ["offline", "sourcify_chain_id", "source_chain_id", "source_preset"]
+    )]
+    source_url: Option<String>,
+    /// The api key for the source block-explorer's api, see `--source-api-key-file` and
+    /// `--use-keyring` to keep it out of the process list. When copying, a comma separated pool
+    /// of keys is rotated between to multiply the rate limit
+    #[clap(long)]
+    source_api_key: Option<String>,
+    /// File containing the api key for the source block-explorer's api
+    #[clap(long, conflicts_with = "source_api_key")]
+    source_api_key_file: Option<PathBuf>,
+    /// Chain of the multichain api given as `--source-url` (e.g. Etherscan's V2 api), without
+    /// `--source-url` the block-explorer of the chain is discovered
+    #[clap(long)]
+    source_chain_id: Option<u64>,
+    /// Known block-explorer as `<explorer>:<network>` (e.g. `etherscan:mainnet`) instead of
+    /// `--source-url`
+    #[clap(
+        long = "source",
+        value_name = "PRESET",
+        conflicts_with_all = ["source_url", "source_chain_id"]
+    )]
+    source_preset: Option<String>,
+
+    /// Read the verified metadata from a directory written by the `export` command instead of
+    /// the source block-explorer, which is not contacted at all
+    #[clap(long, value_name = "EXPORT_DIR", conflicts_with = "sourcify_chain_id")]
+    offline: Option<PathBuf>,
+
+    /// Fetch the verified sources from the Sourcify repository of the given chain instead of the
+    /// source block-explorer, which is not contacted at all
+    #[clap(long, value_name = "CHAIN_ID")]
+    sourcify_chain_id: Option<u64>,
+
+    /// Url of the Sourcify server
+    #[clap(long, default_value = DEFAULT_SOURCIFY_URL, requires = "sourcify_chain_id")]
+    sourcify_url: String,
+
+    /// The api url of the source block-explorer once resolved, shared with [`SourceArgs`]
+    #[clap(skip)]
+    resolved_url: Arc<tokio::sync::OnceCell<String>>,
+    /// Settings of the http clients connecting to the source block-explorer, see
+    /// [`Cli::configure_explorers`]
+    #[clap(skip)]
+    http: ExplorerHttpOptions,
+}
+
+impl CopySourceArgs {
+    /// The source block-explorer, if configured
+    fn explorer(&self) -> Option<SourceArgs> {
+        if self.source_url.is_none()
+            && self.source_chain_id.is_none()
+            && self.source_preset.is_none()
+        {
+            return None;
+        }
+        Some(self.explorer_with_api_key(self.source_api_key.clone()?))
+    }
+
+    /// The source block-explorer with the given api key
+    fn explorer_with_api_key(&self, source_api_key: String) -> SourceArgs {
+        SourceArgs {
+            source_url: self.source_url.clone(),
+            source_api_key,
+            source_api_key_file: self.source_api_key_file.clone(),
+            source_chain_id: self.source_chain_id,
+            source_preset: self.source_preset.clone(),
+            resolved_url: self.resolved_url.clone(),
+            http: self.http.clone(),
+        }
+    }
+
+    /// Read the api key from `--source-api-key-file` or the OS keyring unless passed directly or
+    /// the metadata is not read from the source block-explorer
+    async fn resolve_api_key(&mut self, use_keyring: bool) -> eyre::Result<()> {
+        if self.source_api_key.is_some()
+            || self.offline.is_some()
+            || self.sourcify_chain_id.is_some()
+        {
+            return Ok(());
+        }
+        let mut source = self.explorer_with_api_key(String::new());
+        source.resolve_api_key(use_keyring).await?;
+        self.source_api_key = Some(source.source_api_key);
+        Ok(())
+    }
+
+    /// The url of the source block-explorer's api, empty if not configured
+    async fn url(&self) -> eyre::Result<String> {
+        match self.explorer() {
+            Some(source) => source.url().await,
+            None => Ok(String::new()),
+        }
+    }
+}
+
+/// Arguments configuring the target block-explorer
+#[derive(Args, Debug, Clone)]
+struct TargetArgs {
+    #[clap(long, required_unless_present_any = ["target_chain_id", "target_preset"])]
+    target_url: Option<String>,
+    /// The api key for the target block-explorer's api, see `--target-api-key-file` and
+    /// `--use-keyring` to keep it out of the process list. When copying, a comma separated pool
+    /// of keys is rotated between to multiply the rate limit
+    #[clap(long, default_value = "", hide_default_value = true)]
+    target_api_key: String,
+    /// File containing the api key for the target block-explorer's api
+    #[clap(long, conflicts_with = "target_api_key")]
+    target_api_key_file: Option<PathBuf>,
+    /// Chain of the multichain api given as `--target-url` (e.g. Etherscan's V2 api), without
+    /// `--target-url` the block-explorer of the chain is discovered
+    #[clap(long)]
+    target_chain_id: Option<u64>,
+    /// Known block-explorer as `<explorer>:<network>` (e.g. `blockscout:gnosis`) instead of
+    /// `--target-url`
+    #[clap(
+        long = "target",
+        value_name = "PRESET",
+        conflicts_with_all = ["target_url", "target_chain_id"]
+    )]
+    target_preset: Option<String>,
+    /// The api url once resolved, so the block-explorer of a chain is discovered only once
+    #[clap(skip)]
+    resolved_url: Arc<tokio::sync::OnceCell<String>>,
+    /// Settings of the http clients connecting to the block-explorer, see
+    /// [`Cli::configure_explorers`]
+    #[clap(skip)]
+    http: ExplorerHttpOptions,
+}
+
+impl TargetArgs {
+    /// The url of the target block-explorer's api, resolving the `--target` preset and selecting
+    /// the `--target-chain-id` if set
+    async fn url(&self) -> eyre::Result<String> {
+        self.resolved_url
+            .get_or_try_init(|| self.resolve_url())
+            .await
+            .cloned()
+    }
+
+    /// Resolve the url returned by [`TargetArgs::url`]
+    async fn resolve_url(&self) -> eyre::Result<String> {
+        if let Some(preset) = &self.target_preset {
+            return Ok(resolve_explorer_preset(preset)?.api_url.to_string());
+        }
+        match (&self.target_url, self.target_chain_id) {
+            (Some(url), Some(chain_id)) => api_url_with_chain_id(url, chain_id),
+            (None, Some(chain_id)) => discover_api_url(chain_id, &self.http).await.wrap_err(
+                "Failed to discover the block-explorer of the --target-chain-id, pass its --target-url",
+            ),
+            (url, None) => Ok(url.clone().unwrap_or_default()),
+        }
+    }
+
+    /// Read the api key from `--target-api-key-file` or the OS keyring unless passed directly
+    async fn resolve_api_key(&mut self, use_keyring: bool) -> eyre::Result<()> {
+        self.target_api_key = resolve_api_key(
+            &self.target_api_key,
+            self.target_api_key_file.as_deref(),
+            use_keyring,
+            self.url(),
+            "--target-api-key",
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Api key passed via `flag`, read from the file passed via `<flag>-file` or looked up in the OS
+/// keyring for the block-explorer's api url
+async fn resolve_api_key(
+    api_key: &str,
+    file: Option<&Path>,
+    use_keyring: bool,
+    url: impl std::future::Future<Output = eyre::Result<String>>,
+    flag: &str,
+) -> eyre::Result<String> {
+    if !api_key.is_empty() {
+        return Ok(api_key.to_string());
+    }
+    if let Some(file) = file {
+        return read_api_key_file(file);
+    }
+    if use_keyring {
+        return keyring_api_key(&url.await?).await;
+    }
+    Err(eyre::eyre!(
+        "Pass the api key via {}, {}-file or --use-keyring",
+        flag,
+        flag
+    ))
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let mut cli = Cli::parse();
+    cli.configure_explorers();
+    cli.command.resolve_api_keys(cli.use_keyring).await?;
+    match cli.command {
+        Command::Copy {
+            contracts,
+            source,
+            target,
+            options,
+        } => {
+            let related = options.related_contracts_options();
+            let contracts =
+                collect_contracts(contracts, source.explorer().as_ref(), &source.http, related)
+                    .await?;
+            copy(contracts, source, target, *options).await?;
+        }
+        Command::RetryFailed {
+            previous_report,
+            source,
+            target,
+            options,
+        } => {
+            let contracts = MigrationReport::read_json(previous_report)?.failed_contracts();
+            copy(contracts, source, target, *options).await?;
+        }
+        Command::Resume {
+            guid_file,
+            target,
+            target_type,
+            retry,
+            poll,
+            tenderly,
+        } => {
+            let target_url = target.url().await?;
+            let poll = poll.options(target_type.unwrap_or_else(|| TargetType::detect(&target_url)));
+            let results = contract_verification_migrator_core::resume_etherscan_verification(
+                guid_file,
+                target.target_api_key.clone(),
+                target_url,
+                &target.http,
+                target_type,
+                retry.policy(),
+                poll,
+                &tenderly.target().into_iter().collect::<Vec<_>>(),
+            )
+            .await?;
+            let results: Vec<_> = results
+                .into_iter()
+                .map(|(verification, result, warnings)| {
+                    let result = result.map(|result| (result, warnings));
+                    (Contract::from(verification.address), result)
+                })
+                .collect();
+            print_results(&results, |(result, warnings)| {
+                format!(
+                    "{}{}",
+                    style(format!("{:?}", result)).green(),
+                    format_warnings(warnings)
+                )
+            });
+        }
+        Command::Check {
+            contracts,
+            source,
+            target,
+        } => {
+            let contracts = collect_contracts(
+                contracts,
+                Some(&source),
+                &source.http,
+                RelatedContractsOptions::default(),
+            )
+            .await?;
+            let results = contract_verification_migrator_core::check_verification_status(
+                contracts,
+                source.source_api_key.clone(),
+                source.url().await?,
+                &source.http,
+                target.target_api_key.clone(),
+                target.url().await?,
+                &target.http,
+            )
+            .await?;
+            print_results(&results, |status| {
+                format!(
+                    "source: {} | target: {}",
+                    format_status(&status.source),
+                    format_status(&status.target)
+                )
+            });
+        }
+        Command::Diff {
+            contracts,
+            source,
+            target,
+        } => {
+            let contracts = collect_contracts(
+                contracts,
+                Some(&source),
+                &source.http,
+                RelatedContractsOptions::default(),
+            )
+            .await?;
+            let results = contract_verification_migrator_core::diff_etherscan_verification(
+                contracts,
+                source.source_api_key.clone(),
+                source.url().await?,
+                &source.http,
+                target.target_api_key.clone(),
+                target.url().await?,
+                &target.http,
+            )
+            .await?;
+            print_results(&results, |differences| {
+                if differences.is_empty() {
+                    return format!("{}", style("Identical").green());
+                }
+                let mut output = format!(
+                    "{}",
+                    style(format!("{} difference(s)", differences.len())).yellow()
+                );
+                for difference in differences {
+                    output.push_str(&format!("\n    {}", difference));
+                }
+                output
+            });
+        }
+        Command::Export {
+            contracts,
+            source,
+            output_dir,
+        } => {
+            let contracts = collect_contracts(
+                contracts,
+                Some(&source),
+                &source.http,
+                RelatedContractsOptions::default(),
+            )
+            .await?;
+            let results = contract_verification_migrator_core::export_etherscan_verification(
+                contracts,
+                source.source_api_key.clone(),
+                source.url().await?,
+                &source.http,
+                output_dir,
+            )
+            .await?;
+            print_results(&results, |path| {
+                format!(
+                    "{}",
+                    style(format!("Exported to {}", path.display())).green()
+                )
+            });
+        }
+        Command::Fetch {
+            contracts,
+            source,
+            output_dir,
+        } => {
+            let contracts = collect_contracts(
+                contracts,
+                Some(&source),
+                &source.http,
+                RelatedContractsOptions::default(),
+            )
+            .await?;
+            let results = contract_verification_migrator_core::fetch_etherscan_sources(
+                contracts,
+                source.source_api_key.clone(),
+                source.url().await?,
+                &source.http,
+                output_dir,
+            )
+            .await?;
+            print_results(&results, |path| {
+                format!(
+                    "{}",
+                    style(format!("Downloaded to {}", path.display())).green()
+                )
+            });
+        }
+        Command::Watch {
+            contracts,
+            source,
+            target,
+            interval,
+            state,
+            discover_verified,
+            no_progress_bar,
+        } => {
+            let contracts = collect_contracts(
+                contracts,
+                Some(&source),
+                &source.http,
+                RelatedContractsOptions::default(),
+            )
+            .await?;
+            contract_verification_migrator_core::watch_etherscan_verification(
+                contracts,
+                source.source_api_key.clone(),
+                source.url().await?,
+                &source.http,
+                target.target_api_key.clone(),
+                target.url().await?,
+                &target.http,
+                std::time::Duration::from_secs(interval),
+                state,
+                discover_verified,
+                !no_progress_bar,
+            )
+            .await?;
+        }
+        Command::Import {
+            artifact,
+            target,
+            target_type,
+            tenderly,
+        } => {
+            let verification_request = build_import_request(artifact)?;
+            let address = verification_request.address;
+            let result = contract_verification_migrator_core::import_etherscan_verification(
+                verification_request,
+                target.target_api_key.clone(),
+                target.url().await?,
+                &target.http,
+                target_type,
+                &tenderly.target().into_iter().collect::<Vec<_>>(),
+            )
+            .await;
+            match result {
+                Ok((result, warnings)) => println!(
+                    "{} - {}{}",
+                    address,
+                    style(format!("{:?}", result)).green(),
+                    format_warnings(&warnings)
+                ),
+                Err(err) => {
+                    println!("{} - {}", address, style(format!("Error: {}", err)).red());
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn copy(
+    contracts: Vec<Contract>,
+    source: CopySourceArgs,
+    target: TargetArgs,
+    args: CopyArgs,
+) -> eyre::Result<()> {
+    let events = match args.output {
+        OutputFormat::Text => None,
+        OutputFormat::Jsonl => Some(EventHandler::new(|event| {
+            match serde_json::to_string(event) {
+                Ok(line) => println!("{}", line),
+                Err(err) => eprintln!("Failed to serialize event: {}", err),
+            }
+        })),
+    };
+    let shutdown = ShutdownSignal::new();
+    tokio::spawn(trigger_on_interrupt(shutdown.clone()));
+    let target_type = match args.target_type {
+        Some(target_type) => target_type,
+        None => TargetType::detect(&target.url().await?),
+    };
+    let options = CopyOptions {
+        progress_bar: args.output == OutputFormat::Text,
+        guid_file: args.guid_file.clone(),
+        events,
+        target_browser_url: args.target_browser_url.clone(),
+        target_type: args.target_type,
+        target_explorer: None,
+        source_explorer: None,
+        additional_targets: args.tenderly.target().into_iter().collect(),
+        libraries: args.libraries.clone(),
+        remappings: args.remappings.clone(),
+        settings_overrides: args.settings_overrides(),
+        contract_paths: args.contract_paths.clone(),
+        mark_proxies: args.mark_proxies,
+        skip_verified_check: args.skip_verified_check,
+        target_rpc_url: args.target_rpc_url.clone(),
+        target_rpc_max_requests_per_second: args.target_rpc_max_requests_per_second,
+        compare_bytecode: args.compare_bytecode,
+        compile_locally: args.compile_locally,
+        check_constructor_arguments: args.check_constructor_args,
+        skip_unverified: args.skip_unverified,
+        retry: args.retry.policy(),
+        poll: args.poll.options(target_type),
+        contract_timeout: args.contract_timeout,
+        deadline: args.deadline,
+        shutdown: Some(shutdown.clone()),
+        source_export_dir: source.offline.clone(),
+        source_sourcify: source.sourcify_chain_id.map(|chain_id| SourcifySource {
+            url: source.sourcify_url.clone(),
+            chain_id,
+        }),
+        dedupe_bytecode: args.dedupe_bytecode,
+        concurrency: args.concurrency,
+        source_max_requests_per_second: args
+            .source_max_requests_per_second
+            .or(args.max_requests_per_second),
+        target_max_requests_per_second: args
+            .target_max_requests_per_second
+            .or(args.max_requests_per_second),
+        source_http: source.http.clone(),
+        target_http: target.http.clone(),
+    };
+    if args.dry_run {
+        return dry_run(contracts, &source, &target, &args, &options).await;
+    }
+    #[cfg(feature = "sqlite")]
+    let run = match &args.queue {
+        Some(queue) => copy_from_queue(contracts, queue, &source, &target, &args, &options).await,
+        None => copy_in_chunks(contracts, &source, &target, &args, &options).await,
+    };
+    #[cfg(not(feature = "sqlite"))]
+    let run = copy_in_chunks(contracts, &source, &target, &args, &options).await;
+    let (previous, outcomes) = run?;
+    if let Some(report) = &args.report {
+        let mut full_report = previous;
+        full_report
+            .contracts
+            .extend(MigrationReport::new(&outcomes).contracts);
+        full_report.write(report, args.report_format)?;
+    }
+    let mut summary = format_summary(&outcomes);
+    if args.timings {
+        summary.push_str(&format_timings(&outcomes));
+    }
+    match args.output {
+        OutputFormat::Text => println!("{}", summary),
+        OutputFormat::Jsonl => eprintln!("{}", summary),
+    }
+    if shutdown.is_triggered() {
+        std::process::exit(130);
+    }
+    let failed = outcomes
+        .iter()
+        .any(|outcome| outcome.result.is_err() && !outcome.details.skipped);
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Copy the verification of the contracts in chunks of `--chunk-size`, recording the completed
+/// contracts in the `--checkpoint` after every chunk and in the `--state` after every contract
+///
+/// Returns the report entries of the contracts completed in a previous run and the outcomes of
+/// the contracts processed now.
+async fn copy_in_chunks(
+    contracts: Vec<Contract>,
+    source: &CopySourceArgs,
+    target: &TargetArgs,
+    args: &CopyArgs,
+    options: &CopyOptions,
+) -> eyre::Result<(MigrationReport, Vec<CopyOutcome>)> {
+    // Contracts completed according to the checkpoint of a previous run are not processed again
+    let mut checkpoint = match &args.checkpoint {
+        Some(path) if path.exists() => MigrationReport::read_json(path)?,
+        _ => MigrationReport::default(),
+    };
+    let mut previous = checkpoint.clone();
+    let mut completed: HashSet<String> = previous
+        .contracts
+        .iter()
+        .map(|entry| entry.address.to_lowercase())
+        .collect();
+    if let Some(path) = args.state.as_ref().filter(|path| path.exists()) {
+        let state = MigrationReport::read_jsonl(path)?;
+        previous.contracts.extend(
+            state
+                .contracts
+                .into_iter()
+                .filter(|entry| completed.insert(entry.address.to_lowercase())),
+        );
+    }
+    let contracts: Vec<Contract> = contracts
+        .into_iter()
+        .filter(|contract| !completed.contains(&contract.address.to_lowercase()))
+        .collect();
+    if !previous.contracts.is_empty() {
+        eprintln!(
+            "Skipping {} contracts completed according to the checkpoint or state",
+            previous.contracts.len()
+        );
+    }
+    let chunk_size = match args.checkpoint {
+        Some(_) => args.chunk_size.max(1),
+        None => contracts.len().max(1),
+    };
+    // All chunks share the rate limits, the polling of the target and the deadline of the run
+    let migrator = Migrator::builder()
+        .options(options.clone())
+        .source_url(source.url().await?)
+        .source_api_key(source.source_api_key.clone().unwrap_or_default())
+        .target_url(target.url().await?)
+        .target_api_key(target.target_api_key.clone())
+        .build()?;
+    let mut outcomes = Vec::new();
+    for chunk in contracts.chunks(chunk_size) {
+        let chunk_outcomes = match &args.state {
+            Some(path) => copy_recording_state(chunk, path, &migrator).await?,
+            None => migrator.run(chunk.to_vec()).await?,
+        };
+        if let Some(path) = &args.checkpoint {
+            // Skipped contracts were not processed, so they are retried when restarting
+            checkpoint.contracts.extend(
+                MigrationReport::new(&chunk_outcomes)
+                    .contracts
+                    .into_iter()
+                    .filter(|entry| entry.outcome != ReportOutcome::Skipped),
+            );
+            checkpoint.write_json(path)?;
+        }
+        outcomes.extend(chunk_outcomes);
+        if options
+            .shutdown
+            .as_ref()
+            .is_some_and(ShutdownSignal::is_triggered)
+        {
+            break;
+        }
+    }
+    Ok((previous, outcomes))
+}
+
+/// Copy the verification of the contracts, appending the outcome of every contract to the
+/// `--state` as soon as it completes
+async fn copy_recording_state(
+    contracts: &[Contract],
+    path: &Path,
+    migrator: &Migrator,
+) -> eyre::Result<Vec<CopyOutcome>> {
+    let mut stream = migrator.stream(contracts.to_vec());
+    let mut outcomes = Vec::with_capacity(contracts.len());
+    while let Some(outcome) = stream.next().await {
+        let entry = ContractReport::new(&outcome);
+        // Skipped contracts were not processed, so they are retried when restarting
+        if entry.outcome != ReportOutcome::Skipped {
+            entry.append_jsonl(path)?;
+        }
+        outcomes.push(outcome);
+    }
+    // The stream yields the outcomes in order of completion, restore the order of the input
+    let positions: HashMap<&str, usize> = contracts
+        .iter()
+        .enumerate()
+        .map(|(position, contract)| (contract.address.as_str(), position))
+        .collect();
+    outcomes.sort_by_key(|outcome| positions.get(outcome.contract.address.as_str()).copied());
+    Ok(outcomes)
+}
+
+/// Add the contracts to the `--queue` and copy the verification of all pending contracts in it,
+/// claiming `--chunk-size` contracts at a time
+#[cfg(feature = "sqlite")]
+async fn copy_from_queue(
+    contracts: Vec<Contract>,
+    queue: &std::path::Path,
+    source: &CopySourceArgs,
+    target: &TargetArgs,
+    args: &CopyArgs,
+    options: &CopyOptions,
+) -> eyre::Result<(MigrationReport, Vec<CopyOutcome>)> {
+    let queue = Arc::new(contract_verification_migrator_core::JobQueue::open(queue)?);
+    let added = queue.enqueue(&contracts)?;
+    eprintln!("Added {} contracts to the queue", added);
+    let outcomes = contract_verification_migrator_core::copy_etherscan_verification_from_queue(
+        queue,
+        args.chunk_size,
+        source.source_api_key.clone().unwrap_or_default(),
+        source.url().await?,
+        target.target_api_key.clone(),
+        target.url().await?,
+        options,
+    )
+    .await?;
+    Ok((MigrationReport::default(), outcomes))
+}
+
+/// Trigger the shutdown signal on the first `SIGINT` / `SIGTERM`, so the contracts that completed
+/// so far are still reported, and exit immediately on the second one
+async fn trigger_on_interrupt(shutdown: ShutdownSignal) {
+    for _ in 0..2 {
+        if wait_for_interrupt().await.is_err() {
+            return;
+        }
+        if shutdown.is_triggered() {
+            std::process::exit(130);
+        }
+        eprintln!(
+            "Interrupted, cancelling remaining contracts (interrupt again to exit immediately)"
+        );
+        shutdown.trigger();
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_interrupt() -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_interrupt() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
+
+/// Print the verification requests copying would submit (or write them to `--dry-run-output`),
+/// built by the same steps as when copying
+async fn dry_run(
+    contracts: Vec<Contract>,
+    source: &CopySourceArgs,
+    target: &TargetArgs,
+    args: &CopyArgs,
+    options: &CopyOptions,
+) -> eyre::Result<()> {
+    let results = contract_verification_migrator_core::dry_run_etherscan_verification(
+        contracts,
+        source.source_api_key.clone().unwrap_or_default(),
+        source.url().await?,
+        target.target_api_key.clone(),
+        target.url().await?,
+        options,
+    )
+    .await?;
+    let output_dir = &args.dry_run_output;
+    if let Some(output_dir) = output_dir {
+        std::fs::create_dir_all(output_dir)?;
+    }
+    let mut outputs = Vec::new();
+    for (contract, verification_request) in results {
+        let output = verification_request.and_then(|verification_request| {
+            let Some(verification_request) = verification_request else {
+                return Ok("Already verified on the target".to_string());
+            };
+            let json = serde_json::to_string_pretty(&verification_request)?;
+            match output_dir {
+                Some(output_dir) => {
+                    let path = output_dir.join(format!("{}.json", contract.address));
+                    std::fs::write(&path, json)?;
+                    Ok(format!("Written to {}", path.display()))
+                }
+                None => Ok(json),
+            }
+        });
+        outputs.push((contract, output));
+    }
+    print_results(&outputs, |output| output.clone());
+    Ok(())
+}
+
+fn build_import_request(args: ImportArgs) -> eyre::Result<VerifyContract> {
+    let mut verification_request = if let Some(export_dir) = args.export_dir {
+        contract_verification_migrator_core::verification_request_from_export(export_dir)?
+    } else {
+        let address = args.address.unwrap_or_default();
+        if let Some(standard_json) = args.standard_json {
+            contract_verification_migrator_core::verification_request_from_standard_json(
+                &address,
+                std::fs::read_to_string(standard_json)?,
+                args.contract_name.clone().unwrap_or_default(),
+                args.compiler_version.clone().unwrap_or_default(),
+                args.constructor_args.clone(),
+            )?
+        } else {
+            contract_verification_migrator_core::verification_request_from_foundry_artifact(
+                &address,
+                args.foundry_artifact.unwrap_or_default(),
+                args.project_root,
+                args.constructor_args.clone(),
+            )?
+        }
+    };
+    // Explicitly passed values take precedence over the ones found in the artifacts
+    if let Some(contract_name) = args.contract_name {
+        verification_request.contract_name = contract_name;
+    }
+    if let Some(compiler_version) = args.compiler_version {
+        verification_request.compiler_version = compiler_version;
+    }
+    if let Some(constructor_args) = args.constructor_args {
+        let constructor_args = constructor_args.trim_start_matches("0x").to_string();
+        verification_request.constructor_arguments = Some(constructor_args.clone());
+        verification_request.blockscout_constructor_arguments = Some(constructor_args);
+    }
+    Ok(verification_request)
+}
+
+/// Print the outcome for each contract and exit with a non-zero code if any of them failed
+fn print_results<T>(results: &[(Contract, eyre::Result<T>)], format_ok: impl Fn(&T) -> String) {
+    for (contract, result) in results {
+        match result {
+            Ok(value) => println!("{} - {}", contract.display_name(), format_ok(value)),
+            Err(err) => println!(
+                "{} - {}",
+                contract.display_name(),
+                style(format!("Error: {}", err)).red()
+            ),
+        }
+    }
+    if results.iter().any(|(_, result)| result.is_err()) {
+        std::process::exit(1);
+    }
+}
+
+fn format_summary(outcomes: &[CopyOutcome]) -> String {
+    let succeeded = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.result, Ok(VerificationResult::Success)))
+        .count();
+    let already_verified = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.result, Ok(VerificationResult::AlreadyVerified)))
+        .count();
+    let failed: Vec<_> = outcomes
+        .iter()
+        .filter_map(|outcome| Some((outcome, outcome.result.as_ref().err()?)))
+        .collect();
+    let skipped = failed
+        .iter()
+        .filter(|(outcome, err)| {
+            outcome.details.skipped || FailureReason::of(err) == FailureReason::Skipped
+        })
+        .count();
+    let failed_count = failed.len() - skipped;
+    let mut summary = format!(
+        "\n{} succeeded, {} already verified, {}",
+        style(succeeded).green(),
+        style(already_verified).green(),
+        if failed_count == 0 {
+            style("0 failed".to_string()).green()
+        } else {
+            style(format!("{} failed", failed_count)).red()
+        }
+    );
+    if skipped > 0 {
+        summary.push_str(&format!(
+            ", {}",
+            style(format!("{} skipped", skipped)).yellow()
+        ));
+    }
+    for (outcome, err) in failed {
+        let contract = &outcome.contract;
+        let error = if FailureReason::of(err).is_transient() {
+            format!("Error (transient, retry later): {}", err)
+        } else {
+            format!("Error: {}", err)
+        };
+        summary.push_str(&format!(
+            "\n    {} - {}",
+            contract.display_name(),
+            style(error).red()
+        ));
+    }
+    for outcome in outcomes {
+        for warning in &outcome.details.warnings {
+            summary.push_str(&format!(
+                "\n    {} - {}",
+                outcome.contract.display_name(),
+                style(format!("Warning: {}", warning)).yellow()
+            ));
+        }
+    }
+    summary
+}
+
+/// Indented lines listing the problems that did not fail copying a verification
+fn format_warnings(warnings: &[String]) -> String {
+    warnings
+        .iter()
+        .map(|warning| format!("\n    {}", style(format!("Warning: {}", warning)).yellow()))
+        .collect()
+}
+
+/// Parse credentials given as `<user>:<password>`, the password may contain colons
+fn parse_basic_auth(value: &str) -> Result<(String, String), String> {
+    match value.split_once(':') {
+        Some((user, password)) => Ok((user.to_string(), password.to_string())),
+        None => Err("Expected `<user>:<password>`".to_string()),
+    }
+}
+
+/// Parse a header given as `<key>=<value>`, the value may be empty or contain `=`
+fn parse_header(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err("Expected `<key>=<value>`".to_string()),
+    }
+}
+
+/// Parse a json object (e.g. `{"viaIR": true}`)
+fn parse_json_object(value: &str) -> Result<serde_json::Value, String> {
+    let value: serde_json::Value = serde_json::from_str(value).map_err(|err| err.to_string())?;
+    if !value.is_object() {
+        return Err("Expected a json object".to_string());
+    }
+    Ok(value)
+}
+
+/// Parse a duration given in seconds, minutes or hours (e.g. `90`, `90s`, `30m` or `2h`)
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration {}", value))?;
+    let multiplier = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => {
+            return Err(format!(
+                "Invalid duration unit in {} (expected s, m or h)",
+                value
+            ))
+        }
+    };
+    let seconds = number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Duration {} is too long", value))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+fn format_timings(outcomes: &[CopyOutcome]) -> String {
+    let count = outcomes.len().max(1) as u32;
+    let average = |stage: fn(&StageTimings) -> Duration| {
+        outcomes
+            .iter()
+            .map(|outcome| stage(&outcome.details.timings))
+            .sum::<Duration>()
+            / count
+    };
+    format!(
+        "\nAverage time per contract: check {:?}, fetch {:?}, submit {:?}, poll {:?}",
+        average(|timings| timings.check),
+        average(|timings| timings.fetch),
+        average(|timings| timings.submit),
+        average(|timings| timings.poll),
+    )
+}
+
+fn format_status(status: &ExplorerVerificationStatus) -> String {
+    match status {
+        ExplorerVerificationStatus::Verified {
+            contract_name,
+            compiler_version,
+        } => format!(
+            "{}",
+            style(format!(
+                "Verified ({}, {})",
+                contract_name, compiler_version
+            ))
+            .green()
+        ),
+        ExplorerVerificationStatus::Unverified => format!("{}", style("Not verified").yellow()),
+    }
+}
+
+/// Gather the contracts from all input sources specified on the command line
+async fn collect_contracts(
+    args: ContractArgs,
+    source: Option<&SourceArgs>,
+    source_http: &ExplorerHttpOptions,
+    related: RelatedContractsOptions,
+) -> eyre::Result<Vec<Contract>> {
+    let explorer = || {
+        source.ok_or_else(|| {
+            eyre::eyre!("Discovering contracts requires --source-url and --source-api-key")
+        })
+    };
+    let mut read_stdin = args.stdin;
+    let mut addresses = Vec::new();
+    for address in args.addresses {
+        if address == "-" {
+            read_stdin = true;
+        } else {
+            contract_verification_migrator_core::validate_address(&address)?;
+            addresses.push(address);
+        }
+    }
+    if read_stdin {
+        addresses.extend(contract_verification_migrator_core::read_addresses_stdin()?);
+    }
+    if let Some(addresses_file) = args.addresses_file {
+        addresses.extend(contract_verification_migrator_core::read_addresses_file(
+            addresses_file,
+        )?);
+    }
+    if let Some(deployer) = args.deployer {
+        let source = explorer()?;
+        addresses.extend(
+            contract_verification_migrator_core::find_contracts_deployed_by(
+                deployer,
+                source.source_api_key.clone(),
+                source.url().await?,
+                &source.http,
+            )
+            .await?,
+        );
+    }
+    if let Some(factory) = args.factory {
+        let source = explorer()?;
+        addresses.extend(
+            contract_verification_migrator_core::find_factory_children(
+                factory,
+                args.factory_event,
+                args.factory_event_param,
+                source.source_api_key.clone(),
+                source.url().await?,
+                &source.http,
+            )
+            .await?,
+        );
+    }
+    let mut contracts: Vec<Contract> = addresses.into_iter().map(Contract::from).collect();
+    if let Some(addresses_csv) = args.addresses_csv {
+        contracts.extend(contract_verification_migrator_core::read_contracts_csv(
+            addresses_csv,
+        )?);
+    }
+    if let Some(broadcast) = args.broadcast {
+        contracts.extend(contract_verification_migrator_core::read_foundry_broadcast(
+            broadcast,
+        )?);
+    }
+    if let Some(hardhat_deployments) = args.hardhat_deployments {
+        contracts.extend(
+            contract_verification_migrator_core::read_hardhat_deployments(hardhat_deployments)?,
+        );
+    }
+    if let (Some(token_list), Some(chain_id)) = (args.token_list, args.chain_id) {
+        contracts.extend(contract_verification_migrator_core::read_token_list(
+            token_list, chain_id,
+        )?);
+    }
+    if args.follow_proxies || args.diamond_facets {
+        let reader = match args.rpc_url {
+            Some(rpc_url) => StorageReader::Rpc {
+                url: rpc_url,
+                http: source_http.clone(),
+            },
+            None => {
+                let source = source.ok_or_else(|| {
+                    eyre::eyre!(
+                        "Reading proxies and diamonds requires --rpc-url or --source-url and --source-api-key"
+                    )
+                })?;
+                StorageReader::Explorer {
+                    api_key: source.source_api_key.clone(),
+                    url: source.url().await?,
+                    http: source.http.clone(),
+                }
+            }
+        };
+        if args.diamond_facets {
+            contracts = contract_verification_migrator_core::with_diamond_facets(
+                contracts, &reader, related,
+            )
+            .await?;
+        }
+        if args.follow_proxies {
+            contracts = contract_verification_migrator_core::with_proxy_implementations(
+                contracts, &reader, related,
+            )
+            .await?;
+        }
+        if args.include_past_implementations {
+            contracts = contract_verification_migrator_core::with_past_proxy_implementations(
+                contracts, &reader, related,
+            )
+            .await?;
+        }
+    }
+    Ok(contracts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_duration("2d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration(&format!("{}h", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_poll_options() {
+        let poll = PollArgs {
+            poll_interval: None,
+            poll_backoff: None,
+            poll_max_interval: None,
+            poll_retries: Some(3),
+            poll_initial_delay: None,
+        };
+        assert_eq!(
+            poll.options(TargetType::BlockscoutV2),
+            PollOptions {
+                max_attempts: 3,
+                ..PollOptions::for_target(TargetType::BlockscoutV2)
+            }
+        );
+        assert_eq!(
+            poll.options(TargetType::Etherscan).initial_delay,
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_explorer_http_options() {
+        assert_eq!(
+            parse_basic_auth("admin:pass:word"),
+            Ok(("admin".to_string(), "pass:word".to_string()))
+        );
+        assert!(parse_basic_auth("admin").is_err());
+        assert_eq!(
+            parse_header(" CF-Access-Token =a=b"),
+            Ok(("CF-Access-Token".to_string(), "a=b".to_string()))
+        );
+        assert!(parse_header("=value").is_err());
+
+        // Both block-explorers share the host of Etherscan's multichain api
+        let mut cli = Cli::parse_from([
+            "contract-verification-migrator",
+            "check",
+            "0xE592427A0AEce92De3Edee1F18E0157C05861564",
+            "--source-url",
+            "https://api.etherscan.io/v2/api?chainid=1",
+            "--target-url",
+            "https://api.etherscan.io/v2/api?chainid=10",
+            "--insecure",
+            "target",
+            "--target-basic-auth",
+            "admin:secret",
+            "--proxy",
+            "socks5h://localhost:1080",
+            "--ca-cert",
+            "ca.pem",
+        ]);
+        cli.configure_explorers();
+        let Command::Check { source, target, .. } = cli.command else {
+            panic!("expected the check command");
+        };
+        let proxy = Some("socks5h://localhost:1080".to_string());
+        assert_eq!(
+            source.http,
+            ExplorerHttpOptions {
+                proxy: proxy.clone(),
+                ca_certificates: vec![PathBuf::from("ca.pem")],
+                ..Default::default()
+            }
+        );
+        assert_eq!(target.http.proxy, proxy);
+        assert_eq!(target.http.ca_certificates, source.http.ca_certificates);
+        assert!(target.http.insecure);
+        assert_eq!(
+            target.http.basic_auth,
+            Some(("admin".to_string(), "secret".to_string()))
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::input::Contract;
+use crate::{copy_etherscan_verification, VerificationResult};
+use eyre::Result;
+
+/// Copy contract verification of multiple contracts from one block-explorer to another without
+/// an async runtime of the caller, see [`copy_etherscan_verification`] for the arguments
+///
+/// Spins up a tokio runtime for the duration of the call, so it must not be called from within
+/// an async runtime. Returns an error only if the runtime can not be started.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let results = contract_verification_migrator_core::copy_etherscan_verification_blocking(
+///     vec!["0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string()],
+///     "<YOUR_ETHERSCAN_API_KEY>".to_string(),
+///     "https://api.etherscan.io/api".to_string(),
+///     "<YOUR_BLOCKSCOUT_API_KEY>".to_string(),
+///     "https://eth.blockscout.com/api".to_string(),
+///     false,
+/// )
+/// .unwrap();
+/// ```
+pub fn copy_etherscan_verification_blocking(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    target_api_key: String,
+    target_url: String,
+    progress_bar: bool,
+) -> Result<Vec<(String, Result<VerificationResult>)>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    Ok(runtime.block_on(copy_etherscan_verification(
+        contracts,
+        source_api_key,
+        source_url,
+        target_api_key,
+        target_url,
+        progress_bar,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_etherscan_verification_blocking() {
+        // Invalid urls fail every contract without contacting any block-explorer
+        let results = copy_etherscan_verification_blocking(
+            vec!["0xE592427A0AEce92De3Edee1F18E0157C05861564"],
+            String::new(),
+            "not a url".to_string(),
+            String::new(),
+            "not a url".to_string(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "0xE592427A0AEce92De3Edee1F18E0157C05861564");
+        assert!(results[0].1.is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use crate::failure::{FailureReason, VerificationFailure};
+use crate::http::ExplorerHttpOptions;
+use crate::license::{self, LICENSE_TYPE_PARAMETER};
+use crate::retry::RetryAfter;
+use crate::verification::{browser_url_from_api_url, normalize_compiler_version};
+use crate::vyper;
+use alloy_primitives::Address;
+use eyre::{eyre, Result};
+use foundry_block_explorers::verify::VerifyContract;
+use reqwest::multipart::{Form, Part};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Client for the REST api of Blockscout v2 (e.g. `https://eth.blockscout.com/api/v2`)
+///
+/// Verifications submitted via this api have no GUID, their outcome is determined by polling
+/// whether the contract is verified. Rejected verifications are only reported via websocket, so
+/// they surface as [`FailureReason::Timeout`] once polling gives up.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockscoutV2Client {
+    client: reqwest::Client,
+    api_key: String,
+    /// Url of the api without trailing slash, ending in `/api/v2`
+    api_url: String,
+    /// Url of the website with trailing slash
+    browser_url: String,
+    /// Compiler versions the instance verifies with, fetched once and shared by all clones
+    compiler_versions: Arc<OnceCell<CompilerVersions>>,
+}
+
+/// Compiler versions of the verification config (`/smart-contracts/verification/config`)
+#[derive(Debug, Default, Deserialize)]
+struct CompilerVersions {
+    #[serde(default)]
+    solidity_compiler_versions: Vec<String>,
+    #[serde(default)]
+    vyper_compiler_versions: Vec<String>,
+}
+
+impl CompilerVersions {
+    /// Returns whether the compiler version of the request is available, which is assumed if
+    /// the instance does not report any versions
+    ///
+    /// Versions with and without the commit (e.g. Vyper's `v0.3.10+commit.91361694` and
+    /// `v0.3.10`) name the same release.
+    fn supports(&self, verification_request: &VerifyContract) -> bool {
+        let (versions, compiler_version) = if vyper::is_vyper_request(verification_request) {
+            (
+                &self.vyper_compiler_versions,
+                vyper::blockscout_compiler_version(verification_request),
+            )
+        } else {
+            (
+                &self.solidity_compiler_versions,
+                verification_request.compiler_version.clone(),
+            )
+        };
+        let compiler_version = normalize_compiler_version(&compiler_version);
+        versions.is_empty()
+            || versions.iter().any(|version| {
+                let version = normalize_compiler_version(version);
+                version == compiler_version
+                    || version.starts_with(&format!("{}+", compiler_version))
+                    || compiler_version.starts_with(&format!("{}+", version))
+            })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SmartContract {
+    #[serde(default)]
+    is_verified: Option<bool>,
+    /// Implementations of proxies detected by Blockscout
+    #[serde(default)]
+    implementations: Vec<Implementation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Implementation {
+    address: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageResponse {
+    message: String,
+}
+
+impl BlockscoutV2Client {
+    pub(crate) fn new(
+        api_key: String,
+        api_url: String,
+        browser_url: Option<String>,
+        http_options: &ExplorerHttpOptions,
+    ) -> Result<Self> {
+        let api_url = api_url.trim_end_matches('/').to_string();
+        let browser_url = match browser_url {
+            Some(browser_url) => format!("{}/", browser_url.trim_end_matches('/')),
+            None => browser_url_from_api_url(api_url.strip_suffix("/v2").unwrap_or(&api_url))?,
+        };
+        Ok(BlockscoutV2Client {
+            client: http_options.client()?,
+            api_key,
+            api_url,
+            browser_url,
+            compiler_versions: Arc::default(),
+        })
+    }
+
+    pub(crate) fn address_url(&self, address: Address) -> String {
+        format!("{}address/{:?}", self.browser_url, address)
+    }
+
+    /// Returns whether the instance can verify contracts with the compiler version of the request
+    pub(crate) async fn supports_compiler(
+        &self,
+        verification_request: &VerifyContract,
+    ) -> Result<bool> {
+        let compiler_versions = self
+            .compiler_versions
+            .get_or_try_init(|| async {
+                let response = self
+                    .client
+                    .get(format!(
+                        "{}/smart-contracts/verification/config",
+                        self.api_url
+                    ))
+                    .query(&[("apikey", &self.api_key)])
+                    .send()
+                    .await?;
+                if let Some(retry_after) = RetryAfter::from_response(&response) {
+                    return Err(eyre::Report::from(retry_after));
+                }
+                Ok(response.error_for_status()?.json().await?)
+            })
+            .await?;
+        Ok(compiler_versions.supports(verification_request))
+    }
+
+    /// Returns whether the contract is verified
+    pub(crate) async fn is_verified(&self, address: &str) -> Result<bool> {
+        let response = self
+            .client
+            .get(format!("{}/smart-contracts/{}", self.api_url, address))
+            .query(&[("apikey", &self.api_key)])
+            .send()
+            .await?;
+        if let Some(retry_after) = RetryAfter::from_response(&response) {
+            return Err(retry_after.into());
+        }
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        let contract: SmartContract = response.error_for_status()?.json().await?;
+        Ok(contract.is_verified.unwrap_or(false))
+    }
+
+    /// Request the status of the verification of the contract at `address`, which is the id
+    /// returned for submissions to Blockscout v2
+    ///
+    /// The api does not report rejected verifications, so an unverified contract is reported as
+    /// pending until polling gives up with [`FailureReason::Timeout`].
+    pub(crate) async fn verification_status(&self, address: &str) -> Result<String> {
+        Ok(status_message(self.is_verified(address).await?).to_string())
+    }
+
+    /// Check that Blockscout detected the contract as proxy of the implementation, which it does
+    /// for the standard proxy patterns (e.g. EIP-1967) once the contract is fetched
+    pub(crate) async fn check_proxy(&self, address: &str, implementation: &str) -> Result<()> {
+        let response = self
+            .client
+            .get(format!("{}/smart-contracts/{}", self.api_url, address))
+            .query(&[("apikey", &self.api_key)])
+            .send()
+            .await?;
+        if let Some(retry_after) = RetryAfter::from_response(&response) {
+            return Err(retry_after.into());
+        }
+        let contract: SmartContract = response.error_for_status()?.json().await?;
+        if contract
+            .implementations
+            .iter()
+            .any(|detected| detected.address.eq_ignore_ascii_case(implementation))
+        {
+            return Ok(());
+        }
+        Err(eyre!(
+            "Blockscout did not detect {} as proxy of {}",
+            address,
+            implementation
+        ))
+    }
+
+    /// Submit the solidity- or vyper-standard-json-input of a verification request
+    pub(crate) async fn submit(&self, verification_request: &VerifyContract) -> Result<()> {
+        let address = format!("{:?}", verification_request.address);
+        let license_type = license::blockscout_license_type(
+            verification_request
+                .other
+                .get(LICENSE_TYPE_PARAMETER)
+                .map(String::as_str),
+        );
+        let (endpoint, form) = if vyper::is_vyper_request(verification_request) {
+            let form = Form::new()
+                .text(
+                    "compiler_version",
+                    vyper::blockscout_compiler_version(verification_request),
+                )
+                .text("license_type", license_type);
+            ("vyper-standard-input", form)
+        } else {
+            // Blockscout expects the plain contract name, not the fully qualified one
+            let contract_name = verification_request
+                .contract_name
+                .rsplit(':')
+                .next()
+                .unwrap_or_default();
+            let constructor_arguments = verification_request
+                .constructor_arguments
+                .as_deref()
+                .unwrap_or_default();
+            let form = Form::new()
+                .text(
+                    "compiler_version",
+                    verification_request.compiler_version.clone(),
+                )
+                .text("contract_name", contract_name.to_string())
+                .text("license_type", license_type)
+                .text(
+                    "autodetect_constructor_args",
+                    constructor_arguments.is_empty().to_string(),
+                )
+                .text("constructor_args", constructor_arguments.to_string());
+            ("standard-input", form)
+        };
+        let input = Part::text(verification_request.source.clone())
+            .file_name("input.json")
+            .mime_str("application/json")?;
+        let response = self
+            .client
+            .post(format!(
+                "{}/smart-contracts/{}/verification/via/{}",
+                self.api_url, address, endpoint
+            ))
+            .query(&[("apikey", &self.api_key)])
+            .multipart(form.part("files[0]", input))
+            .send()
+            .await?;
+        if let Some(retry_after) = RetryAfter::from_response(&response) {
+            return Err(retry_after.into());
+        }
+        let status = response.status();
+        if status.is_client_error() {
+            let message = match response.json::<MessageResponse>().await {
+                Ok(response) => response.message,
+                Err(err) => err.to_string(),
+            };
+            // Blockscout rejects invalid requests (e.g. an unknown compiler version) with an
+            // error status, only rate limited requests are worth retrying
+            let reason = match status {
+                StatusCode::TOO_MANY_REQUESTS => FailureReason::RateLimited,
+                _ => FailureReason::TargetRejected,
+            };
+            return Err(VerificationFailure::report(
+                reason,
+                format!("Verification returned non-ok response: {}", message),
+            ));
+        }
+        response.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Etherscan status message of a verification, given whether the contract is verified
+fn status_message(is_verified: bool) -> &'static str {
+    if is_verified {
+        "Pass - Verified"
+    } else {
+        "Pending in queue"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::{verification_status, VerificationResult};
+
+    #[test]
+    fn test_status_message() {
+        assert!(matches!(
+            verification_status(status_message(true)),
+            Some(Ok(VerificationResult::Success))
+        ));
+        // A rejected verification leaves the contract unverified, it is pending until timed out
+        assert!(verification_status(status_message(false)).is_none());
+    }
+
+    #[test]
+    fn test_browser_url() {
+        let client = BlockscoutV2Client::new(
+            String::new(),
+            "https://eth.blockscout.com/api/v2/".to_string(),
+            None,
+            &ExplorerHttpOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(client.api_url, "https://eth.blockscout.com/api/v2");
+        assert_eq!(
+            client.address_url(Address::ZERO),
+            "https://eth.blockscout.com/address/0x0000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_compiler_versions() {
+        let compiler_versions: CompilerVersions = serde_json::from_value(serde_json::json!({
+            "solidity_compiler_versions": ["v0.8.19+commit.7dd6d404"],
+            "vyper_compiler_versions": ["v0.3.10+commit.91361694"],
+        }))
+        .unwrap();
+        let verification_request = |compiler_version: &str| VerifyContract {
+            address: Address::ZERO,
+            source: String::new(),
+            code_format: foundry_block_explorers::verify::CodeFormat::StandardJsonInput,
+            contract_name: "Counter".to_string(),
+            compiler_version: compiler_version.to_string(),
+            optimization_used: None,
+            runs: None,
+            constructor_arguments: None,
+            blockscout_constructor_arguments: None,
+            evm_version: None,
+            other: Default::default(),
+        };
+        assert!(compiler_versions.supports(&verification_request("v0.8.19+commit.7dd6d404")));
+        assert!(!compiler_versions.supports(&verification_request("v0.8.25+commit.b61c2a91")));
+        assert!(compiler_versions.supports(&verification_request("v0.8.19")));
+        assert!(
+            compiler_versions.supports(&verification_request("0.8.19+commit.7dd6d404.Linux.g++"))
+        );
+        assert!(!compiler_versions.supports(&verification_request("v0.8.1")));
+        assert!(compiler_versions.supports(&verification_request("vyper:0.3.10")));
+        assert!(!compiler_versions.supports(&verification_request("vyper:0.3.7")));
+        assert!(CompilerVersions::default().supports(&verification_request("v0.8.25")));
+    }
+}
@@ -0,0 +1,108 @@
+/// Opcode of `PUSH1`, the `PUSH<n>` opcodes follow up to `PUSH32`
+const PUSH1: u8 = 0x60;
+/// Opcode of `PUSH32`, which solc uses to insert the values of immutables
+const PUSH32: u8 = 0x7f;
+
+/// Returns whether two runtime bytecodes belong to the same contract, ignoring the CBOR encoded
+/// metadata solc appends (which contains the hash of the sources) and the values of immutables
+pub(crate) fn same_contract(a: &[u8], b: &[u8]) -> bool {
+    let (a, b) = (strip_metadata(a), strip_metadata(b));
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut position = 0;
+    while position < a.len() {
+        let opcode = a[position];
+        if opcode != b[position] {
+            return false;
+        }
+        let immediate = match opcode {
+            PUSH1..=PUSH32 => usize::from(opcode - PUSH1) + 1,
+            _ => 0,
+        };
+        let end = (position + 1 + immediate).min(a.len());
+        // Immutables are inserted as `PUSH32` with the value set on deployment
+        if opcode != PUSH32 && a[position + 1..end] != b[position + 1..end] {
+            return false;
+        }
+        position = end;
+    }
+    true
+}
+
+/// Constructor arguments appended to the creation code in the input of the transaction that
+/// created the contract, given its runtime bytecode
+///
+/// The creation code ends with the runtime bytecode and thereby its CBOR encoded metadata, so the
+/// arguments are the bytes after the last occurrence of the metadata. `None` if the runtime
+/// bytecode has no metadata, it is not found or the remainder is no abi encoding.
+pub(crate) fn constructor_arguments<'a>(
+    creation_input: &'a [u8],
+    runtime_code: &[u8],
+) -> Option<&'a [u8]> {
+    let metadata = &runtime_code[strip_metadata(runtime_code).len()..];
+    if metadata.is_empty() || creation_input.len() < metadata.len() {
+        return None;
+    }
+    let end = (0..=creation_input.len() - metadata.len())
+        .rev()
+        .find(|&start| creation_input[start..].starts_with(metadata))?
+        + metadata.len();
+    let arguments = &creation_input[end..];
+    arguments.len().is_multiple_of(32).then_some(arguments)
+}
+
+/// Bytecode without the CBOR encoded metadata, whose length is stored in the last two bytes
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    let Some(rest) = code.len().checked_sub(2).map(|end| &code[..end]) else {
+        return code;
+    };
+    let length = usize::from(u16::from_be_bytes([code[rest.len()], code[rest.len() + 1]]));
+    match rest.len().checked_sub(length) {
+        // Metadata is a CBOR map with up to a handful of entries
+        Some(start) if length > 0 && matches!(rest[start], 0xa1..=0xa5) => &rest[..start],
+        _ => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_contract() {
+        let metadata = |hash: u8| {
+            let mut metadata = vec![0xa1, 0x64, b'i', b'p', b'f', b's', 0x42, hash, hash];
+            metadata.extend_from_slice(&(metadata.len() as u16).to_be_bytes());
+            metadata
+        };
+        let code = |immutable: u8, hash: u8| {
+            let mut code = vec![0x60, 0x80, 0x60, 0x40, 0x52, 0x7f];
+            code.extend_from_slice(&[immutable; 32]);
+            code.push(0x00);
+            code.extend(metadata(hash));
+            code
+        };
+        assert!(same_contract(&code(1, 1), &code(1, 1)));
+        assert!(same_contract(&code(1, 1), &code(2, 3)));
+        let mut other = code(1, 1);
+        other[1] = 0x81;
+        assert!(!same_contract(&code(1, 1), &other));
+        assert!(!same_contract(&code(1, 1), &code(1, 1)[1..]));
+        assert!(!same_contract(&code(1, 1), &[]));
+
+        let runtime_code = code(1, 1);
+        let mut creation_input = vec![0x60, 0x80];
+        creation_input.extend(code(0, 1));
+        assert_eq!(
+            constructor_arguments(&creation_input, &runtime_code),
+            Some(&[][..])
+        );
+        creation_input.extend([7; 32]);
+        assert_eq!(
+            constructor_arguments(&creation_input, &runtime_code),
+            Some(&[7; 32][..])
+        );
+        assert_eq!(constructor_arguments(&creation_input, &code(1, 2)), None);
+    }
+}
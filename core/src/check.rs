@@ -0,0 +1,92 @@
+use crate::http::ExplorerHttpOptions;
+use crate::input::Contract;
+use crate::verification::{build_client, fetch_verified_metadata};
+use eyre::Result;
+use foundry_block_explorers::Client;
+
+/// Verification status of a contract on a single block-explorer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplorerVerificationStatus {
+    /// The contract's source code is verified
+    Verified {
+        /// Name of the verified contract
+        contract_name: String,
+        /// Compiler version the contract was verified with
+        compiler_version: String,
+    },
+    /// The contract's source code is not verified
+    Unverified,
+}
+
+/// Verification status of a contract on both the source and the target block-explorer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationStatus {
+    /// Status on the source block-explorer
+    pub source: ExplorerVerificationStatus,
+    /// Status on the target block-explorer
+    pub target: ExplorerVerificationStatus,
+}
+
+/// Check the verification status of multiple contracts on the source and target block-explorer
+///
+/// This is read-only and does not submit anything to either block-explorer.
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts for which to check the verification status
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `target_http` - Settings of the http clients connecting to the target block-explorer
+pub async fn check_verification_status(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+    target_api_key: String,
+    target_url: String,
+    target_http: &ExplorerHttpOptions,
+) -> Result<Vec<(Contract, Result<VerificationStatus>)>> {
+    let source_client = build_client(source_api_key, source_url, source_http)?;
+    let target_client = build_client(target_api_key, target_url, target_http)?;
+    let tasks = contracts
+        .into_iter()
+        .map(Into::into)
+        .map(|contract: Contract| {
+            let source_client = &source_client;
+            let target_client = &target_client;
+            async move {
+                let status = check_contract(&contract.address, source_client, target_client).await;
+                (contract, status)
+            }
+        });
+    Ok(futures::future::join_all(tasks).await)
+}
+
+async fn check_contract(
+    contract_address: &str,
+    source_client: &Client,
+    target_client: &Client,
+) -> Result<VerificationStatus> {
+    let (source, target) = futures::try_join!(
+        explorer_status(contract_address, source_client),
+        explorer_status(contract_address, target_client),
+    )?;
+    Ok(VerificationStatus { source, target })
+}
+
+async fn explorer_status(
+    contract_address: &str,
+    client: &Client,
+) -> Result<ExplorerVerificationStatus> {
+    Ok(
+        match fetch_verified_metadata(client, contract_address).await? {
+            Some(metadata) => ExplorerVerificationStatus::Verified {
+                contract_name: metadata.contract_name,
+                compiler_version: metadata.compiler_version,
+            },
+            None => ExplorerVerificationStatus::Unverified,
+        },
+    )
+}
@@ -0,0 +1,163 @@
+use crate::failure::{FailureReason, VerificationFailure};
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::verify::VerifyContract;
+use semver::Version;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::OnceCell;
+
+/// Paths of the solc binaries by release, installed once per process while different releases
+/// are installed at the same time
+static INSTALLS: Mutex<Vec<(Version, Arc<OnceCell<PathBuf>>)>> = Mutex::new(Vec::new());
+
+/// Release of solc named by the full version (`0.8.19+commit.7dd6d404`), nightly builds can not
+/// be installed
+fn release_version(long_version: &str) -> Result<Version> {
+    let version = Version::parse(long_version)
+        .wrap_err_with(|| format!("Invalid solc version {}", long_version))?;
+    if !version.pre.is_empty() {
+        return Err(eyre!("solc {} is not a release", long_version));
+    }
+    Ok(Version::new(version.major, version.minor, version.patch))
+}
+
+/// Path of the solc binary of the release (`0.8.19+commit.7dd6d404`), installed with svm
+/// (`~/.svm/0.8.19/solc-0.8.19`) unless it is installed there already
+async fn solc_binary(long_version: &str) -> Result<PathBuf> {
+    let version = release_version(long_version)?;
+    let install = {
+        let mut installs = INSTALLS.lock().unwrap_or_else(PoisonError::into_inner);
+        match installs.iter().find(|(installed, _)| *installed == version) {
+            Some((_, install)) => install.clone(),
+            None => {
+                let install = Arc::new(OnceCell::new());
+                installs.push((version.clone(), install.clone()));
+                install
+            }
+        }
+    };
+    install
+        .get_or_try_init(|| async {
+            let binary =
+                svm_lib::version_path(&version.to_string()).join(format!("solc-{}", version));
+            if binary.exists() {
+                return Ok(binary);
+            }
+            svm_lib::install(&version)
+                .await
+                .wrap_err_with(|| format!("Failed to install solc {}", version))
+        })
+        .await
+        .cloned()
+}
+
+/// Standard-json output of solc, failing with what solc printed to stderr (e.g. when it crashed)
+/// if it printed none
+fn solc_output(output: &Output) -> Result<Value> {
+    serde_json::from_slice(&output.stdout).map_err(|_| {
+        eyre!(
+            "solc failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+    })
+}
+
+/// Compile the standard-json of a verification request with the solc release it names and
+/// return the runtime bytecode of the contract
+///
+/// Errors reported by solc fail with [`FailureReason::CompilerMismatch`] and contain solc's
+/// messages.
+pub(crate) async fn compile_runtime_code(verification_request: &VerifyContract) -> Result<Vec<u8>> {
+    let long_version = verification_request
+        .compiler_version
+        .trim_start_matches('v');
+    if !long_version.contains("+commit.") {
+        return Err(eyre!(
+            "Compiler version {} does not name a solc release",
+            verification_request.compiler_version
+        ));
+    }
+    let (path, name) = verification_request
+        .contract_name
+        .rsplit_once(':')
+        .ok_or_else(|| eyre!("Contract name is not fully qualified"))?;
+    let mut input: Value = serde_json::from_str(&verification_request.source)
+        .wrap_err("Sources are not standard-json")?;
+    input["settings"]["outputSelection"] =
+        json!({ path: { name: ["evm.deployedBytecode.object"] } });
+    let binary = solc_binary(long_version).await?;
+    let mut solc = tokio::process::Command::new(&binary)
+        .arg("--standard-json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .wrap_err_with(|| format!("Failed to run {}", binary.display()))?;
+    // Failing to write the input means solc exited early, which its stderr explains
+    if let Some(mut stdin) = solc.stdin.take() {
+        let _ = stdin.write_all(&serde_json::to_vec(&input)?).await;
+    }
+    let output = solc_output(&solc.wait_with_output().await?)?;
+    let errors: Vec<&str> = output["errors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|error| error["severity"] == "error")
+        .filter_map(|error| {
+            error["formattedMessage"]
+                .as_str()
+                .or(error["message"].as_str())
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(VerificationFailure::report(
+            FailureReason::CompilerMismatch,
+            format!("Local compilation failed:\n{}", errors.join("\n")),
+        ));
+    }
+    let object = output["contracts"][path][name]["evm"]["deployedBytecode"]["object"]
+        .as_str()
+        .ok_or_else(|| {
+            eyre!(
+                "Local compilation did not produce {}",
+                verification_request.contract_name
+            )
+        })?;
+    hex::decode(object).wrap_err("Locally compiled bytecode has unlinked libraries")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_version() {
+        assert_eq!(
+            release_version("0.8.19+commit.7dd6d404").unwrap(),
+            Version::new(0, 8, 19)
+        );
+        assert!(release_version("0.4.11-nightly.2017.3.15+commit.0c8c2091").is_err());
+        assert!(release_version("latest").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_solc_output() {
+        use std::os::unix::process::ExitStatusExt;
+        let output = |stdout: &str, stderr: &str| Output {
+            status: std::process::ExitStatus::from_raw(if stdout.is_empty() { 134 } else { 0 }),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        assert_eq!(
+            solc_output(&output("{\"errors\": []}", "")).unwrap(),
+            json!({ "errors": [] })
+        );
+        let err = solc_output(&output("", "Illegal instruction\n")).unwrap_err();
+        assert!(err.to_string().ends_with(": Illegal instruction"));
+    }
+}
@@ -0,0 +1,11 @@
+use eyre::{eyre, Result};
+use foundry_block_explorers::verify::VerifyContract;
+
+/// Stand-in for the local compilation, which fails for every contract
+pub(crate) async fn compile_runtime_code(
+    _verification_request: &VerifyContract,
+) -> Result<Vec<u8>> {
+    Err(eyre!(
+        "Compiling locally requires the local-compile feature"
+    ))
+}
@@ -0,0 +1,335 @@
+use crate::failure::FailureReason;
+use crate::http::ExplorerHttpOptions;
+use crate::rate_limit::RateLimiter;
+use crate::retry::{RetryAfter, RetryPolicy};
+use alloy_primitives::{keccak256, Address, B256};
+use eyre::{eyre, Result};
+use foundry_block_explorers::errors::EtherscanError;
+use foundry_block_explorers::verify::VerifyContract;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{watch, OnceCell};
+
+/// Deterministic deployment proxy (`CREATE2` factory) used by Foundry and Hardhat
+const DETERMINISTIC_DEPLOYER: &str = "0x4e59b44847b379578588920ca78fbf26c0b4956c";
+
+/// Groups contracts by their runtime bytecode so the verification is only submitted for the first
+/// contract of each group
+///
+/// The remaining contracts of a group wait for the first one, as the target block-explorer
+/// typically matches them to it ("similar contracts") once it is verified. Each of them is only
+/// submitted if the target does not report it as verified afterwards.
+#[derive(Default)]
+pub(crate) struct BytecodeDeduplicator {
+    groups: Mutex<HashMap<B256, Group>>,
+}
+
+/// Verification of the implementation of EIP-1167 minimal proxies (clones), the request
+/// submitted for it if known or the reason and message of the failure
+pub(crate) type ImplementationCopy =
+    std::result::Result<Option<VerifyContract>, (FailureReason, String)>;
+
+/// Copies the verification of the implementation of many clones only once, all clones of an
+/// implementation share the outcome of the first one copying it
+#[derive(Default)]
+pub(crate) struct CloneImplementations {
+    copies: Mutex<HashMap<Address, Arc<OnceCell<ImplementationCopy>>>>,
+}
+
+/// Fetches the bytecode and creation transactions of contracts from the source block-explorer
+#[derive(Debug, Clone)]
+pub(crate) struct RuntimeCodeFetcher {
+    client: reqwest::Client,
+    /// The api key for the source block-explorer's api the bytecode is fetched from
+    api_key: String,
+    /// The url of the source block-explorer's api the bytecode is fetched from
+    url: String,
+}
+
+struct Group {
+    done: watch::Receiver<bool>,
+}
+
+/// Role of a contract within the group of contracts sharing its bytecode
+pub(crate) enum BytecodeClaim {
+    /// The contract is the first of its group and has to be verified
+    First(FirstOfGroup),
+    /// A contract with the same bytecode is verified already or in progress
+    Duplicate(Duplicate),
+}
+
+/// Handle of the first contract of a group, used to notify the group once it is done
+pub(crate) struct FirstOfGroup(watch::Sender<bool>);
+
+/// Handle of a contract whose bytecode matches a contract that is verified first
+pub(crate) struct Duplicate {
+    done: watch::Receiver<bool>,
+}
+
+impl RuntimeCodeFetcher {
+    pub(crate) fn new(
+        api_key: String,
+        url: String,
+        http_options: &ExplorerHttpOptions,
+    ) -> Result<Self> {
+        Ok(RuntimeCodeFetcher {
+            client: http_options.client()?,
+            api_key,
+            url,
+        })
+    }
+
+    async fn get(
+        &self,
+        query: &[(&str, &str)],
+        retry: RetryPolicy,
+        limiter: Option<&RateLimiter>,
+    ) -> Result<serde_json::Value> {
+        retry
+            .retry(limiter, || async {
+                let response = self
+                    .client
+                    .get(&self.url)
+                    .query(query)
+                    .query(&[("apikey", &self.api_key)])
+                    .send()
+                    .await?;
+                if let Some(retry_after) = RetryAfter::from_response(&response) {
+                    return Err(retry_after.into());
+                }
+                response
+                    .error_for_status()?
+                    .json::<ExplorerResponse>()
+                    .await?
+                    .into_result()
+            })
+            .await
+    }
+
+    /// Fetch the runtime bytecode of a contract via the `eth_getCode` proxy of the source
+    /// block-explorer's api
+    pub(crate) async fn fetch(
+        &self,
+        address: &str,
+        retry: RetryPolicy,
+        limiter: Option<&RateLimiter>,
+    ) -> Result<Vec<u8>> {
+        let query = [
+            ("module", "proxy"),
+            ("action", "eth_getCode"),
+            ("address", address),
+            ("tag", "latest"),
+        ];
+        let result = self.get(&query, retry, limiter).await?;
+        let code = result
+            .as_str()
+            .and_then(|code| code.strip_prefix("0x"))
+            .ok_or_else(|| eyre!("Failed to fetch bytecode: {}", result))?;
+        Ok(hex::decode(code)?)
+    }
+
+    /// Fetch the input of the transaction that created the contract, `None` if it was created
+    /// by a call to another contract (e.g. a factory) whose input does not end with the
+    /// contract's creation code
+    pub(crate) async fn creation_input(
+        &self,
+        address: &str,
+        retry: RetryPolicy,
+        limiter: Option<&RateLimiter>,
+    ) -> Result<Option<Vec<u8>>> {
+        let query = [
+            ("module", "contract"),
+            ("action", "getcontractcreation"),
+            ("contractaddresses", address),
+        ];
+        let result = self.get(&query, retry, limiter).await?;
+        let tx_hash = result[0]["txHash"]
+            .as_str()
+            .ok_or_else(|| eyre!("Failed to fetch the contract creation: {}", result))?;
+        let query = [
+            ("module", "proxy"),
+            ("action", "eth_getTransactionByHash"),
+            ("txhash", tx_hash),
+        ];
+        let transaction = self.get(&query, retry, limiter).await?;
+        let input = transaction["input"]
+            .as_str()
+            .and_then(|input| input.strip_prefix("0x"))
+            .ok_or_else(|| eyre!("Failed to fetch the creation transaction: {}", transaction))?;
+        let deployed_directly = match transaction["to"].as_str() {
+            None => true,
+            // The deterministic deployment proxy is called with the salt followed by the code
+            Some(to) => to.eq_ignore_ascii_case(DETERMINISTIC_DEPLOYER),
+        };
+        if !deployed_directly {
+            return Ok(None);
+        }
+        Ok(Some(hex::decode(input)?))
+    }
+}
+
+impl BytecodeDeduplicator {
+    /// Register a contract with the given runtime bytecode
+    pub(crate) fn claim(&self, code: &[u8]) -> BytecodeClaim {
+        let mut groups = self.groups.lock().expect("bytecode groups lock poisoned");
+        if let Some(group) = groups.get(&keccak256(code)) {
+            return BytecodeClaim::Duplicate(Duplicate {
+                done: group.done.clone(),
+            });
+        }
+        let (sender, receiver) = watch::channel(false);
+        groups.insert(keccak256(code), Group { done: receiver });
+        BytecodeClaim::First(FirstOfGroup(sender))
+    }
+}
+
+impl CloneImplementations {
+    /// Outcome of copying the implementation, copied with `copy` unless another clone copied it
+    /// already or is copying it
+    ///
+    /// If the clone copying the implementation is cancelled, the next one waiting copies it.
+    pub(crate) async fn copy_once<F, Fut>(
+        &self,
+        implementation: Address,
+        copy: F,
+    ) -> ImplementationCopy
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ImplementationCopy>,
+    {
+        let copied = self
+            .copies
+            .lock()
+            .expect("clone implementations lock poisoned")
+            .entry(implementation)
+            .or_default()
+            .clone();
+        copied.get_or_init(copy).await.clone()
+    }
+}
+
+impl FirstOfGroup {
+    /// Notify the other contracts of the group that the first one is done, whether it was
+    /// verified or not
+    pub(crate) fn finish(self) {
+        self.0.send_replace(true);
+    }
+}
+
+impl Duplicate {
+    /// Wait until the first contract with the same bytecode is done (or was cancelled)
+    pub(crate) async fn wait(mut self) {
+        // The sender is dropped without notifying if the first contract was cancelled
+        let _ = self.done.wait_for(|done| *done).await;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplorerResponse {
+    /// `0` if the request failed, missing for the JSON-RPC proxy
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    result: serde_json::Value,
+}
+
+impl ExplorerResponse {
+    /// Result of the request, the error reported by the api if it failed (e.g. because the rate
+    /// limit was reached or it has no data about the contract)
+    fn into_result(self) -> Result<serde_json::Value> {
+        if self.status.as_deref() == Some("0") {
+            return Err(EtherscanError::ErrorResponse {
+                status: "0".to_string(),
+                message: self.message.unwrap_or_default(),
+                result: Some(
+                    self.result
+                        .as_str()
+                        .map_or_else(|| self.result.to_string(), ToString::to_string),
+                ),
+            }
+            .into());
+        }
+        Ok(self.result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explorer_response() {
+        let response = |value: serde_json::Value| -> ExplorerResponse {
+            serde_json::from_value(value).unwrap()
+        };
+        assert_eq!(
+            response(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x6080" }))
+                .into_result()
+                .unwrap(),
+            "0x6080"
+        );
+        let err = response(serde_json::json!({
+            "status": "0",
+            "message": "No data found",
+            "result": null,
+        }))
+        .into_result()
+        .unwrap_err();
+        assert_eq!(FailureReason::of(&err), FailureReason::Unknown);
+        let err = response(serde_json::json!({
+            "status": "0",
+            "message": "NOTOK",
+            "result": "Max rate limit reached",
+        }))
+        .into_result()
+        .unwrap_err();
+        assert_eq!(FailureReason::of(&err), FailureReason::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_wait_for_first() {
+        let deduplicator = BytecodeDeduplicator::default();
+        let BytecodeClaim::First(first) = deduplicator.claim(&[0x60, 0x80]) else {
+            panic!("first contract of a bytecode must be verified");
+        };
+        let BytecodeClaim::Duplicate(duplicate) = deduplicator.claim(&[0x60, 0x80]) else {
+            panic!("contract with known bytecode must be a duplicate");
+        };
+        let BytecodeClaim::First(cancelled) = deduplicator.claim(&[0x60]) else {
+            panic!("first contract of a bytecode must be verified");
+        };
+
+        let mut wait = Box::pin(duplicate.wait());
+        assert!(futures::poll!(wait.as_mut()).is_pending());
+        first.finish();
+        wait.await;
+
+        let BytecodeClaim::Duplicate(duplicate) = deduplicator.claim(&[0x60]) else {
+            panic!("contract with known bytecode must be a duplicate");
+        };
+        drop(cancelled);
+        duplicate.wait().await;
+    }
+
+    #[tokio::test]
+    async fn test_clone_implementations_copied_once() {
+        let clone_implementations = CloneImplementations::default();
+        let copies = std::sync::atomic::AtomicUsize::new(0);
+        let copy = || async {
+            copies.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Err((FailureReason::TargetRejected, "rejected".to_string()))
+        };
+        let (first, second) = futures::future::join(
+            clone_implementations.copy_once(Address::ZERO, copy),
+            clone_implementations.copy_once(Address::ZERO, copy),
+        )
+        .await;
+        assert_eq!(copies.into_inner(), 1);
+        assert_eq!(first.unwrap_err().0, FailureReason::TargetRejected);
+        assert_eq!(second.unwrap_err().1, "rejected");
+    }
+}
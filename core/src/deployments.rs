@@ -0,0 +1,256 @@
+use crate::input::Contract;
+use eyre::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastFile {
+    transactions: Vec<BroadcastTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastTransaction {
+    transaction_type: String,
+    contract_name: Option<String>,
+    contract_address: Option<String>,
+    #[serde(default)]
+    additional_contracts: Vec<AdditionalContract>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AdditionalContract {
+    address: String,
+}
+
+/// Read the contracts deployed by a forge script from its broadcast file(s)
+///
+/// `path` can either point to a single broadcast file (e.g.
+/// `broadcast/Deploy.s.sol/1/run-latest.json`) or to a directory which is searched recursively
+/// for `run-latest.json` files. Contracts are labeled with their contract name where available.
+///
+/// # Arguments
+/// - `path` - Path to the broadcast file or directory
+pub fn read_foundry_broadcast(path: impl AsRef<Path>) -> Result<Vec<Contract>> {
+    let path = path.as_ref();
+    let files = if path.is_dir() {
+        find_files(path, "run-latest.json")?
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut contracts: Vec<Contract> = Vec::new();
+    for file in files {
+        let content = std::fs::read_to_string(&file)
+            .wrap_err_with(|| format!("Failed to read broadcast file {}", file.display()))?;
+        for contract in parse_foundry_broadcast(&content)
+            .wrap_err_with(|| format!("Failed to parse broadcast file {}", file.display()))?
+        {
+            if !contracts.iter().any(|c| c.address == contract.address) {
+                contracts.push(contract);
+            }
+        }
+    }
+    Ok(contracts)
+}
+
+/// Parse the contracts deployed in a forge script broadcast file
+pub fn parse_foundry_broadcast(content: &str) -> Result<Vec<Contract>> {
+    let broadcast: BroadcastFile = serde_json::from_str(content)?;
+    let mut contracts = Vec::new();
+    for transaction in broadcast.transactions {
+        if transaction.transaction_type.starts_with("CREATE") {
+            if let Some(address) = transaction.contract_address {
+                contracts.push(Contract {
+                    address,
+                    label: transaction.contract_name,
+                    group: None,
+                });
+            }
+        }
+        contracts.extend(
+            transaction
+                .additional_contracts
+                .into_iter()
+                .map(|additional| Contract::from(additional.address)),
+        );
+    }
+    Ok(contracts)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HardhatDeployment {
+    address: String,
+    solc_input_hash: Option<String>,
+}
+
+/// Read the contracts deployed with hardhat-deploy for a single network
+///
+/// `dir` is the network's deployments directory (e.g. `deployments/mainnet`) containing one json
+/// artifact per deployment. Contracts are labeled with their deployment name.
+///
+/// # Arguments
+/// - `dir` - Path to the network's deployments directory
+pub fn read_hardhat_deployments(dir: impl AsRef<Path>) -> Result<Vec<Contract>> {
+    let dir = dir.as_ref();
+    let mut contracts = Vec::new();
+    for (name, path) in hardhat_deployment_files(dir)? {
+        let deployment = read_hardhat_deployment(&path)?;
+        contracts.push(Contract {
+            address: deployment.address,
+            label: Some(name),
+            group: None,
+        });
+    }
+    Ok(contracts)
+}
+
+/// Read the solc standard-json input stored by hardhat-deploy for the given deployment
+///
+/// Returns `None` if the deployment artifact does not reference a stored solc input.
+///
+/// # Arguments
+/// - `dir` - Path to the network's deployments directory
+/// - `deployment_name` - Name of the deployment (the artifact's file name without extension)
+pub fn read_hardhat_solc_input(
+    dir: impl AsRef<Path>,
+    deployment_name: &str,
+) -> Result<Option<String>> {
+    let dir = dir.as_ref();
+    let deployment = read_hardhat_deployment(&dir.join(format!("{}.json", deployment_name)))?;
+    match deployment.solc_input_hash {
+        Some(hash) => {
+            let path = dir.join("solcInputs").join(format!("{}.json", hash));
+            let solc_input = std::fs::read_to_string(&path)
+                .wrap_err_with(|| format!("Failed to read solc input {}", path.display()))?;
+            Ok(Some(solc_input))
+        }
+        None => Ok(None),
+    }
+}
+
+fn read_hardhat_deployment(path: &Path) -> Result<HardhatDeployment> {
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read deployment {}", path.display()))?;
+    serde_json::from_str(&content)
+        .wrap_err_with(|| format!("Failed to parse deployment {}", path.display()))
+}
+
+fn hardhat_deployment_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .wrap_err_with(|| format!("Failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension == "json");
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // Skip hidden bookkeeping files such as `.chainId` or `.migrations.json`
+        if path.is_file() && is_json && !name.starts_with('.') {
+            files.push((name, path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn find_files(dir: &Path, file_name: &str) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .wrap_err_with(|| format!("Failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(find_files(&path, file_name)?);
+        } else if path.file_name().is_some_and(|name| name == file_name) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_hardhat_deployments() {
+        // Unique per process, so concurrent test runs do not share the directory
+        let dir = std::env::temp_dir().join(format!(
+            "cvm-test-hardhat-deployments-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("solcInputs")).unwrap();
+        std::fs::write(dir.join(".chainId"), "1").unwrap();
+        std::fs::write(
+            dir.join("Vault.json"),
+            r#"{"address": "0x5FbDB2315678afecb367f032d93F642f64180aa3", "abi": [], "solcInputHash": "abc"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("solcInputs").join("abc.json"), "{}").unwrap();
+
+        let contracts = read_hardhat_deployments(&dir).unwrap();
+        assert_eq!(
+            contracts,
+            vec![Contract {
+                address: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+                label: Some("Vault".to_string()),
+                group: None,
+            }]
+        );
+        assert_eq!(
+            read_hardhat_solc_input(&dir, "Vault").unwrap(),
+            Some("{}".to_string())
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_foundry_broadcast() {
+        let content = r#"{
+            "transactions": [
+                {
+                    "hash": "0x01",
+                    "transactionType": "CREATE",
+                    "contractName": "Vault",
+                    "contractAddress": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+                    "additionalContracts": []
+                },
+                {
+                    "hash": "0x02",
+                    "transactionType": "CALL",
+                    "contractName": "Vault",
+                    "contractAddress": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+                    "additionalContracts": [
+                        {
+                            "transactionType": "CREATE",
+                            "address": "0xa16E02E87b7454126E5E10d957A927A7F5B5d2be",
+                            "initCode": "0x"
+                        }
+                    ]
+                }
+            ],
+            "receipts": []
+        }"#;
+        let contracts = parse_foundry_broadcast(content).unwrap();
+        assert_eq!(
+            contracts,
+            vec![
+                Contract {
+                    address: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+                    label: Some("Vault".to_string()),
+                    group: None,
+                },
+                Contract::from("0xa16E02E87b7454126E5E10d957A927A7F5B5d2be"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::http::ExplorerHttpOptions;
+use crate::input::Contract;
+use crate::verification::{
+    build_client, fetch_verified_metadata, normalize_compiler_version, source_files,
+};
+use eyre::{eyre, Result};
+use foundry_block_explorers::contract::Metadata;
+use foundry_block_explorers::Client;
+use std::fmt;
+
+/// A difference between the verification of a contract on the source and target block-explorer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationDifference {
+    /// A verification parameter has a different value on both explorers
+    Field {
+        /// Name of the parameter
+        field: String,
+        /// Value on the source explorer
+        source: String,
+        /// Value on the target explorer
+        target: String,
+    },
+    /// A source file is only present on the source explorer
+    FileOnlyInSource(String),
+    /// A source file is only present on the target explorer
+    FileOnlyInTarget(String),
+    /// A source file has different content on both explorers
+    FileContent(String),
+}
+
+impl fmt::Display for VerificationDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationDifference::Field {
+                field,
+                source,
+                target,
+            } => write!(f, "{}: {} (source) != {} (target)", field, source, target),
+            VerificationDifference::FileOnlyInSource(path) => {
+                write!(f, "{}: only present on source", path)
+            }
+            VerificationDifference::FileOnlyInTarget(path) => {
+                write!(f, "{}: only present on target", path)
+            }
+            VerificationDifference::FileContent(path) => write!(f, "{}: content differs", path),
+        }
+    }
+}
+
+/// Compare the verification of multiple contracts on the source and target block-explorer
+///
+/// For each contract the metadata is fetched from both explorers and the differences in source
+/// files, compiler version, optimizer settings and constructor arguments are returned.
+/// Contracts that are not verified on one of the explorers result in an error.
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts to compare
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `target_http` - Settings of the http clients connecting to the target block-explorer
+pub async fn diff_etherscan_verification(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+    target_api_key: String,
+    target_url: String,
+    target_http: &ExplorerHttpOptions,
+) -> Result<Vec<(Contract, Result<Vec<VerificationDifference>>)>> {
+    let source_client = build_client(source_api_key, source_url, source_http)?;
+    let target_client = build_client(target_api_key, target_url, target_http)?;
+    let tasks = contracts
+        .into_iter()
+        .map(Into::into)
+        .map(|contract: Contract| {
+            let source_client = &source_client;
+            let target_client = &target_client;
+            async move {
+                let diff = diff_contract(&contract.address, source_client, target_client).await;
+                (contract, diff)
+            }
+        });
+    Ok(futures::future::join_all(tasks).await)
+}
+
+async fn diff_contract(
+    contract_address: &str,
+    source_client: &Client,
+    target_client: &Client,
+) -> Result<Vec<VerificationDifference>> {
+    let (source, target) = futures::try_join!(
+        fetch_verified_metadata(source_client, contract_address),
+        fetch_verified_metadata(target_client, contract_address),
+    )?;
+    let source = source.ok_or_else(|| eyre!("Contract is not verified on the source explorer"))?;
+    let target = target.ok_or_else(|| eyre!("Contract is not verified on the target explorer"))?;
+    Ok(diff_metadata(&source, &target))
+}
+
+fn diff_metadata(source: &Metadata, target: &Metadata) -> Vec<VerificationDifference> {
+    let fields = [
+        (
+            "contract name",
+            source.contract_name.clone(),
+            target.contract_name.clone(),
+        ),
+        (
+            "compiler version",
+            normalize_compiler_version(&source.compiler_version),
+            normalize_compiler_version(&target.compiler_version),
+        ),
+        (
+            "optimization used",
+            source.optimization_used.to_string(),
+            target.optimization_used.to_string(),
+        ),
+        ("runs", source.runs.to_string(), target.runs.to_string()),
+        (
+            "evm version",
+            source.evm_version.to_lowercase(),
+            target.evm_version.to_lowercase(),
+        ),
+        (
+            "constructor arguments",
+            hex::encode(&source.constructor_arguments),
+            hex::encode(&target.constructor_arguments),
+        ),
+    ];
+    let mut differences: Vec<VerificationDifference> = fields
+        .into_iter()
+        .filter(|(_, source, target)| source != target)
+        .map(|(field, source, target)| VerificationDifference::Field {
+            field: field.to_string(),
+            source,
+            target,
+        })
+        .collect();
+
+    let source_sources = source_files(source);
+    let target_sources = source_files(target);
+    for (path, content) in &source_sources {
+        match target_sources.get(path) {
+            None => differences.push(VerificationDifference::FileOnlyInSource(path.clone())),
+            Some(target_content) if normalize(content) != normalize(target_content) => {
+                differences.push(VerificationDifference::FileContent(path.clone()))
+            }
+            Some(_) => {}
+        }
+    }
+    differences.extend(
+        target_sources
+            .keys()
+            .filter(|path| !source_sources.contains_key(*path))
+            .map(|path| VerificationDifference::FileOnlyInTarget(path.clone())),
+    );
+    differences
+}
+
+fn normalize(content: &str) -> String {
+    content.replace("\r\n", "\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(compiler_version: &str, source_code: &str) -> Metadata {
+        let mut metadata = crate::test_utils::metadata(source_code);
+        metadata.compiler_version = compiler_version.to_string();
+        metadata
+    }
+
+    #[test]
+    fn test_diff_metadata() {
+        let source = metadata("v0.8.19+commit.7dd6d404", "contract Counter {}\r\n");
+        let target = metadata("0.8.19+commit.7dd6d404", "contract Counter {}");
+        assert_eq!(diff_metadata(&source, &target), vec![]);
+
+        let target = metadata("v0.8.20+commit.a1b79de6", "contract Counter { uint256 x; }");
+        assert_eq!(
+            diff_metadata(&source, &target),
+            vec![
+                VerificationDifference::Field {
+                    field: "compiler version".to_string(),
+                    source: "v0.8.19+commit.7dd6d404".to_string(),
+                    target: "v0.8.20+commit.a1b79de6".to_string(),
+                },
+                VerificationDifference::FileContent("Counter.sol".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,523 @@
+use crate::http::ExplorerHttpOptions;
+use crate::retry::{RetryAfter, RetryPolicy};
+use crate::verification::build_client;
+use alloy_primitives::{keccak256, Address};
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::account::{
+    InternalTransaction, InternalTxQueryOption, NormalTransaction, Sort, TxListParams,
+};
+use foundry_block_explorers::block_number::BlockNumber;
+use foundry_block_explorers::errors::EtherscanError;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::future::Future;
+
+/// Maximum number of transactions Etherscan returns for a `txlist` or `txlistinternal` request,
+/// regardless of its pagination
+const MAX_TRANSACTIONS: u64 = 10000;
+
+/// Maximum number of logs Etherscan returns for a `getLogs` request
+const MAX_LOGS: u64 = 1000;
+
+/// Block up to which the transaction lists are requested, beyond the current block of any chain
+const LAST_BLOCK: u64 = 9_999_999_999;
+
+/// Find all contracts deployed by the given address on the source block-explorer
+///
+/// This includes contracts created directly by transactions sent from the deployer as well as
+/// contracts created in internal transactions involving the deployer (e.g. via a factory).
+/// The transactions are requested block range by block range, as explorers return at most 10000
+/// of them per request.
+///
+/// # Arguments
+/// - `deployer` - The address that deployed the contracts
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+pub async fn find_contracts_deployed_by(
+    deployer: String,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+) -> Result<Vec<String>> {
+    let client = build_client(source_api_key, source_url, source_http)?;
+    let deployer: Address = deployer.parse()?;
+    let transactions = fetch_transactions(
+        |params| client.get_transactions(&deployer, Some(params)),
+        |tx: &NormalTransaction| tx.block_number,
+    )
+    .await
+    .wrap_err("Failed to fetch deployer transactions")?;
+    let internal_transactions = fetch_transactions(
+        |params| {
+            client
+                .get_internal_transactions(InternalTxQueryOption::ByAddress(deployer), Some(params))
+        },
+        |tx: &InternalTransaction| tx.block_number,
+    )
+    .await
+    .wrap_err("Failed to fetch deployer internal transactions")?;
+
+    let created = transactions
+        .into_iter()
+        .filter(|tx| tx.is_error == "0")
+        .filter_map(|tx| tx.contract_address)
+        .chain(created_by_internal_transactions(internal_transactions));
+    Ok(unique_addresses(created))
+}
+
+/// Find all child contracts deployed by a factory contract
+///
+/// If an `event_signature` is given (e.g.
+/// `PoolCreated(address indexed token0,address indexed token1,uint24 indexed fee,int24 tickSpacing,address pool)`)
+/// the child addresses are read from the factory's logs of that event. The address is taken from
+/// the parameter named `event_param` or, if not given, the last `address` parameter of the event.
+/// Without an event signature the contracts created in the factory's internal transactions
+/// (CREATE / CREATE2 traces) are used.
+///
+/// # Arguments
+/// - `factory` - The address of the factory contract
+/// - `event_signature` - Optional signature of the event emitted for each deployment
+/// - `event_param` - Optional name or index of the event parameter containing the child address
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+pub async fn find_factory_children(
+    factory: String,
+    event_signature: Option<String>,
+    event_param: Option<String>,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+) -> Result<Vec<String>> {
+    let factory_address: Address = factory.parse()?;
+    let children: Vec<Address> = match event_signature {
+        Some(event_signature) => {
+            let event = EventSignature::parse(&event_signature)?;
+            let location = event.address_location(event_param.as_deref())?;
+            let logs = fetch_logs(
+                &factory,
+                &event.topic0(),
+                &source_api_key,
+                &source_url,
+                source_http,
+            )
+            .await
+            .wrap_err("Failed to fetch factory logs")?;
+            logs.iter()
+                .map(|log| log.read_address(location))
+                .collect::<Result<_>>()?
+        }
+        None => {
+            let client = build_client(source_api_key, source_url, source_http)?;
+            let internal_transactions = fetch_transactions(
+                |params| {
+                    client.get_internal_transactions(
+                        InternalTxQueryOption::ByAddress(factory_address),
+                        Some(params),
+                    )
+                },
+                |tx: &InternalTransaction| tx.block_number,
+            )
+            .await
+            .wrap_err("Failed to fetch factory internal transactions")?;
+            created_by_internal_transactions(internal_transactions)
+                .filter(|child| *child != factory_address)
+                .collect()
+        }
+    };
+    Ok(unique_addresses(children))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedContract {
+    address: String,
+}
+
+/// List the contracts that are verified on the source block-explorer
+///
+/// This uses the `listcontracts` endpoint which is supported by Blockscout but not by etherscan.
+///
+/// # Arguments
+/// - `page` - Page of the result list to fetch (starting at 1)
+/// - `page_size` - Number of contracts per page
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+pub async fn list_verified_contracts(
+    page: u64,
+    page_size: u64,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+) -> Result<Vec<String>> {
+    let contracts: Vec<ListedContract> = fetch_list(
+        &source_url,
+        source_http,
+        &[
+            ("module", "contract"),
+            ("action", "listcontracts"),
+            ("filter", "verified"),
+            ("page", &page.to_string()),
+            ("offset", &page_size.to_string()),
+            ("apikey", &source_api_key),
+        ],
+    )
+    .await
+    .wrap_err("Failed to list verified contracts")?;
+    Ok(contracts
+        .into_iter()
+        .map(|contract| contract.address)
+        .collect())
+}
+
+/// Fetch all transactions of a `txlist` or `txlistinternal` list, sorted by block
+///
+/// Once a response is full the next request starts at the block of its last transaction, see
+/// [`next_start_block`].
+async fn fetch_transactions<T, Fut>(
+    fetch: impl Fn(TxListParams) -> Fut,
+    block_number: impl Fn(&T) -> BlockNumber,
+) -> Result<Vec<T>>
+where
+    Fut: Future<Output = std::result::Result<Vec<T>, EtherscanError>>,
+{
+    let block_number = |tx: &T| {
+        block_number(tx)
+            .as_number()
+            .map(|number| number.to::<u64>())
+    };
+    let mut transactions = Vec::new();
+    let mut start_block = 0;
+    loop {
+        let params = TxListParams::new(start_block, LAST_BLOCK, 1, MAX_TRANSACTIONS, Sort::Asc);
+        let mut page = fetch(params).await?;
+        let next = next_start_block(&mut page, start_block, MAX_TRANSACTIONS, block_number)?;
+        transactions.extend(page);
+        match next {
+            Some(next) => start_block = next,
+            None => return Ok(transactions),
+        }
+    }
+}
+
+/// Block the request following a response with the items of a list starting at `start_block`
+/// starts at, `None` if the response has fewer than `max_items` and is the last one
+///
+/// A full response may end within the block of its last item, so the items of that block are
+/// removed from it and requested again with the next request. Fails if a single block has more
+/// items than fit into one response, which the explorer can not list.
+fn next_start_block<T>(
+    items: &mut Vec<T>,
+    start_block: u64,
+    max_items: u64,
+    block_number: impl Fn(&T) -> Option<u64>,
+) -> Result<Option<u64>> {
+    if (items.len() as u64) < max_items {
+        return Ok(None);
+    }
+    let last_block = items
+        .last()
+        .and_then(&block_number)
+        .ok_or_else(|| eyre!("Explorer returned an item without block number"))?;
+    if last_block <= start_block {
+        return Err(eyre!(
+            "Block {} has more than {} items, which the explorer can not list",
+            last_block,
+            max_items
+        ));
+    }
+    items.retain(|item| block_number(item).is_some_and(|block| block < last_block));
+    Ok(Some(last_block))
+}
+
+fn created_by_internal_transactions(
+    internal_transactions: Vec<InternalTransaction>,
+) -> impl Iterator<Item = Address> {
+    internal_transactions
+        .into_iter()
+        .filter(|tx| tx.is_error == "0" && tx.result_type.starts_with("create"))
+        .filter_map(|tx| tx.contract_address.into())
+}
+
+fn unique_addresses(addresses: impl IntoIterator<Item = Address>) -> Vec<String> {
+    let mut unique: Vec<Address> = Vec::new();
+    for address in addresses {
+        if !unique.contains(&address) {
+            unique.push(address);
+        }
+    }
+    unique.iter().map(ToString::to_string).collect()
+}
+
+/// Position of a value within an event log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogLocation {
+    /// Index into the log's topics (topic 0 being the event selector)
+    Topic(usize),
+    /// Index of the 32 byte word in the log's data
+    DataWord(usize),
+}
+
+#[derive(Debug)]
+struct EventParam {
+    kind: String,
+    indexed: bool,
+    name: Option<String>,
+}
+
+#[derive(Debug)]
+struct EventSignature {
+    name: String,
+    params: Vec<EventParam>,
+}
+
+impl EventSignature {
+    fn parse(signature: &str) -> Result<Self> {
+        let signature = signature.trim().trim_start_matches("event ").trim();
+        let (name, params) = signature
+            .split_once('(')
+            .ok_or_else(|| eyre!("Invalid event signature: {}", signature))?;
+        let params = params
+            .trim_end_matches(';')
+            .strip_suffix(')')
+            .ok_or_else(|| eyre!("Invalid event signature: {}", signature))?;
+        let params = params
+            .split(',')
+            .map(str::trim)
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let mut parts = param.split_whitespace();
+                let kind = parts.next().unwrap_or_default().to_string();
+                let mut indexed = false;
+                let mut name = None;
+                for part in parts {
+                    if part == "indexed" {
+                        indexed = true;
+                    } else {
+                        name = Some(part.to_string());
+                    }
+                }
+                EventParam {
+                    kind,
+                    indexed,
+                    name,
+                }
+            })
+            .collect();
+        Ok(EventSignature {
+            name: name.trim().to_string(),
+            params,
+        })
+    }
+
+    fn topic0(&self) -> String {
+        let types: Vec<&str> = self.params.iter().map(|p| p.kind.as_str()).collect();
+        let canonical = format!("{}({})", self.name, types.join(","));
+        format!("0x{}", hex::encode(keccak256(canonical.as_bytes())))
+    }
+
+    fn address_location(&self, param: Option<&str>) -> Result<LogLocation> {
+        let position = match param {
+            Some(param) => match param.parse::<usize>() {
+                Ok(index) if index < self.params.len() => index,
+                Ok(index) => return Err(eyre!("Event has no parameter at index {}", index)),
+                Err(_) => self
+                    .params
+                    .iter()
+                    .position(|p| p.name.as_deref() == Some(param))
+                    .ok_or_else(|| eyre!("Event has no parameter named {}", param))?,
+            },
+            None => self
+                .params
+                .iter()
+                .rposition(|p| p.kind == "address")
+                .ok_or_else(|| eyre!("Event has no address parameter"))?,
+        };
+        let preceding = &self.params[..position];
+        if self.params[position].indexed {
+            Ok(LogLocation::Topic(
+                1 + preceding.iter().filter(|p| p.indexed).count(),
+            ))
+        } else {
+            Ok(LogLocation::DataWord(
+                preceding.iter().filter(|p| !p.indexed).count(),
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplorerResponse<T> {
+    message: String,
+    result: T,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Log {
+    topics: Vec<String>,
+    data: String,
+    /// Hex encoded number of the block the log was emitted in
+    block_number: String,
+}
+
+impl Log {
+    fn block_number(&self) -> Option<u64> {
+        u64::from_str_radix(self.block_number.trim_start_matches("0x"), 16).ok()
+    }
+
+    fn read_address(&self, location: LogLocation) -> Result<Address> {
+        let word = match location {
+            LogLocation::Topic(index) => self
+                .topics
+                .get(index)
+                .ok_or_else(|| eyre!("Log is missing topic {}", index))?
+                .trim_start_matches("0x")
+                .to_string(),
+            LogLocation::DataWord(index) => {
+                let data = self.data.trim_start_matches("0x");
+                data.get(index * 64..(index + 1) * 64)
+                    .ok_or_else(|| eyre!("Log data is missing word {}", index))?
+                    .to_string()
+            }
+        };
+        Ok(format!("0x{}", &word[word.len().saturating_sub(40)..]).parse()?)
+    }
+}
+
+/// Fetch all logs of the event emitted by the contract, requested block range by block range as
+/// explorers return at most [`MAX_LOGS`] per request, see [`next_start_block`]
+async fn fetch_logs(
+    address: &str,
+    topic0: &str,
+    api_key: &str,
+    url: &str,
+    http_options: &ExplorerHttpOptions,
+) -> Result<Vec<Log>> {
+    let mut logs = Vec::new();
+    let mut start_block = 0;
+    loop {
+        let mut page: Vec<Log> = fetch_list(
+            url,
+            http_options,
+            &[
+                ("module", "logs"),
+                ("action", "getLogs"),
+                ("fromBlock", &start_block.to_string()),
+                ("toBlock", "latest"),
+                ("page", "1"),
+                ("offset", &MAX_LOGS.to_string()),
+                ("address", address),
+                ("topic0", topic0),
+                ("apikey", api_key),
+            ],
+        )
+        .await?;
+        let next = next_start_block(&mut page, start_block, MAX_LOGS, Log::block_number)?;
+        logs.extend(page);
+        match next {
+            Some(next) => start_block = next,
+            None => return Ok(logs),
+        }
+    }
+}
+
+/// Send a request for a list of items to an etherscan compatible api
+///
+/// Explorers report an empty list with a non-ok message (e.g. "No records found"), so an empty
+/// result is treated as success. Transient failures are retried with the default
+/// [`RetryPolicy`], honoring the `Retry-After` header of rate limited responses.
+async fn fetch_list<T: DeserializeOwned>(
+    url: &str,
+    http_options: &ExplorerHttpOptions,
+    query: &[(&str, &str)],
+) -> Result<Vec<T>> {
+    let client = http_options.client()?;
+    let response = RetryPolicy::default()
+        .retry(None, || async {
+            let response = client.get(url).query(query).send().await?;
+            if let Some(retry_after) = RetryAfter::from_response(&response) {
+                return Err(retry_after.into());
+            }
+            Ok(response.error_for_status()?)
+        })
+        .await?;
+    let response: ExplorerResponse<serde_json::Value> = response.json().await?;
+    if response.message != "OK" {
+        if response.result.as_array().is_some_and(Vec::is_empty) {
+            return Ok(Vec::new());
+        }
+        return Err(eyre!(
+            "Explorer returned non-ok response: {}",
+            response.result
+        ));
+    }
+    Ok(serde_json::from_value(response.result)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL_CREATED: &str = "PoolCreated(address indexed token0, address indexed token1, uint24 indexed fee, int24 tickSpacing, address pool)";
+
+    #[test]
+    fn test_event_signature_topic0() {
+        let event = EventSignature::parse(POOL_CREATED).unwrap();
+        assert_eq!(
+            event.topic0(),
+            "0x783cca1c0412dd0d695e784568c96da2e9c22ff989357a2e8b1d9b2b4e6b7118"
+        );
+    }
+
+    #[test]
+    fn test_next_start_block() {
+        let block_number = |block: &u64| Some(*block);
+        let mut items = vec![1, 2];
+        assert_eq!(
+            next_start_block(&mut items, 0, 3, block_number).unwrap(),
+            None
+        );
+        assert_eq!(items, [1, 2]);
+        // The full response may end within block 3, so it is requested again
+        let mut items = vec![1, 3, 3];
+        assert_eq!(
+            next_start_block(&mut items, 0, 3, block_number).unwrap(),
+            Some(3)
+        );
+        assert_eq!(items, [1]);
+        let mut items = vec![3, 3, 3];
+        assert!(next_start_block(&mut items, 3, 3, block_number).is_err());
+    }
+
+    #[test]
+    fn test_log_block_number() {
+        let log: Log = serde_json::from_value(serde_json::json!({
+            "address": "0x1f98431c8ad98523631ae4a59f267346ea31f984",
+            "topics": [],
+            "data": "0x",
+            "blockNumber": "0xbc0e71",
+        }))
+        .unwrap();
+        assert_eq!(log.block_number(), Some(12324465));
+    }
+
+    #[test]
+    fn test_event_address_location() {
+        let event = EventSignature::parse(POOL_CREATED).unwrap();
+        assert_eq!(
+            event.address_location(None).unwrap(),
+            LogLocation::DataWord(1)
+        );
+        assert_eq!(
+            event.address_location(Some("token1")).unwrap(),
+            LogLocation::Topic(2)
+        );
+        assert_eq!(
+            event.address_location(Some("0")).unwrap(),
+            LogLocation::Topic(1)
+        );
+        assert!(event.address_location(Some("fee0")).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::failure::FailureReason;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+/// State transition while copying the verification of a single contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum VerificationEvent {
+    /// The verified metadata was fetched from the source block-explorer
+    Fetched {
+        /// The contract's address
+        address: String,
+        /// Name of the contract as reported by the source block-explorer
+        contract_name: String,
+        /// Compiler version the contract was compiled with
+        compiler_version: String,
+    },
+    /// The verification request was accepted by the target block-explorer
+    Submitted {
+        /// The contract's address
+        address: String,
+        /// The GUID returned by the target block-explorer
+        guid: String,
+    },
+    /// Waiting for the target block-explorer to finish the verification
+    Polling {
+        /// The contract's address
+        address: String,
+        /// The GUID returned by the target block-explorer
+        guid: String,
+    },
+    /// The contract is verified on the target block-explorer
+    Verified {
+        /// The contract's address
+        address: String,
+        /// Whether the contract had been verified on the target already
+        already_verified: bool,
+    },
+    /// Copying the verification failed
+    Failed {
+        /// The contract's address
+        address: String,
+        /// Machine-readable reason of the failure
+        reason: FailureReason,
+        /// Whether the failure is temporary and copying the verification may succeed later
+        transient: bool,
+        /// Error message
+        error: String,
+    },
+}
+
+/// Callback receiving the [`VerificationEvent`]s emitted while copying verifications
+#[derive(Clone)]
+pub struct EventHandler(Arc<dyn Fn(&VerificationEvent) + Send + Sync>);
+
+impl EventHandler {
+    /// Create a handler calling the given function for every event
+    pub fn new(handler: impl Fn(&VerificationEvent) + Send + Sync + 'static) -> Self {
+        EventHandler(Arc::new(handler))
+    }
+
+    /// Create a handler calling the methods of the observer for the matching events
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use contract_verification_migrator_core::{CopyOptions, EventHandler, ProgressObserver};
+    ///
+    /// #[derive(Debug)]
+    /// struct Log;
+    ///
+    /// impl ProgressObserver for Log {
+    ///     fn on_submitted(&self, address: &str, guid: &str) {
+    ///         println!("{} submitted as {}", address, guid);
+    ///     }
+    /// }
+    ///
+    /// let options = CopyOptions {
+    ///     events: Some(EventHandler::observer(Log)),
+    ///     ..Default::default()
+    /// };
+    /// ```
+    pub fn observer(observer: impl ProgressObserver + 'static) -> Self {
+        EventHandler::new(move |event| match event {
+            VerificationEvent::Fetched {
+                address,
+                contract_name,
+                compiler_version,
+            } => observer.on_fetched(address, contract_name, compiler_version),
+            VerificationEvent::Submitted { address, guid } => observer.on_submitted(address, guid),
+            VerificationEvent::Polling { address, guid } => observer.on_poll(address, guid),
+            VerificationEvent::Verified {
+                address,
+                already_verified,
+            } => observer.on_finished(address, Ok(*already_verified)),
+            VerificationEvent::Failed {
+                address,
+                reason,
+                error,
+                ..
+            } => observer.on_finished(address, Err((*reason, error))),
+        })
+    }
+
+    pub(crate) fn emit(&self, event: VerificationEvent) {
+        (self.0)(&event)
+    }
+}
+
+/// Receives the progress of copying the verification of each contract, e.g. to render it in a GUI
+/// instead of the progress bars, see [`EventHandler::observer`]
+///
+/// All methods do nothing by default, so only the relevant ones need to be implemented.
+pub trait ProgressObserver: Send + Sync {
+    /// The verified metadata of the contract was fetched from the source block-explorer
+    fn on_fetched(&self, _address: &str, _contract_name: &str, _compiler_version: &str) {}
+
+    /// The verification request was accepted by the target block-explorer
+    fn on_submitted(&self, _address: &str, _guid: &str) {}
+
+    /// Waiting for the target block-explorer to finish the verification
+    fn on_poll(&self, _address: &str, _guid: &str) {}
+
+    /// Copying the verification finished, with whether the contract had been verified on the
+    /// target already or the reason and message of the failure
+    fn on_finished(&self, _address: &str, _outcome: Result<bool, (FailureReason, &str)>) {}
+}
+
+impl fmt::Debug for EventHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventHandler")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_event() {
+        let event = VerificationEvent::Fetched {
+            address: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+            contract_name: "SwapRouter".to_string(),
+            compiler_version: "v0.7.6+commit.7338295f".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"fetched","address":"0xE592427A0AEce92De3Edee1F18E0157C05861564","contractName":"SwapRouter","compilerVersion":"v0.7.6+commit.7338295f"}"#
+        );
+    }
+
+    #[test]
+    fn test_observer() {
+        #[derive(Default)]
+        struct Recorder(std::sync::Mutex<Vec<String>>);
+
+        impl ProgressObserver for Arc<Recorder> {
+            fn on_submitted(&self, address: &str, guid: &str) {
+                self.0.lock().unwrap().push(format!("{} {}", address, guid));
+            }
+
+            fn on_finished(&self, address: &str, outcome: Result<bool, (FailureReason, &str)>) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {:?}", address, outcome));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let events = EventHandler::observer(recorder.clone());
+        events.emit(VerificationEvent::Submitted {
+            address: "0x1".to_string(),
+            guid: "abc".to_string(),
+        });
+        events.emit(VerificationEvent::Polling {
+            address: "0x1".to_string(),
+            guid: "abc".to_string(),
+        });
+        events.emit(VerificationEvent::Verified {
+            address: "0x1".to_string(),
+            already_verified: false,
+        });
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec!["0x1 abc".to_string(), "0x1 Ok(false)".to_string()]
+        );
+    }
+}
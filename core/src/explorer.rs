@@ -0,0 +1,74 @@
+use alloy_primitives::Address;
+use eyre::Result;
+use foundry_block_explorers::verify::VerifyContract;
+use futures::future::BoxFuture;
+use std::fmt::Debug;
+
+/// Source of verified contracts not built into the migrator, set as
+/// [`CopyOptions::source_explorer`](crate::CopyOptions::source_explorer)
+///
+/// The verification requests go through the same processing as the ones of the built-in sources
+/// (e.g. [`CopyOptions::settings_overrides`](crate::CopyOptions::settings_overrides)) before
+/// they are submitted.
+pub trait SourceExplorer: Debug + Send + Sync {
+    /// Fetch the verification request of the contract at the address, `None` if the contract is
+    /// not verified
+    fn fetch_verification_request<'a>(
+        &'a self,
+        address: &'a str,
+    ) -> BoxFuture<'a, Result<Option<VerifyContract>>>;
+}
+
+/// Block-explorer the verifications are submitted to that is not built into the migrator, set as
+/// [`CopyOptions::target_explorer`](crate::CopyOptions::target_explorer)
+///
+/// # Examples
+///
+/// ```rust
+/// use alloy_primitives::Address;
+/// use contract_verification_migrator_core::TargetExplorer;
+/// use foundry_block_explorers::verify::VerifyContract;
+/// use futures::future::{BoxFuture, FutureExt};
+///
+/// /// Accepts every verification right away
+/// #[derive(Debug)]
+/// struct Accepting;
+///
+/// impl TargetExplorer for Accepting {
+///     fn submit<'a>(
+///         &'a self,
+///         verification_request: &'a VerifyContract,
+///     ) -> BoxFuture<'a, eyre::Result<Option<String>>> {
+///         async move { Ok(Some(format!("{:?}", verification_request.address))) }.boxed()
+///     }
+///
+///     fn verification_status<'a>(&'a self, _id: &'a str) -> BoxFuture<'a, eyre::Result<String>> {
+///         async { Ok("Pass - Verified".to_string()) }.boxed()
+///     }
+///
+///     fn address_url(&self, address: Address) -> String {
+///         format!("https://explorer.example/address/{:?}", address)
+///     }
+/// }
+/// ```
+pub trait TargetExplorer: Debug + Send + Sync {
+    /// Submit a verification request, returning the id to poll its status with or `None` if the
+    /// contract is verified already
+    fn submit<'a>(
+        &'a self,
+        verification_request: &'a VerifyContract,
+    ) -> BoxFuture<'a, Result<Option<String>>>;
+
+    /// Request the status of the verification with the given id in the format of the Etherscan
+    /// compatible api: `Pass - Verified` and `Already Verified` once verified, a status containing
+    /// `Unable to verify` once rejected and any other status while pending
+    fn verification_status<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<String>>;
+
+    /// Link to the contract's page on the block-explorer's website
+    fn address_url(&self, address: Address) -> String;
+
+    /// Returns whether the contract is verified already, which skips fetching its sources
+    fn is_verified(&self, _address: Address) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(async { Ok(false) })
+    }
+}
@@ -0,0 +1,237 @@
+use crate::failure::{FailureReason, VerificationFailure};
+use crate::http::ExplorerHttpOptions;
+use crate::input::Contract;
+use crate::solc;
+use crate::verification::{
+    build_client, convert_metadata_to_verification_request, fetch_verified_metadata, source_files,
+};
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::verify::VerifyContract;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// File name of the generated solidity-standard-json-input within an export directory
+pub const STANDARD_JSON_FILE_NAME: &str = "standard-json-input.json";
+/// File name of the verification manifest within an export directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Verification parameters stored next to the exported standard-json-input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationManifest {
+    /// The contract's address
+    pub address: String,
+    /// Fully qualified name of the contract (`<path>:<name>`)
+    pub contract_name: String,
+    /// Compiler version including the leading `v`
+    pub compiler_version: String,
+    /// Hex encoded constructor arguments
+    pub constructor_arguments: String,
+    /// EVM version the contract was compiled for
+    pub evm_version: Option<String>,
+    /// Whether the optimizer was enabled ("1") or not ("0")
+    pub optimization_used: Option<String>,
+    /// Number of optimizer runs
+    pub runs: Option<String>,
+}
+
+impl VerificationManifest {
+    fn from_verification_request(verification_request: &VerifyContract) -> Self {
+        VerificationManifest {
+            address: verification_request.address.to_string(),
+            contract_name: verification_request.contract_name.clone(),
+            compiler_version: verification_request.compiler_version.clone(),
+            constructor_arguments: verification_request
+                .constructor_arguments
+                .clone()
+                .unwrap_or_default(),
+            evm_version: verification_request.evm_version.clone(),
+            optimization_used: verification_request.optimization_used.clone(),
+            runs: verification_request.runs.clone(),
+        }
+    }
+}
+
+/// Export the verification of multiple contracts from the source block-explorer to disk
+///
+/// For each contract a directory named after its address is created inside `output_dir`
+/// containing the generated solidity-standard-json-input and a manifest with the remaining
+/// verification parameters (compiler version, constructor arguments, evm version, ...).
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts for which to export the verification
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+/// - `output_dir` - Directory to write the exported files to
+pub async fn export_etherscan_verification(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+    output_dir: impl AsRef<Path>,
+) -> Result<Vec<(Contract, Result<PathBuf>)>> {
+    let output_dir = output_dir.as_ref();
+    Ok(
+        prepare_etherscan_verification(contracts, source_api_key, source_url, source_http)
+            .await?
+            .into_iter()
+            .map(|(contract, verification_request)| {
+                let result = verification_request.and_then(|verification_request| {
+                    write_export(&verification_request, &output_dir.join(&contract.address))
+                });
+                (contract, result)
+            })
+            .collect(),
+    )
+}
+
+/// Build the verification requests for multiple contracts without submitting them
+///
+/// This fetches the metadata from the source block-explorer and converts it into the exact
+/// request that would be submitted to the target, which is useful for debugging the conversion.
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts for which to build the verification requests
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+pub async fn prepare_etherscan_verification(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+) -> Result<Vec<(Contract, Result<VerifyContract>)>> {
+    let source_client = build_client(source_api_key, source_url, source_http)?;
+    let tasks = contracts
+        .into_iter()
+        .map(Into::into)
+        .map(|contract: Contract| {
+            let source_client = &source_client;
+            async move {
+                let result = async {
+                    let metadata = fetch_verified_metadata(source_client, &contract.address)
+                        .await?
+                        .ok_or_else(|| {
+                            VerificationFailure::report(
+                                FailureReason::SourceNotVerified,
+                                "Contract is not verified on the source explorer",
+                            )
+                        })?;
+                    let mut verification_request =
+                        convert_metadata_to_verification_request(&contract.address, &metadata)?;
+                    solc::resolve_compiler_version(&mut verification_request, source_http).await;
+                    Ok(verification_request)
+                }
+                .await;
+                (contract, result)
+            }
+        });
+    Ok(futures::future::join_all(tasks).await)
+}
+
+/// Download the verified source files of multiple contracts from the source block-explorer
+///
+/// For each contract a directory named after its address is created inside `output_dir`
+/// containing the verified source files at their original (relative) paths.
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts for which to download the sources
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+/// - `output_dir` - Directory to write the source files to
+pub async fn fetch_etherscan_sources(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+    output_dir: impl AsRef<Path>,
+) -> Result<Vec<(Contract, Result<PathBuf>)>> {
+    let source_client = build_client(source_api_key, source_url, source_http)?;
+    let output_dir = output_dir.as_ref();
+    let tasks = contracts
+        .into_iter()
+        .map(Into::into)
+        .map(|contract: Contract| {
+            let source_client = &source_client;
+            async move {
+                let result = async {
+                    let metadata = fetch_verified_metadata(source_client, &contract.address)
+                        .await?
+                        .ok_or_else(|| {
+                            VerificationFailure::report(
+                                FailureReason::SourceNotVerified,
+                                "Contract is not verified on the source explorer",
+                            )
+                        })?;
+                    let dir = output_dir.join(&contract.address);
+                    for (path, content) in source_files(&metadata) {
+                        let file = dir.join(sanitize_source_path(&path)?);
+                        if let Some(parent) = file.parent() {
+                            std::fs::create_dir_all(parent).wrap_err_with(|| {
+                                format!("Failed to create directory {}", parent.display())
+                            })?;
+                        }
+                        std::fs::write(&file, content)
+                            .wrap_err_with(|| format!("Failed to write {}", file.display()))?;
+                    }
+                    Ok(dir)
+                }
+                .await;
+                (contract, result)
+            }
+        });
+    Ok(futures::future::join_all(tasks).await)
+}
+
+/// Turn a source path reported by the explorer into a relative path that can not escape the
+/// output directory
+fn sanitize_source_path(path: &str) -> Result<PathBuf> {
+    let mut sanitized = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => return Err(eyre!("Invalid source path {}", path)),
+        }
+    }
+    if sanitized.as_os_str().is_empty() {
+        return Err(eyre!("Invalid source path {}", path));
+    }
+    Ok(sanitized)
+}
+
+fn write_export(verification_request: &VerifyContract, dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .wrap_err_with(|| format!("Failed to create directory {}", dir.display()))?;
+    let standard_json: serde_json::Value = serde_json::from_str(&verification_request.source)?;
+    std::fs::write(
+        dir.join(STANDARD_JSON_FILE_NAME),
+        serde_json::to_string_pretty(&standard_json)?,
+    )?;
+    let manifest = VerificationManifest::from_verification_request(verification_request);
+    std::fs::write(
+        dir.join(MANIFEST_FILE_NAME),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    Ok(dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_source_path() {
+        assert_eq!(
+            sanitize_source_path("/@openzeppelin/contracts/token/ERC20/ERC20.sol").unwrap(),
+            PathBuf::from("@openzeppelin/contracts/token/ERC20/ERC20.sol")
+        );
+        assert_eq!(
+            sanitize_source_path("./src/Vault.sol").unwrap(),
+            PathBuf::from("src/Vault.sol")
+        );
+        assert!(sanitize_source_path("../../etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,363 @@
+use crate::retry::RetryAfter;
+use foundry_block_explorers::errors::EtherscanError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Machine-readable reason why copying the verification of a contract failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailureReason {
+    /// The contract is not verified on the source block-explorer
+    SourceNotVerified,
+    /// One of the block-explorers rejected the request because of its rate limit
+    RateLimited,
+    /// The target block-explorer does not support or rejected the compiler version
+    CompilerMismatch,
+    /// The target block-explorer did not finish the verification in time
+    Timeout,
+    /// The target block-explorer rejected the verification (e.g. bytecode mismatch)
+    TargetRejected,
+    /// The target block-explorer is temporarily unable to accept the verification (e.g. because
+    /// its verification queue is full)
+    TargetBusy,
+    /// A request to one of the block-explorers failed on the network level
+    Network,
+    /// The contract was skipped (e.g. because the deadline of the run was reached)
+    Skipped,
+    /// There is no contract at the address on the target chain
+    NotDeployed,
+    /// The bytecode on the target chain differs from the one on the source chain
+    BytecodeMismatch,
+    /// The constructor arguments are not the ones the contract was created with
+    ConstructorArgumentsMismatch,
+    /// The input (e.g. the contract address) was invalid
+    InvalidInput,
+    /// Any other error
+    Unknown,
+}
+
+impl FailureReason {
+    /// Determine the reason of a failure from the error returned for a contract
+    pub fn of(err: &eyre::Report) -> Self {
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<MigrationError>() {
+                return FailureReason::of(err.cause());
+            }
+            if let Some(failure) = cause.downcast_ref::<VerificationFailure>() {
+                return failure.reason;
+            }
+            if cause.is::<RetryAfter>() {
+                return FailureReason::RateLimited;
+            }
+            if let Some(err) = cause.downcast_ref::<EtherscanError>() {
+                return match err {
+                    EtherscanError::ContractCodeNotVerified(_) => FailureReason::SourceNotVerified,
+                    EtherscanError::RateLimitExceeded => FailureReason::RateLimited,
+                    EtherscanError::Reqwest(err) if is_rate_limited_reqwest_error(err) => {
+                        FailureReason::RateLimited
+                    }
+                    EtherscanError::Reqwest(err) if is_transient_reqwest_error(err) => {
+                        FailureReason::Network
+                    }
+                    EtherscanError::BadStatusCode(status)
+                        if status.starts_with("429") || is_rate_limit_message(status) =>
+                    {
+                        FailureReason::RateLimited
+                    }
+                    EtherscanError::BadStatusCode(_) => FailureReason::Network,
+                    EtherscanError::ErrorResponse {
+                        message, result, ..
+                    } if is_rate_limit_message(message)
+                        || result.as_deref().is_some_and(is_rate_limit_message) =>
+                    {
+                        FailureReason::RateLimited
+                    }
+                    // e.g. `Unknown` or `ExecutionFailed` with the message of the block-explorer
+                    err if is_rate_limit_message(&err.to_string()) => FailureReason::RateLimited,
+                    _ => FailureReason::Unknown,
+                };
+            }
+            if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+                return if is_rate_limited_reqwest_error(err) {
+                    FailureReason::RateLimited
+                } else if is_transient_reqwest_error(err) {
+                    FailureReason::Network
+                } else {
+                    FailureReason::Unknown
+                };
+            }
+            if cause.is::<alloy_primitives::hex::FromHexError>() {
+                return FailureReason::InvalidInput;
+            }
+        }
+        FailureReason::Unknown
+    }
+
+    /// Returns whether the failure is temporary, so copying the verification may succeed when
+    /// tried again later
+    ///
+    /// Only requests failing with a transient reason are retried automatically.
+    pub fn is_transient(self) -> bool {
+        matches!(
+            self,
+            FailureReason::RateLimited
+                | FailureReason::Timeout
+                | FailureReason::Network
+                | FailureReason::TargetBusy
+        )
+    }
+
+    /// Classify an error message returned by the target block-explorer
+    pub(crate) fn from_target_message(message: &str) -> Self {
+        let lowercase = message.to_lowercase();
+        if is_rate_limit_message(message) {
+            FailureReason::RateLimited
+        } else if lowercase.contains("queue") || lowercase.contains("try again later") {
+            FailureReason::TargetBusy
+        } else if lowercase.contains("compiler") {
+            FailureReason::CompilerMismatch
+        } else {
+            FailureReason::TargetRejected
+        }
+    }
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Returns whether the message of a block-explorer indicates that its rate limit was reached
+/// (e.g. Etherscan's `Max rate limit reached` or `Max calls per sec rate limit reached (5/sec)`,
+/// or the `Too Many Requests` body of a 429 response)
+fn is_rate_limit_message(message: &str) -> bool {
+    let lowercase = message.to_lowercase();
+    lowercase.contains("rate limit") || lowercase.contains("too many requests")
+}
+
+/// Returns whether a request was rejected with `429 Too Many Requests`
+fn is_rate_limited_reqwest_error(err: &reqwest::Error) -> bool {
+    err.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS)
+}
+
+/// Returns whether a request failed because of the network (e.g. a timeout or connection error)
+/// or because the server failed to handle it (5xx)
+fn is_transient_reqwest_error(err: &reqwest::Error) -> bool {
+    err.is_timeout()
+        || err.is_connect()
+        || err.is_request()
+        || err.status().is_some_and(|status| status.is_server_error())
+}
+
+/// Error with a known [`FailureReason`]
+#[derive(Debug)]
+pub(crate) struct VerificationFailure {
+    reason: FailureReason,
+    message: String,
+}
+
+impl VerificationFailure {
+    pub(crate) fn report(reason: FailureReason, message: impl Into<String>) -> eyre::Report {
+        eyre::Report::new(VerificationFailure {
+            reason,
+            message: message.into(),
+        })
+    }
+}
+
+impl fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for VerificationFailure {}
+
+/// Stage of copying the verification of a contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailureStage {
+    /// Asking the target chain and block-explorer about the contract itself, e.g. whether it is
+    /// deployed, verified already or matched to the implementation of a clone
+    Check,
+    /// Fetching the verified sources from the source (including the creation transaction to
+    /// recover missing constructor arguments from)
+    Fetch,
+    /// Converting the sources into the verification request and checking it
+    Convert,
+    /// Submitting the verification request to the target
+    Submit,
+    /// Polling the target for the outcome of the submitted verification
+    Poll,
+}
+
+impl fmt::Display for FailureStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Error of copying the verification of a contract with the stage it occurred in
+///
+/// The errors returned for contracts are [`eyre::Report`]s (displayed like the underlying
+/// cause), [`MigrationError::of`] finds the error among their causes. The public functions keep
+/// returning [`eyre::Result`] rather than `Result<_, MigrationError>`, so the errors of the setup
+/// (e.g. an invalid api url) and of contracts cancelled or timed out before finishing a stage,
+/// which have no stage, are returned the same way.
+///
+/// # Examples
+///
+/// ```rust
+/// use contract_verification_migrator_core::{FailureStage, MigrationError};
+///
+/// fn describe(err: &eyre::Report) -> String {
+///     match MigrationError::of(err) {
+///         Some(MigrationError::Poll(cause)) => format!("Rejected by the target: {}", cause),
+///         Some(err) => format!("{} failed ({}): {}", err.stage(), err.reason(), err),
+///         None => err.to_string(),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub enum MigrationError {
+    /// Checking the contract on the target chain or block-explorer failed
+    Check(eyre::Report),
+    /// Fetching the verified sources from the source failed
+    Fetch(eyre::Report),
+    /// Converting the sources into a verification request or checking it failed
+    Convert(eyre::Report),
+    /// Submitting the verification request to the target (or an additional target) failed
+    Submit(eyre::Report),
+    /// The target rejected the verification or did not finish it in time
+    Poll(eyre::Report),
+}
+
+impl MigrationError {
+    pub(crate) fn new(stage: FailureStage, cause: eyre::Report) -> Self {
+        match stage {
+            FailureStage::Check => MigrationError::Check(cause),
+            FailureStage::Fetch => MigrationError::Fetch(cause),
+            FailureStage::Convert => MigrationError::Convert(cause),
+            FailureStage::Submit => MigrationError::Submit(cause),
+            FailureStage::Poll => MigrationError::Poll(cause),
+        }
+    }
+
+    /// Wrap the error into the error of the stage, unless it records its stage already
+    pub(crate) fn staged(stage: FailureStage, err: eyre::Report) -> eyre::Report {
+        match MigrationError::of(&err) {
+            Some(_) => err,
+            None => MigrationError::new(stage, err).into(),
+        }
+    }
+
+    /// Find the error among the causes of an error returned for a contract
+    pub fn of(err: &eyre::Report) -> Option<&MigrationError> {
+        err.chain().find_map(|cause| cause.downcast_ref())
+    }
+
+    /// Stage the error occurred in
+    pub fn stage(&self) -> FailureStage {
+        match self {
+            MigrationError::Check(_) => FailureStage::Check,
+            MigrationError::Fetch(_) => FailureStage::Fetch,
+            MigrationError::Convert(_) => FailureStage::Convert,
+            MigrationError::Submit(_) => FailureStage::Submit,
+            MigrationError::Poll(_) => FailureStage::Poll,
+        }
+    }
+
+    /// Machine-readable reason of the error
+    pub fn reason(&self) -> FailureReason {
+        FailureReason::of(self.cause())
+    }
+
+    /// Underlying cause of the error
+    pub fn cause(&self) -> &eyre::Report {
+        match self {
+            MigrationError::Check(cause)
+            | MigrationError::Fetch(cause)
+            | MigrationError::Convert(cause)
+            | MigrationError::Submit(cause)
+            | MigrationError::Poll(cause) => cause,
+        }
+    }
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.cause(), f)
+    }
+}
+
+impl std::error::Error for MigrationError {
+    // Continues with the causes of the underlying error, whose message is displayed as the one
+    // of this error
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause().chain().nth(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::WrapErr;
+
+    #[test]
+    fn test_failure_reason_of() {
+        let err = VerificationFailure::report(FailureReason::Timeout, "Verification timed out");
+        assert_eq!(FailureReason::of(&err), FailureReason::Timeout);
+        assert_eq!(err.to_string(), "Verification timed out");
+
+        let err: eyre::Result<()> = Err(EtherscanError::RateLimitExceeded.into());
+        let err = err.wrap_err("Failed to fetch metadata").unwrap_err();
+        assert_eq!(FailureReason::of(&err), FailureReason::RateLimited);
+
+        assert_eq!(
+            FailureReason::of(&eyre::eyre!("Something went wrong")),
+            FailureReason::Unknown
+        );
+        assert_eq!(
+            FailureReason::from_target_message("Invalid compiler version"),
+            FailureReason::CompilerMismatch
+        );
+
+        let busy = FailureReason::from_target_message("Verification queue is full");
+        assert_eq!(busy, FailureReason::TargetBusy);
+        assert!(busy.is_transient());
+        assert!(!FailureReason::TargetRejected.is_transient());
+        assert!(FailureReason::of(
+            &EtherscanError::BadStatusCode("502 Bad Gateway".to_string()).into()
+        )
+        .is_transient());
+
+        for err in [
+            EtherscanError::BadStatusCode("429 Too Many Requests".to_string()),
+            EtherscanError::Unknown("Max calls per sec rate limit reached (5/sec)".to_string()),
+            EtherscanError::ErrorResponse {
+                status: "0".to_string(),
+                message: "NOTOK".to_string(),
+                result: Some("Max rate limit reached".to_string()),
+            },
+        ] {
+            let reason = FailureReason::of(&err.into());
+            assert_eq!(reason, FailureReason::RateLimited);
+            assert!(reason.is_transient());
+        }
+    }
+
+    #[test]
+    fn test_migration_error() {
+        let cause = VerificationFailure::report(FailureReason::TargetRejected, "Unable to verify.")
+            .wrap_err("Verification failed");
+        let err = MigrationError::staged(FailureStage::Poll, cause);
+        let err = MigrationError::staged(FailureStage::Submit, err);
+        let migration_error = MigrationError::of(&err).unwrap();
+        assert_eq!(migration_error.stage(), FailureStage::Poll);
+        assert_eq!(migration_error.reason(), FailureReason::TargetRejected);
+        assert_eq!(FailureReason::of(&err), FailureReason::TargetRejected);
+        assert_eq!(
+            format!("{:#}", err),
+            "Verification failed: Unable to verify."
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::export::{VerificationManifest, MANIFEST_FILE_NAME, STANDARD_JSON_FILE_NAME};
+use crate::http::ExplorerHttpOptions;
+use crate::solc;
+use crate::target::{additional_target_clients, AdditionalTarget, TargetClient, TargetType};
+use crate::verification::{
+    normalize_compiler_version, submit_and_await_verification, submit_to_additional_targets,
+    CopyContext, VerificationDetails, VerificationResult,
+};
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::verify::{CodeFormat, VerifyContract};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Build a verification request from a local solidity-standard-json-input
+///
+/// # Arguments
+/// - `contract_address` - The address of the deployed contract
+/// - `standard_json` - The solidity-standard-json-input the contract was compiled from
+/// - `contract_name` - Fully qualified name of the contract (e.g. `src/Vault.sol:Vault`)
+/// - `compiler_version` - The solc version used for compilation (e.g. `v0.8.19+commit.7dd6d404`)
+/// - `constructor_arguments` - Optional hex encoded constructor arguments
+pub fn verification_request_from_standard_json(
+    contract_address: &str,
+    standard_json: String,
+    contract_name: String,
+    compiler_version: String,
+    constructor_arguments: Option<String>,
+) -> Result<VerifyContract> {
+    let input: Value =
+        serde_json::from_str(&standard_json).wrap_err("Failed to parse standard-json-input")?;
+    let settings = &input["settings"];
+    let constructor_arguments =
+        constructor_arguments.map(|args| args.trim_start_matches("0x").to_string());
+    Ok(VerifyContract {
+        address: contract_address.parse()?,
+        code_format: CodeFormat::StandardJsonInput,
+        contract_name,
+        compiler_version: normalize_compiler_version(&compiler_version),
+        runs: settings["optimizer"]["runs"]
+            .as_u64()
+            .map(|runs| runs.to_string()),
+        optimization_used: settings["optimizer"]["enabled"]
+            .as_bool()
+            .map(|enabled| u8::from(enabled).to_string()),
+        constructor_arguments: constructor_arguments.clone(),
+        blockscout_constructor_arguments: constructor_arguments,
+        evm_version: settings["evmVersion"].as_str().map(ToString::to_string),
+        source: standard_json,
+        other: HashMap::new(),
+    })
+}
+
+/// Build a verification request from a Foundry build artifact (e.g. `out/Vault.sol/Vault.json`)
+///
+/// The compiler version, settings and contract name are taken from the artifact's metadata.
+/// Since the metadata only references the source files, their content is read from disk
+/// relative to `project_root`.
+///
+/// # Arguments
+/// - `contract_address` - The address of the deployed contract
+/// - `artifact_path` - Path to the Foundry artifact
+/// - `project_root` - Root of the Foundry project the source paths are relative to
+/// - `constructor_arguments` - Optional hex encoded constructor arguments
+pub fn verification_request_from_foundry_artifact(
+    contract_address: &str,
+    artifact_path: impl AsRef<Path>,
+    project_root: impl AsRef<Path>,
+    constructor_arguments: Option<String>,
+) -> Result<VerifyContract> {
+    let artifact_path = artifact_path.as_ref();
+    let artifact: Value = serde_json::from_str(
+        &std::fs::read_to_string(artifact_path)
+            .wrap_err_with(|| format!("Failed to read artifact {}", artifact_path.display()))?,
+    )
+    .wrap_err_with(|| format!("Failed to parse artifact {}", artifact_path.display()))?;
+    let metadata = match &artifact["metadata"] {
+        Value::String(raw_metadata) => serde_json::from_str(raw_metadata)?,
+        Value::Object(_) => artifact["metadata"].clone(),
+        _ => match artifact["rawMetadata"].as_str() {
+            Some(raw_metadata) => serde_json::from_str(raw_metadata)?,
+            None => return Err(eyre!("Artifact does not contain metadata")),
+        },
+    };
+    let project_root = project_root.as_ref();
+    let (standard_json, contract_name) = standard_json_from_metadata(&metadata, |source_path| {
+        let file = project_root.join(source_path);
+        std::fs::read_to_string(&file)
+            .wrap_err_with(|| format!("Failed to read source {}", file.display()))
+    })?;
+    let compiler_version = metadata["compiler"]["version"]
+        .as_str()
+        .ok_or_else(|| eyre!("Artifact metadata is missing the compiler version"))?
+        .to_string();
+    verification_request_from_standard_json(
+        contract_address,
+        serde_json::to_string(&standard_json)?,
+        contract_name,
+        compiler_version,
+        constructor_arguments,
+    )
+}
+
+/// Build a verification request from a directory previously written by the `export` command
+///
+/// # Arguments
+/// - `export_dir` - Directory containing the manifest and standard-json-input of one contract
+pub fn verification_request_from_export(export_dir: impl AsRef<Path>) -> Result<VerifyContract> {
+    let export_dir = export_dir.as_ref();
+    let manifest: VerificationManifest = serde_json::from_str(
+        &std::fs::read_to_string(export_dir.join(MANIFEST_FILE_NAME))
+            .wrap_err_with(|| format!("Failed to read manifest in {}", export_dir.display()))?,
+    )?;
+    let standard_json = std::fs::read_to_string(export_dir.join(STANDARD_JSON_FILE_NAME))
+        .wrap_err_with(|| {
+            format!(
+                "Failed to read standard-json-input in {}",
+                export_dir.display()
+            )
+        })?;
+    let mut verification_request = verification_request_from_standard_json(
+        &manifest.address,
+        standard_json,
+        manifest.contract_name,
+        manifest.compiler_version,
+        Some(manifest.constructor_arguments),
+    )?;
+    verification_request.evm_version = manifest.evm_version;
+    verification_request.optimization_used = manifest.optimization_used;
+    verification_request.runs = manifest.runs;
+    Ok(verification_request)
+}
+
+/// Submit a locally built verification request to the target block-explorer and, once it
+/// passed, to the additional targets
+///
+/// Returns the result on the target with the failures of the additional targets as warnings.
+///
+/// # Arguments
+/// - `verification_request` - The verification request to submit
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `target_http` - Settings of the http clients connecting to the target block-explorer
+/// - `target_type` - Api of the target block-explorer, detected from the url if `None`
+/// - `additional_targets` - Targets the verification is submitted to in addition to the target
+///   block-explorer, see [`CopyOptions::additional_targets`](crate::CopyOptions)
+pub async fn import_etherscan_verification(
+    mut verification_request: VerifyContract,
+    target_api_key: String,
+    target_url: String,
+    target_http: &ExplorerHttpOptions,
+    target_type: Option<TargetType>,
+    additional_targets: &[AdditionalTarget],
+) -> Result<(VerificationResult, Vec<String>)> {
+    let target_client =
+        TargetClient::new(target_type, target_api_key, target_url, None, target_http)?;
+    let additional_targets = additional_target_clients(additional_targets)?;
+    let context = CopyContext {
+        additional_targets: &additional_targets,
+        ..Default::default()
+    };
+    solc::resolve_compiler_version(&mut verification_request, target_http).await;
+    let mut details = VerificationDetails::default();
+    let result = submit_and_await_verification(
+        verification_request.clone(),
+        &target_client,
+        context,
+        &mut details,
+    )
+    .await?;
+    submit_to_additional_targets(&verification_request, context, &mut details).await;
+    Ok((result, details.warnings))
+}
+
+/// Build a solidity-standard-json-input from the metadata emitted by solc, returning it with the
+/// fully qualified name of the compilation target
+///
+/// The content of sources not embedded in the metadata is read via `read_source`.
+pub(crate) fn standard_json_from_metadata(
+    metadata: &Value,
+    read_source: impl Fn(&str) -> Result<String>,
+) -> Result<(Value, String)> {
+    let mut settings = metadata["settings"].clone();
+    let compilation_target = settings
+        .as_object_mut()
+        .and_then(|settings| settings.remove("compilationTarget"))
+        .ok_or_else(|| eyre!("Metadata is missing the compilation target"))?;
+    let (path, name) = compilation_target
+        .as_object()
+        .and_then(|target| target.iter().next())
+        .and_then(|(path, name)| Some((path.clone(), name.as_str()?.to_string())))
+        .ok_or_else(|| eyre!("Metadata has an invalid compilation target"))?;
+
+    let mut sources = serde_json::Map::new();
+    for (source_path, source) in metadata["sources"]
+        .as_object()
+        .ok_or_else(|| eyre!("Metadata is missing the sources"))?
+    {
+        let content = match source["content"].as_str() {
+            Some(content) => content.to_string(),
+            None => read_source(source_path)?,
+        };
+        sources.insert(source_path.clone(), json!({ "content": content }));
+    }
+    let standard_json = json!({
+        "language": metadata["language"].as_str().unwrap_or("Solidity"),
+        "sources": sources,
+        "settings": settings,
+    });
+    Ok((standard_json, format!("{}:{}", path, name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_json_from_metadata() {
+        let metadata = json!({
+            "compiler": { "version": "0.8.19+commit.7dd6d404" },
+            "language": "Solidity",
+            "settings": {
+                "compilationTarget": { "src/Counter.sol": "Counter" },
+                "evmVersion": "paris",
+                "libraries": {},
+                "optimizer": { "enabled": true, "runs": 200 },
+                "remappings": []
+            },
+            "sources": {
+                "src/Counter.sol": { "content": "contract Counter {}", "keccak256": "0x00" }
+            }
+        });
+        let (standard_json, contract_name) = standard_json_from_metadata(&metadata, |path| {
+            Err(eyre!("Unexpected read of {}", path))
+        })
+        .unwrap();
+        assert_eq!(contract_name, "src/Counter.sol:Counter");
+        assert_eq!(
+            standard_json,
+            json!({
+                "language": "Solidity",
+                "sources": { "src/Counter.sol": { "content": "contract Counter {}" } },
+                "settings": {
+                    "evmVersion": "paris",
+                    "libraries": {},
+                    "optimizer": { "enabled": true, "runs": 200 },
+                    "remappings": []
+                }
+            })
+        );
+
+        let verification_request = verification_request_from_standard_json(
+            "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            serde_json::to_string(&standard_json).unwrap(),
+            contract_name,
+            "0.8.19+commit.7dd6d404".to_string(),
+            Some("0x01".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            verification_request.compiler_version,
+            "v0.8.19+commit.7dd6d404"
+        );
+        assert_eq!(verification_request.evm_version.as_deref(), Some("paris"));
+        assert_eq!(verification_request.runs.as_deref(), Some("200"));
+        assert_eq!(verification_request.optimization_used.as_deref(), Some("1"));
+        assert_eq!(
+            verification_request.constructor_arguments.as_deref(),
+            Some("01")
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use eyre::{eyre, Context, Result};
+use serde::Deserialize;
+use std::io::BufRead;
+use std::path::Path;
+
+/// A contract to copy the verification for, optionally annotated with a human-readable label
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contract {
+    /// The contract's address
+    pub address: String,
+    /// Optional human-readable name shown next to the address in progress output
+    pub label: Option<String>,
+    /// Address of the contract this contract was added for (e.g. the proxy of an
+    /// implementation), contracts of the same group are shown together in the progress output
+    /// and reports
+    pub group: Option<String>,
+}
+
+impl Contract {
+    /// Returns the label and address for display, or just the address if there is no label
+    pub fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", label, self.address),
+            None => self.address.clone(),
+        }
+    }
+}
+
+impl From<String> for Contract {
+    fn from(address: String) -> Self {
+        Contract {
+            address,
+            label: None,
+            group: None,
+        }
+    }
+}
+
+impl From<&str> for Contract {
+    fn from(address: &str) -> Self {
+        Contract::from(address.to_string())
+    }
+}
+
+/// Read contract addresses from a file containing one address per line
+///
+/// Blank lines are skipped and everything following a `#` is treated as a comment.
+///
+/// # Arguments
+/// - `path` - Path to the file containing the addresses
+pub fn read_addresses_file(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)
+        .wrap_err_with(|| format!("Failed to open addresses file {}", path.display()))?;
+    parse_addresses(std::io::BufReader::new(file))
+}
+
+/// Read contract addresses from stdin containing one address per line
+///
+/// Blank lines are skipped and everything following a `#` is treated as a comment.
+pub fn read_addresses_stdin() -> Result<Vec<String>> {
+    parse_addresses(std::io::stdin().lock()).wrap_err("Failed to read addresses from stdin")
+}
+
+/// Parse contract addresses from a reader containing one address per line
+///
+/// Blank lines are skipped and everything following a `#` is treated as a comment.
+/// Returns an error pointing to the offending line if any entry is not a valid address.
+pub fn parse_addresses(reader: impl BufRead) -> Result<Vec<String>> {
+    let mut addresses = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let address = line.split('#').next().unwrap_or_default().trim();
+        if !address.is_empty() {
+            validate_address(address).wrap_err_with(|| format!("Line {}", index + 1))?;
+            addresses.push(address.to_string());
+        }
+    }
+    Ok(addresses)
+}
+
+/// Read labeled contract addresses from a csv file with `address,label` rows
+///
+/// The label column is optional, an optional `address,label` header row is skipped, as are blank
+/// lines and everything following a `#`.
+///
+/// # Arguments
+/// - `path` - Path to the csv file
+pub fn read_contracts_csv(path: impl AsRef<Path>) -> Result<Vec<Contract>> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)
+        .wrap_err_with(|| format!("Failed to open csv file {}", path.display()))?;
+    parse_contracts_csv(std::io::BufReader::new(file))
+}
+
+/// Parse labeled contract addresses from a reader containing `address,label` rows
+pub fn parse_contracts_csv(reader: impl BufRead) -> Result<Vec<Contract>> {
+    let mut contracts = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let row = line.split('#').next().unwrap_or_default().trim();
+        if row.is_empty() {
+            continue;
+        }
+        let (address, label) = match row.split_once(',') {
+            Some((address, label)) => (address.trim(), label.trim()),
+            None => (row, ""),
+        };
+        if index == 0 && address.eq_ignore_ascii_case("address") {
+            continue;
+        }
+        validate_address(address).wrap_err_with(|| format!("Line {}", index + 1))?;
+        contracts.push(Contract {
+            address: address.to_string(),
+            label: (!label.is_empty()).then(|| label.to_string()),
+            group: None,
+        });
+    }
+    Ok(contracts)
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenList {
+    tokens: Vec<TokenInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenInfo {
+    chain_id: u64,
+    address: String,
+    symbol: String,
+}
+
+/// Read the token contracts for the given chain from a Uniswap-style token list
+///
+/// Contracts are labeled with the token's symbol.
+///
+/// # Arguments
+/// - `path` - Path to the token list json file
+/// - `chain_id` - Only tokens deployed on this chain are returned
+pub fn read_token_list(path: impl AsRef<Path>, chain_id: u64) -> Result<Vec<Contract>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read token list {}", path.display()))?;
+    parse_token_list(&content, chain_id)
+}
+
+/// Parse the token contracts for the given chain from a Uniswap-style token list
+pub fn parse_token_list(content: &str, chain_id: u64) -> Result<Vec<Contract>> {
+    let token_list: TokenList = serde_json::from_str(content)?;
+    Ok(token_list
+        .tokens
+        .into_iter()
+        .filter(|token| token.chain_id == chain_id)
+        .map(|token| Contract {
+            address: token.address,
+            label: Some(token.symbol),
+            group: None,
+        })
+        .collect())
+}
+
+/// Check that the given string is a `0x` prefixed, 20 byte hex encoded address
+pub fn validate_address(address: &str) -> Result<()> {
+    let hex_address = address
+        .strip_prefix("0x")
+        .ok_or_else(|| eyre!("Invalid address {}: missing 0x prefix", address))?;
+    if hex_address.len() != 40 || hex::decode(hex_address).is_err() {
+        return Err(eyre!(
+            "Invalid address {}: expected 20 hex encoded bytes",
+            address
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_addresses_skips_comments_and_blank_lines() {
+        let input = "# Uniswap\n0xE592427A0AEce92De3Edee1F18E0157C05861564\n\n  0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84 # icETH\n";
+        let addresses = parse_addresses(input.as_bytes()).unwrap();
+        assert_eq!(
+            addresses,
+            vec![
+                "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+                "0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_contracts_csv() {
+        let input = "address,label\n0xE592427A0AEce92De3Edee1F18E0157C05861564,UniswapV3Router\n0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84\n";
+        let contracts = parse_contracts_csv(input.as_bytes()).unwrap();
+        assert_eq!(
+            contracts,
+            vec![
+                Contract {
+                    address: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+                    label: Some("UniswapV3Router".to_string()),
+                    group: None,
+                },
+                Contract::from("0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84"),
+            ]
+        );
+        assert_eq!(
+            contracts[0].display_name(),
+            "UniswapV3Router (0xE592427A0AEce92De3Edee1F18E0157C05861564)"
+        );
+    }
+
+    #[test]
+    fn test_parse_token_list() {
+        let content = r#"{
+            "name": "Test List",
+            "tokens": [
+                {"chainId": 1, "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "symbol": "USDC", "name": "USD Coin", "decimals": 6},
+                {"chainId": 10, "address": "0x7F5c764cBc14f9669B88837ca1490cCa17c31607", "symbol": "USDC", "name": "USD Coin", "decimals": 6}
+            ]
+        }"#;
+        let contracts = parse_token_list(content, 1).unwrap();
+        assert_eq!(
+            contracts,
+            vec![Contract {
+                address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+                label: Some("USDC".to_string()),
+                group: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_addresses_rejects_invalid_address() {
+        let input = "0xE592427A0AEce92De3Edee1F18E0157C05861564\nnot-an-address\n";
+        let err = parse_addresses(input.as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "Line 2");
+        assert!(validate_address("0xE592427A0AEce92De3Edee1F18E0157C0586156").is_err());
+        assert!(validate_address("E592427A0AEce92De3Edee1F18E0157C05861564").is_err());
+    }
+}
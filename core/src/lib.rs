@@ -0,0 +1,1065 @@
+//! Copy contract verification from one block-explorer to another
+//!
+//! This crate allows you to easily copy the verfied source code from one block-explorer to another.
+//! It reads the source code / metadata from the source_url and submits it to the target_url.
+//! This assumes that both block-explorers are compatible with the the etherscan api specification
+//!
+//! ```rust
+//!    let results = contract_verification_migrator_core::copy_etherscan_verification(
+//!        vec!["0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string()],
+//!        "<YOUR_ETHERSCAN_API_KEY>".to_string(),
+//!        "https://api.etherscan.io/api".to_string(),
+//!        "<YOUR_BLOCKSCOUT_API_KEY>".to_string(),
+//!        "https://eth.blockscout.com/api".to_string(),
+//!        true,
+//!    );
+//! ```
+#![warn(missing_docs)]
+
+use eyre::Result;
+use foundry_block_explorers::verify::VerifyContract;
+use foundry_block_explorers::Client;
+use futures::future::FutureExt;
+use futures::stream::{BoxStream, FuturesUnordered, StreamExt};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+#[cfg(feature = "blocking")]
+mod blocking;
+#[cfg(feature = "blocking")]
+pub use blocking::copy_etherscan_verification_blocking;
+mod blockscout;
+mod bytecode;
+mod check;
+pub use check::{check_verification_status, ExplorerVerificationStatus, VerificationStatus};
+mod dedupe;
+use dedupe::{BytecodeClaim, BytecodeDeduplicator, CloneImplementations, RuntimeCodeFetcher};
+mod deployments;
+pub use deployments::{
+    parse_foundry_broadcast, read_foundry_broadcast, read_hardhat_deployments,
+    read_hardhat_solc_input,
+};
+mod diff;
+pub use diff::{diff_etherscan_verification, VerificationDifference};
+mod discovery;
+pub use discovery::{find_contracts_deployed_by, find_factory_children, list_verified_contracts};
+mod events;
+pub use events::{EventHandler, ProgressObserver, VerificationEvent};
+mod export;
+pub use export::{
+    export_etherscan_verification, fetch_etherscan_sources, prepare_etherscan_verification,
+    VerificationManifest, MANIFEST_FILE_NAME, STANDARD_JSON_FILE_NAME,
+};
+mod explorer;
+pub use explorer::{SourceExplorer, TargetExplorer};
+mod failure;
+use failure::VerificationFailure;
+pub use failure::{FailureReason, FailureStage, MigrationError};
+mod http;
+pub use http::ExplorerHttpOptions;
+mod import;
+pub use import::{
+    import_etherscan_verification, verification_request_from_export,
+    verification_request_from_foundry_artifact, verification_request_from_standard_json,
+};
+mod input;
+pub use input::{
+    parse_addresses, parse_contracts_csv, parse_token_list, read_addresses_file,
+    read_addresses_stdin, read_contracts_csv, read_token_list, validate_address, Contract,
+};
+mod license;
+mod migrator;
+pub use migrator::{Migrator, MigratorBuilder};
+mod oklink;
+mod pending;
+use pending::PendingVerificationStore;
+pub use pending::{read_pending_verifications, resume_etherscan_verification, PendingVerification};
+mod pipeline;
+pub use pipeline::{await_verification, fetch_source_metadata, submit_verification};
+mod poll;
+pub use poll::PollOptions;
+use poll::PollScheduler;
+mod proxy;
+use proxy::TargetChain;
+pub use proxy::{
+    read_diamond_facets, read_past_implementations, read_proxy_beacon, read_proxy_implementation,
+    with_diamond_facets, with_past_proxy_implementations, with_proxy_implementations,
+    RelatedContractsOptions, StorageReader,
+};
+#[cfg(feature = "sqlite")]
+mod queue;
+#[cfg(feature = "sqlite")]
+pub use queue::{copy_etherscan_verification_from_queue, ClaimedJob, JobQueue, JobStatus};
+mod rate_limit;
+use rate_limit::RateLimiter;
+mod registry;
+pub use registry::{discover_api_url, resolve_explorer_preset, ExplorerPreset, EXPLORER_PRESETS};
+mod report;
+mod retry;
+pub use report::{ContractReport, MigrationReport, ReportFormat, ReportOutcome};
+pub use retry::RetryPolicy;
+mod secrets;
+use secrets::api_keys;
+pub use secrets::{keyring_api_key, read_api_key_file, KEYRING_SERVICE};
+mod settings;
+pub use settings::{
+    add_remappings, link_libraries, override_settings, set_contract_path, ContractPath,
+    LinkedLibrary,
+};
+mod shutdown;
+mod solc;
+pub use shutdown::ShutdownSignal;
+mod sourcify;
+use sourcify::SourcifyClient;
+pub use sourcify::{SourcifySource, DEFAULT_SOURCIFY_URL};
+mod target;
+#[cfg(test)]
+mod test_utils;
+use target::TargetClient;
+pub use target::{routescan_api_url, AdditionalTarget, TargetType};
+mod verification;
+pub use verification::{
+    api_url_with_chain_id, convert_metadata_to_verification_request,
+    copy_etherscan_verification_for_contract, reconstructed_settings, StageTimings,
+    VerificationDetails, VerificationResult, ETHERSCAN_V2_API_URL,
+};
+use verification::{
+    build_client, copy_contract_verification, prepare_contract_verification, CopyContext,
+    MetadataSource,
+};
+mod vyper;
+mod watch;
+mod yul;
+mod zksync;
+pub use watch::{watch_etherscan_verification, WatchState};
+use zksync::{is_zksync_url, ZkSyncSource};
+#[cfg(feature = "local-compile")]
+mod compile;
+/// Without the `local-compile` feature contracts can not be compiled locally
+#[cfg(not(feature = "local-compile"))]
+#[path = "compile_disabled.rs"]
+mod compile;
+#[cfg(feature = "progress-bar")]
+mod progress_bar;
+/// Without the `progress-bar` feature no progress bars are displayed
+#[cfg(not(feature = "progress-bar"))]
+#[path = "progress_bar_disabled.rs"]
+mod progress_bar;
+use progress_bar::{
+    initialize_multi_progress, initialize_progress_bar, update_progress_bar,
+    with_progress_bar_events,
+};
+
+/// Copy contract verification of multiple contracts from one block-explorer to another
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts (addresses with optional labels) for which to copy the
+///   contract verification
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `progress_bar` - Boolean indicating wether or not to display progress bars for the individual
+///   requests
+///
+/// Returns the result for each contract together with its address, in the order of `contracts`.
+///
+/// # Examples
+///
+/// ```rust
+///    let results = contract_verification_migrator_core::copy_etherscan_verification(
+///        vec!["0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string()],
+///        "<YOUR_ETHERSCAN_API_KEY>".to_string(),
+///        "https://api.etherscan.io/api".to_string(),
+///        "<YOUR_BLOCKSCOUT_API_KEY>".to_string(),
+///        "https://eth.blockscout.com/api".to_string(),
+///        true,
+///     );
+///
+/// ```
+pub async fn copy_etherscan_verification(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    target_api_key: String,
+    target_url: String,
+    progress_bar: bool,
+) -> Vec<(String, Result<VerificationResult>)> {
+    let options = CopyOptions {
+        progress_bar,
+        ..Default::default()
+    };
+    let contracts: Vec<Contract> = contracts.into_iter().map(Into::into).collect();
+    let addresses: Vec<String> = contracts.iter().map(|c| c.address.clone()).collect();
+    match copy_etherscan_verification_with_options(
+        contracts,
+        source_api_key,
+        source_url,
+        target_api_key,
+        target_url,
+        &options,
+    )
+    .await
+    {
+        Ok(outcomes) => outcomes
+            .into_iter()
+            .map(|outcome| (outcome.contract.address, outcome.result))
+            .collect(),
+        // Building the clients failed, which affects every contract in the same way
+        Err(err) => addresses
+            .into_iter()
+            .map(|address| (address, Err(eyre::eyre!("{:#}", err))))
+            .collect(),
+    }
+}
+
+/// Number of contracts processed concurrently unless configured otherwise
+const DEFAULT_CONCURRENCY: usize = 5;
+
+/// Additional options for [`copy_etherscan_verification_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    /// Display progress bars for the individual requests, requires the `progress-bar` feature
+    pub progress_bar: bool,
+    /// File to record the GUIDs of submitted verifications in until their outcome is known, so
+    /// polling can be resumed with [`resume_etherscan_verification`] if the process dies
+    pub guid_file: Option<PathBuf>,
+    /// Handler receiving a [`VerificationEvent`] for every state transition of each contract, see
+    /// [`EventHandler::observer`] to receive them through a [`ProgressObserver`]
+    pub events: Option<EventHandler>,
+    /// Url of the target block-explorer's website used for the links to verified contracts,
+    /// derived from `target_url` if not set
+    pub target_browser_url: Option<String>,
+    /// Api of the target block-explorer, detected from `target_url` if not set
+    pub target_type: Option<TargetType>,
+    /// Adapter the verifications are submitted to instead of the target block-explorer at
+    /// `target_url`, for block-explorers that are not built in
+    pub target_explorer: Option<Arc<dyn TargetExplorer>>,
+    /// Adapter the verified contracts are read from instead of the source block-explorer at
+    /// `source_url`, which is not contacted at all if set
+    pub source_explorer: Option<Arc<dyn SourceExplorer>>,
+    /// Targets (e.g. Tenderly) the verification of each contract is submitted to once the target
+    /// block-explorer verified it (also if it was verified before), their failures are recorded
+    /// as [`VerificationDetails::warnings`] without failing the contract
+    pub additional_targets: Vec<AdditionalTarget>,
+    /// Libraries linked in the compiler settings of every contract, in addition to the ones
+    /// reported by the source block-explorer
+    pub libraries: Vec<LinkedLibrary>,
+    /// Remappings set in the compiler settings of contracts that have none, e.g. because the
+    /// source block-explorer reports a single file or multiple files without settings
+    pub remappings: Vec<String>,
+    /// Compiler settings (e.g. `{"viaIR": true}`) merged into the settings of every contract,
+    /// for settings the source block-explorer does not report (the settings of contracts
+    /// verified as standard-json are forwarded untouched otherwise)
+    pub settings_overrides: Option<serde_json::Value>,
+    /// Paths of the source files declaring the contracts, overriding the paths derived from the
+    /// sources (e.g. if several files declare a contract of the same name)
+    pub contract_paths: Vec<ContractPath>,
+    /// Mark contracts the source block-explorer reports as proxies (e.g. Etherscan's `Proxy`
+    /// field) as proxies of their implementation on the target once verified, so it shows the
+    /// "Read as Proxy" tab like the source does
+    ///
+    /// Etherscan compatible targets get them marked via `verifyproxycontract`, Blockscout v2 has
+    /// no api for it and is only checked to detect them. Failures are recorded as
+    /// [`VerificationDetails::warnings`], the contract counts as verified regardless.
+    pub mark_proxies: bool,
+    /// Fetch and submit every contract instead of skipping the ones the target reports as
+    /// verified already, e.g. for targets whose check is unreliable or to submit a different
+    /// verification of contracts verified already
+    pub skip_verified_check: bool,
+    /// JSON-RPC endpoint of the target chain, contracts without bytecode there fail with
+    /// [`FailureReason::NotDeployed`] before anything is fetched or submitted if set
+    pub target_rpc_url: Option<String>,
+    /// Maximum number of requests per second sent to `target_rpc_url` across all contracts,
+    /// only slowing down once the node reports its rate limit if not set
+    pub target_rpc_max_requests_per_second: Option<f64>,
+    /// Compare the runtime bytecode on the target chain (read via `target_rpc_url`) with the one
+    /// on the source chain, ignoring the metadata hash and immutables, and fail contracts that
+    /// differ with [`FailureReason::BytecodeMismatch`] before submitting them (ignored when not
+    /// reading the metadata from the source block-explorer)
+    pub compare_bytecode: bool,
+    /// Compile every Solidity contract with the matching solc (installed into `~/.svm` with svm
+    /// if missing) and fail contracts whose bytecode differs from the deployed one with
+    /// [`FailureReason::BytecodeMismatch`] before submitting them
+    ///
+    /// The deployed bytecode is read via `target_rpc_url` if set and from the source
+    /// block-explorer otherwise, contracts without either are not compiled. Requires the
+    /// `local-compile` feature.
+    pub compile_locally: bool,
+    /// Check that the input of the transaction that created each contract ends with its
+    /// constructor arguments and fail contracts that differ with
+    /// [`FailureReason::ConstructorArgumentsMismatch`] before submitting them (ignored when not
+    /// reading the metadata from the source block-explorer, for contracts created by factories
+    /// and for contracts whose creation transaction the source does not report)
+    pub check_constructor_arguments: bool,
+    /// Mark contracts that are not verified on the source (failing with
+    /// [`FailureReason::SourceNotVerified`]) as skipped via [`VerificationDetails::skipped`], so
+    /// they can be told apart from the contracts that failed
+    pub skip_unverified: bool,
+    /// Policy for retrying requests to either block-explorer that failed with a transient error
+    pub retry: RetryPolicy,
+    /// Options for polling the target block-explorer for the outcome of submitted verifications
+    pub poll: PollOptions,
+    /// Maximum time spent on a single contract (fetching, submitting and polling) before it is
+    /// reported as failed with [`FailureReason::Timeout`]
+    pub contract_timeout: Option<Duration>,
+    /// Maximum time for the whole run, contracts that are not done by then are cancelled and
+    /// reported as failed with [`FailureReason::Skipped`]
+    pub deadline: Option<Duration>,
+    /// Signal to stop early, contracts that are not done once it is triggered are cancelled and
+    /// reported as failed with [`FailureReason::Skipped`]
+    pub shutdown: Option<ShutdownSignal>,
+    /// Directory written by [`export_etherscan_verification`] to read the verified metadata from
+    /// instead of fetching it from the source block-explorer, which is not contacted at all if set
+    pub source_export_dir: Option<PathBuf>,
+    /// Sourcify repository to fetch the verified sources from instead of the source
+    /// block-explorer, which is not contacted at all if set
+    pub source_sourcify: Option<SourcifySource>,
+    /// Only submit the verification of the first contract of each group of contracts with
+    /// identical runtime bytecode and report the others as already verified if the target
+    /// block-explorer matched them to it, the others are submitted on their own otherwise
+    /// (ignored when not reading the metadata from the source block-explorer)
+    pub dedupe_bytecode: bool,
+    /// Maximum number of contracts processed concurrently, defaults to 5
+    pub concurrency: Option<usize>,
+    /// Maximum number of requests per second sent to the source block-explorer across all
+    /// contracts, per api key if a comma separated pool of keys is passed
+    pub source_max_requests_per_second: Option<f64>,
+    /// Maximum number of requests per second sent to the target block-explorer across all
+    /// contracts, per api key if a comma separated pool of keys is passed
+    pub target_max_requests_per_second: Option<f64>,
+    /// Settings of the http clients connecting to the source block-explorer (e.g. basic auth of
+    /// a private instance)
+    pub source_http: ExplorerHttpOptions,
+    /// Settings of the http clients connecting to the target block-explorer, the additional
+    /// targets are connected to without them
+    pub target_http: ExplorerHttpOptions,
+}
+
+/// Outcome of copying the verification of a single contract
+#[derive(Debug)]
+pub struct CopyOutcome {
+    /// The contract whose verification was copied
+    pub contract: Contract,
+    /// Result of copying the verification
+    pub result: Result<VerificationResult>,
+    /// Details gathered while copying the verification
+    pub details: VerificationDetails,
+}
+
+impl CopyOutcome {
+    /// Summary of the outcome with the contract's name, compiler version, GUID, timings and link
+    /// on the target, serializable as in the [`MigrationReport`]
+    pub fn report(&self) -> ContractReport {
+        ContractReport::new(self)
+    }
+}
+
+/// Copy contract verification of multiple contracts from one block-explorer to another with
+/// additional options
+///
+/// Returns an error only if the setup fails (e.g. building the clients from invalid urls or
+/// reading the GUID file), the outcome of each contract (including details for reporting, see
+/// [`MigrationReport`]) is returned in the same order as the given contracts.
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts (addresses with optional labels) for which to copy the
+///   contract verification
+/// - `source_api_key` - The api key for the source block-explorer's api, a comma separated pool
+///   of keys is rotated between per contract
+/// - `source_url` - The url of the source block-explorer's api
+/// - `target_api_key` - The api key for the target block-explorer's api, a comma separated pool
+///   of keys is rotated between per contract and while polling per request
+/// - `target_url` - The url of the target block-explorer's api
+/// - `options` - Additional options, see [`CopyOptions`]
+pub async fn copy_etherscan_verification_with_options(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    target_api_key: String,
+    target_url: String,
+    options: &CopyOptions,
+) -> Result<Vec<CopyOutcome>> {
+    let run = CopyRun::new(
+        source_api_key,
+        source_url,
+        target_api_key,
+        target_url,
+        options,
+    )?;
+    Ok(futures::future::join_all(copy_tasks(
+        contracts.into_iter().map(Into::into).collect(),
+        run,
+    ))
+    .await)
+}
+
+/// Copy contract verification of multiple contracts from one block-explorer to another, yielding
+/// the outcome of each contract as soon as it completes
+///
+/// Works like [`copy_etherscan_verification_with_options`], but lets the caller report progress
+/// or persist the outcomes incrementally instead of waiting for all contracts. The outcomes are
+/// yielded in the order the contracts complete, which differs from the order of `contracts`.
+///
+/// Returns an error only if the setup fails (e.g. building the clients from invalid urls or
+/// reading the GUID file).
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts (addresses with optional labels) for which to copy the
+///   contract verification
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `options` - Additional options, see [`CopyOptions`]
+///
+/// # Examples
+///
+/// ```rust
+/// use futures::StreamExt;
+///
+/// # async fn run() -> eyre::Result<()> {
+/// let mut outcomes = contract_verification_migrator_core::copy_etherscan_verification_stream(
+///     vec!["0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string()],
+///     "<YOUR_ETHERSCAN_API_KEY>".to_string(),
+///     "https://api.etherscan.io/api".to_string(),
+///     "<YOUR_BLOCKSCOUT_API_KEY>".to_string(),
+///     "https://eth.blockscout.com/api".to_string(),
+///     &Default::default(),
+/// )?;
+/// while let Some(outcome) = outcomes.next().await {
+///     println!("{}: {:?}", outcome.contract.address, outcome.result);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn copy_etherscan_verification_stream(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    target_api_key: String,
+    target_url: String,
+    options: &CopyOptions,
+) -> Result<BoxStream<'static, CopyOutcome>> {
+    let run = CopyRun::new(
+        source_api_key,
+        source_url,
+        target_api_key,
+        target_url,
+        options,
+    )?;
+    Ok(
+        copy_tasks(contracts.into_iter().map(Into::into).collect(), run)
+            .into_iter()
+            .collect::<FuturesUnordered<_>>()
+            .boxed(),
+    )
+}
+
+/// Build the verification requests of multiple contracts the way
+/// [`copy_etherscan_verification_with_options`] does, without submitting them to the target
+///
+/// Every request goes through the same steps as when copying, e.g. the compiler version is
+/// normalized, missing constructor arguments are recovered and the checks enabled in `options`
+/// run, and copying stops right before the request would be submitted. The target is only asked
+/// whether it verified the contract already and which compiler versions it supports, contracts it
+/// verified already yield `None`. Minimal proxies yield the request of their implementation. No
+/// GUID file is written.
+///
+/// Returns an error only if the setup fails, the request of each contract is returned in the
+/// same order as the given contracts.
+///
+/// # Arguments
+/// - `contracts` - Vector of contracts (addresses with optional labels) to build the
+///   verification requests of
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `options` - Additional options, see [`CopyOptions`]
+pub async fn dry_run_etherscan_verification(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    target_api_key: String,
+    target_url: String,
+    options: &CopyOptions,
+) -> Result<Vec<(Contract, Result<Option<VerifyContract>>)>> {
+    let run = CopyRun::new(
+        source_api_key,
+        source_url,
+        target_api_key,
+        target_url,
+        &CopyOptions {
+            guid_file: None,
+            ..options.clone()
+        },
+    )?;
+    let tasks = contracts.into_iter().map(Into::into).enumerate().map(
+        |(index, contract): (usize, Contract)| {
+            let run = &run;
+            async move {
+                let _permit = run
+                    .semaphore
+                    .acquire()
+                    .await
+                    .expect("concurrency semaphore is never closed");
+                let result = prepare_contract_verification(
+                    contract.address.clone(),
+                    run.metadata_source(index),
+                    run.target_client(index),
+                    run.context(index, None),
+                )
+                .await;
+                (contract, result)
+            }
+        },
+    );
+    Ok(futures::future::join_all(tasks).await)
+}
+
+/// State shared by all contracts copied in a single run
+struct CopyRun {
+    options: CopyOptions,
+    // The clients are shared by all contracts, so connections to the block-explorers are reused
+    /// One client per api key of the source, empty unless reading from the source block-explorer
+    source_clients: Vec<Client>,
+    sourcify_client: Option<SourcifyClient>,
+    /// Set instead of the source clients if the source is the zkSync Era block explorer, one per
+    /// api key
+    zksync_sources: Vec<ZkSyncSource>,
+    /// One client per api key of the target
+    target_clients: Vec<TargetClient>,
+    additional_targets: Vec<(String, TargetClient)>,
+    pending: Option<PendingVerificationStore>,
+    /// One limiter per api key of the source, as each key has a rate limit of its own
+    source_limiters: Vec<RateLimiter>,
+    /// One limiter per target client, as each api key has a rate limit of its own
+    target_limiters: Vec<Arc<RateLimiter>>,
+    poll_scheduler: PollScheduler,
+    semaphore: Semaphore,
+    deadline: Option<tokio::time::Instant>,
+    deduplicator: Option<BytecodeDeduplicator>,
+    /// One per api key of the source, empty unless reading from the source block-explorer
+    runtime_codes: Vec<RuntimeCodeFetcher>,
+    /// Implementations of the minimal proxies among the contracts, copied once for all of them
+    clone_implementations: CloneImplementations,
+    target_chain: Option<TargetChain>,
+}
+
+impl CopyRun {
+    fn new(
+        source_api_key: String,
+        source_url: String,
+        target_api_key: String,
+        target_url: String,
+        options: &CopyOptions,
+    ) -> Result<Arc<Self>> {
+        for max_requests_per_second in [
+            options.source_max_requests_per_second,
+            options.target_max_requests_per_second,
+            options.target_rpc_max_requests_per_second,
+        ] {
+            RateLimiter::optional(max_requests_per_second)?;
+        }
+        if options.concurrency == Some(0) {
+            return Err(eyre::eyre!("Concurrency must be at least 1"));
+        }
+        if options.compile_locally && !cfg!(feature = "local-compile") {
+            return Err(eyre::eyre!(
+                "Compiling locally requires the local-compile feature"
+            ));
+        }
+        let pending = match &options.guid_file {
+            Some(guid_file) => Some(PendingVerificationStore::open(guid_file)?),
+            None => None,
+        };
+        // A pool of api keys is rotated between, each key has a rate limit of its own
+        let source_api_keys = api_keys(&source_api_key);
+        let target_api_keys = api_keys(&target_api_key);
+        let reads_from_explorer = options.source_export_dir.is_none()
+            && options.source_sourcify.is_none()
+            && options.source_explorer.is_none();
+        // The zkSync Era block explorer additionally reports the zksolc version of contracts
+        let zksync_sources = if reads_from_explorer && is_zksync_url(&source_url) {
+            source_api_keys
+                .iter()
+                .map(|api_key| {
+                    ZkSyncSource::new(api_key.clone(), source_url.clone(), &options.source_http)
+                })
+                .collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        let target_clients = match &options.target_explorer {
+            Some(explorer) => vec![TargetClient::Custom(explorer.clone())],
+            None => target_api_keys
+                .iter()
+                .map(|api_key| {
+                    TargetClient::new(
+                        options.target_type,
+                        api_key.clone(),
+                        target_url.clone(),
+                        options.target_browser_url.clone(),
+                        &options.target_http,
+                    )
+                })
+                .collect::<Result<_>>()?,
+        };
+        let additional_targets = target::additional_target_clients(&options.additional_targets)?;
+        let target_limiters = target_clients
+            .iter()
+            .map(|_| RateLimiter::optional(options.target_max_requests_per_second).map(Arc::new))
+            .collect::<Result<Vec<_>>>()?;
+        let poll_scheduler = PollScheduler::new({
+            let target_clients = target_clients.clone();
+            let next_client = Arc::new(AtomicUsize::new(0));
+            let target_limiters = target_limiters.clone();
+            let retry = options.retry;
+            move |guid| {
+                // The guid identifies the submission on its own, Etherscan and its forks report
+                // its status to any api key, so the keys are rotated per status request
+                let index = next_client.fetch_add(1, Ordering::Relaxed) % target_clients.len();
+                let target_client = target_clients[index].clone();
+                let target_limiter = target_limiters[index].clone();
+                async move {
+                    retry
+                        .retry(Some(&target_limiter), || {
+                            target_client.verification_status(&guid)
+                        })
+                        .await
+                }
+                .boxed()
+            }
+        });
+        let source_clients = if reads_from_explorer {
+            source_api_keys
+                .iter()
+                .map(|api_key| {
+                    build_client(api_key.clone(), source_url.clone(), &options.source_http)
+                })
+                .collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        Ok(Arc::new(CopyRun {
+            options: options.clone(),
+            // The bytecode is fetched from the source block-explorer, which is not used when reading
+            // the metadata from an export or Sourcify
+            deduplicator: (options.dedupe_bytecode && reads_from_explorer)
+                .then(BytecodeDeduplicator::default),
+            runtime_codes: if reads_from_explorer {
+                source_api_keys
+                    .iter()
+                    .map(|api_key| {
+                        RuntimeCodeFetcher::new(
+                            api_key.clone(),
+                            source_url.clone(),
+                            &options.source_http,
+                        )
+                    })
+                    .collect::<Result<_>>()?
+            } else {
+                Vec::new()
+            },
+            clone_implementations: CloneImplementations::default(),
+            source_clients,
+            sourcify_client: options
+                .source_sourcify
+                .clone()
+                .map(|source| SourcifyClient::new(source, &options.source_http))
+                .transpose()?,
+            zksync_sources,
+            target_chain: options
+                .target_rpc_url
+                .clone()
+                .map(|rpc_url| {
+                    TargetChain::new(
+                        rpc_url,
+                        options.target_rpc_max_requests_per_second,
+                        &options.target_http,
+                    )
+                })
+                .transpose()?,
+            target_clients,
+            additional_targets,
+            pending,
+            source_limiters: source_api_keys
+                .iter()
+                .map(|_| RateLimiter::optional(options.source_max_requests_per_second))
+                .collect::<Result<_>>()?,
+            target_limiters,
+            poll_scheduler,
+            semaphore: Semaphore::new(options.concurrency.unwrap_or(DEFAULT_CONCURRENCY)),
+            deadline: options
+                .deadline
+                .map(|deadline| tokio::time::Instant::now() + deadline),
+        }))
+    }
+
+    /// Source the contract with the given index reads its metadata from, the api keys of the
+    /// source block-explorer are rotated per contract
+    fn metadata_source(&self, index: usize) -> MetadataSource<'_> {
+        if let Some(explorer) = &self.options.source_explorer {
+            return MetadataSource::Custom(explorer.as_ref());
+        }
+        if let Some(export_dir) = &self.options.source_export_dir {
+            return MetadataSource::Export(export_dir);
+        }
+        if let Some(sourcify_client) = &self.sourcify_client {
+            return MetadataSource::Sourcify(sourcify_client);
+        }
+        if !self.zksync_sources.is_empty() {
+            return MetadataSource::ZkSync(&self.zksync_sources[self.source_key(index)]);
+        }
+        match self.source_clients.len() {
+            0 => unreachable!(
+                "the source clients are built unless reading from a custom source, an export or \
+                 Sourcify"
+            ),
+            count => MetadataSource::Explorer(&self.source_clients[index % count]),
+        }
+    }
+
+    /// Target the contract with the given index is submitted to, the api keys of the target
+    /// block-explorer are rotated per contract
+    fn target_client(&self, index: usize) -> &TargetClient {
+        &self.target_clients[index % self.target_clients.len()]
+    }
+
+    /// Rate limiter of the api key [`CopyRun::target_client`] uses for the contract
+    fn target_limiter(&self, index: usize) -> &RateLimiter {
+        &self.target_limiters[index % self.target_limiters.len()]
+    }
+
+    /// Index of the source api key the contract with the given index uses
+    fn source_key(&self, index: usize) -> usize {
+        index % self.source_limiters.len()
+    }
+
+    /// Rate limiter of the source api key the contract with the given index uses
+    fn source_limiter(&self, index: usize) -> &RateLimiter {
+        &self.source_limiters[self.source_key(index)]
+    }
+
+    /// Fetcher of the bytecode using the source api key of the contract with the given index,
+    /// `None` unless reading from the source block-explorer
+    fn runtime_code(&self, index: usize) -> Option<&RuntimeCodeFetcher> {
+        self.runtime_codes.get(self.source_key(index))
+    }
+
+    /// Settings and hooks for copying the contract with the given index
+    fn context<'a>(&'a self, index: usize, events: Option<&'a EventHandler>) -> CopyContext<'a> {
+        let options = &self.options;
+        CopyContext {
+            pending: self.pending.as_ref(),
+            events,
+            retry: options.retry,
+            poll: options.poll,
+            poll_scheduler: Some(&self.poll_scheduler),
+            source_http: Some(&options.source_http),
+            source_limiter: Some(self.source_limiter(index)),
+            target_limiter: Some(self.target_limiter(index)),
+            additional_targets: &self.additional_targets,
+            libraries: &options.libraries,
+            remappings: &options.remappings,
+            settings_overrides: options.settings_overrides.as_ref(),
+            contract_paths: &options.contract_paths,
+            mark_proxies: options.mark_proxies,
+            skip_verified_check: options.skip_verified_check,
+            // zkSync bytecode is not EVM bytecode, so it is not inspected
+            runtime_code: self
+                .zksync_sources
+                .is_empty()
+                .then(|| self.runtime_code(index))
+                .flatten(),
+            clone_implementations: Some(&self.clone_implementations),
+            target_chain: self.target_chain.as_ref(),
+            compare_bytecode: options.compare_bytecode,
+            compile_locally: options.compile_locally,
+            check_constructor_arguments: options.check_constructor_arguments,
+            dry_run: false,
+        }
+    }
+}
+
+/// Fail with a timeout unless the future finishes within the contract timeout counted from
+/// `start`
+async fn with_contract_timeout<T>(
+    timeout: Option<Duration>,
+    start: Instant,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let Some(timeout) = timeout else {
+        return future.await;
+    };
+    tokio::time::timeout_at((start + timeout).into(), future)
+        .await
+        .unwrap_or_else(|_| {
+            Err(VerificationFailure::report(
+                FailureReason::Timeout,
+                format!("Contract timed out after {:?}", timeout),
+            ))
+        })
+}
+
+/// Build one task per contract copying its verification as part of the given run
+fn copy_tasks(
+    contracts: Vec<Contract>,
+    run: Arc<CopyRun>,
+) -> Vec<impl Future<Output = CopyOutcome>> {
+    let mp = initialize_multi_progress(run.options.progress_bar);
+    contracts
+        .into_iter()
+        .enumerate()
+        .map(move |(index, contract)| {
+            let pb = initialize_progress_bar(mp.clone(), &contract);
+            let events =
+                with_progress_bar_events(pb.clone(), &contract, run.options.events.clone());
+            let run = run.clone();
+            async move {
+                let options = &run.options;
+                let target_client = run.target_client(index);
+                // Set once the contract is worked on, the contract timeout counts from then
+                let start = OnceLock::new();
+                let mut details = VerificationDetails::default();
+                let copy = copy_contract_verification(
+                    contract.address.clone(),
+                    run.metadata_source(index),
+                    target_client,
+                    run.context(index, events.as_ref()),
+                    &mut details,
+                );
+                let copy = async {
+                    let _permit = run
+                        .semaphore
+                        .acquire()
+                        .await
+                        .expect("concurrency semaphore is never closed");
+                    let start = *start.get_or_init(Instant::now);
+                    with_contract_timeout(options.contract_timeout, start, copy).await
+                };
+                let copy = async {
+                    let (Some(deduplicator), Some(runtime_code)) =
+                        (&run.deduplicator, run.runtime_code(index))
+                    else {
+                        return copy.await;
+                    };
+                    let code = {
+                        let _permit = run
+                            .semaphore
+                            .acquire()
+                            .await
+                            .expect("concurrency semaphore is never closed");
+                        runtime_code
+                            .fetch(
+                                &contract.address,
+                                options.retry,
+                                Some(run.source_limiter(index)),
+                            )
+                            .await
+                    };
+                    match code {
+                        Ok(code) if !code.is_empty() => {
+                            match deduplicator.claim(&code) {
+                                BytecodeClaim::First(first) => {
+                                    let result = copy.await;
+                                    first.finish();
+                                    result
+                                }
+                                BytecodeClaim::Duplicate(duplicate) => {
+                                    let start = *start.get_or_init(Instant::now);
+                                    let verified = with_contract_timeout(
+                                        options.contract_timeout,
+                                        start,
+                                        async {
+                                            duplicate.wait().await;
+                                            let address = contract.address.parse()?;
+                                            options
+                                                .retry
+                                                .retry(Some(run.target_limiter(index)), || {
+                                                    target_client.is_verified(address)
+                                                })
+                                                .await
+                                        },
+                                    )
+                                    .await;
+                                    match verified {
+                                        // Verified duplicates still go through copying to submit
+                                        // them to the additional targets and mark proxies
+                                        Ok(true)
+                                            if run.additional_targets.is_empty()
+                                                && !options.mark_proxies =>
+                                        {
+                                            Ok(VerificationResult::AlreadyVerified)
+                                        }
+                                        Err(err)
+                                            if FailureReason::of(&err)
+                                                == FailureReason::Timeout =>
+                                        {
+                                            Err(err)
+                                        }
+                                        // Contracts the target did not match (or whose status
+                                        // is unknown) are submitted on their own
+                                        _ => copy.await,
+                                    }
+                                }
+                            }
+                        }
+                        // Contracts whose bytecode is unknown are copied on their own
+                        _ => copy.await,
+                    }
+                };
+                let copy = async {
+                    match run.deadline {
+                        Some(deadline) => tokio::time::timeout_at(deadline, copy)
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(VerificationFailure::report(
+                                    FailureReason::Skipped,
+                                    "Cancelled because the deadline of the run was reached",
+                                ))
+                            }),
+                        None => copy.await,
+                    }
+                };
+                let result = match &options.shutdown {
+                    Some(shutdown) => tokio::select! {
+                        result = copy => result,
+                        _ = shutdown.triggered() => Err(VerificationFailure::report(
+                            FailureReason::Skipped,
+                            "Cancelled because the run was interrupted",
+                        )),
+                    },
+                    None => copy.await,
+                };
+                details.skipped = options.skip_unverified
+                    && result.as_ref().is_err_and(|err| {
+                        FailureReason::of(err) == FailureReason::SourceNotVerified
+                    });
+                details.duration = start.get().map_or(Duration::ZERO, Instant::elapsed);
+                if let (Ok(_), None, Ok(address)) = (
+                    &result,
+                    &details.target_link,
+                    contract.address.parse::<alloy_primitives::Address>(),
+                ) {
+                    // Contracts matched via their bytecode are never submitted themselves
+                    details.target_link = Some(target_client.address_url(address));
+                }
+                if let Some(events) = &events {
+                    events.emit(match &result {
+                        Ok(result) => VerificationEvent::Verified {
+                            address: contract.address.clone(),
+                            already_verified: matches!(result, VerificationResult::AlreadyVerified),
+                        },
+                        Err(err) => {
+                            let reason = FailureReason::of(err);
+                            VerificationEvent::Failed {
+                                address: contract.address.clone(),
+                                reason,
+                                transient: reason.is_transient(),
+                                error: format!("{:#}", err),
+                            }
+                        }
+                    });
+                }
+                CopyOutcome {
+                    contract,
+                    result,
+                    details,
+                }
+            }
+            .then(move |outcome| {
+                update_progress_bar(pb, &outcome.result, outcome.details.target_link.as_deref());
+                futures::future::ready(outcome)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_rate_limits() {
+        let options = CopyOptions {
+            target_rpc_url: Some("http://localhost:8545".to_string()),
+            target_rpc_max_requests_per_second: Some(0.0),
+            ..Default::default()
+        };
+        let run = CopyRun::new(
+            String::new(),
+            "https://api.etherscan.io/api".to_string(),
+            String::new(),
+            "https://eth.blockscout.com/api".to_string(),
+            &options,
+        );
+        assert!(run.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_copy_verification_from_etherscan_to_blockscout() {
+        let results = copy_etherscan_verification(
+            contract_addresses(),
+            etherscan_api_key(),
+            etherscan_url(),
+            blockscout_api_key(),
+            blockscout_url(),
+            false,
+        )
+        .await;
+        assert!(!results.into_iter().any(|(_, result)| result.is_err()));
+    }
+
+    #[tokio::test]
+    // TODO: Re-enable test once the fix in foundry-block-explorers is pushed to crates.io
+    // See: https://github.com/foundry-rs/block-explorers/commit/472118dafcbeb9bfdcd8df1c11ef28a5c51884d9
+    #[ignore]
+    async fn test_copy_verification_from_blockscout_to_etherscan() {
+        let results = copy_etherscan_verification(
+            contract_addresses(),
+            blockscout_api_key(),
+            blockscout_url(),
+            etherscan_api_key(),
+            etherscan_url(),
+            false,
+        )
+        .await;
+        assert!(!results.into_iter().any(|(_, result)| result.is_err()));
+    }
+
+    // Complex contract verified in "standard-solidity-json" format (non-flattened)
+    const UNI_V3_ROUTER: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
+    // Complex contract verified in flattened format on etherscan
+    const ICETH_TOKEN: &str = "0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84";
+    // Proxy contract - note that this should only attempt to migrate / copy the proxy itself
+    // and not the implementation contract
+    const BLUR: &str = "0x000000000000Ad05Ccc4F10045630fb830B95127";
+
+    fn contract_addresses() -> Vec<String> {
+        vec![
+            UNI_V3_ROUTER.to_string(),
+            ICETH_TOKEN.to_string(),
+            BLUR.to_string(),
+        ]
+    }
+    fn etherscan_url() -> String {
+        "https://api.etherscan.io/api".to_string()
+    }
+    fn etherscan_api_key() -> String {
+        std::env::var("ETHERSCAN_API_KEY").expect("ETHERSCAN_API_KEY not set")
+    }
+
+    fn blockscout_url() -> String {
+        "https://eth.blockscout.com/api".to_string()
+    }
+
+    fn blockscout_api_key() -> String {
+        std::env::var("BLOCKSCOUT_API_KEY").expect("BLOCKSCOUT_API_KEY not set")
+    }
+}
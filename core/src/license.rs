@@ -0,0 +1,64 @@
+/// Key of the license in [`VerifyContract::other`](foundry_block_explorers::verify::VerifyContract),
+/// sent along as the numeric license code of Etherscan compatible apis
+pub(crate) const LICENSE_TYPE_PARAMETER: &str = "licenseType";
+
+/// Licenses known to Etherscan: numeric code, name reported in the metadata, SPDX identifier and
+/// license type of Blockscout's REST api
+const LICENSES: &[(u8, &str, &str, &str)] = &[
+    (1, "None", "UNLICENSED", "none"),
+    (2, "Unlicense", "Unlicense", "unlicense"),
+    (3, "MIT", "MIT", "mit"),
+    (4, "GNU GPLv2", "GPL-2.0", "gnu_gpl_v2"),
+    (5, "GNU GPLv3", "GPL-3.0", "gnu_gpl_v3"),
+    (6, "GNU LGPLv2.1", "LGPL-2.1", "gnu_lgpl_v2_1"),
+    (7, "GNU LGPLv3", "LGPL-3.0", "gnu_lgpl_v3"),
+    (8, "BSD-2-Clause", "BSD-2-Clause", "bsd_2_clause"),
+    (9, "BSD-3-Clause", "BSD-3-Clause", "bsd_3_clause"),
+    (10, "MPL-2.0", "MPL-2.0", "mpl_2_0"),
+    (11, "OSL-3.0", "OSL-3.0", "osl_3_0"),
+    (12, "Apache-2.0", "Apache-2.0", "apache_2_0"),
+    (13, "GNU AGPLv3", "AGPL-3.0", "gnu_agpl_v3"),
+    (14, "BSL 1.1", "BUSL-1.1", "bsl_1_1"),
+];
+
+/// Etherscan's numeric code of the license reported in the metadata, which is either the name
+/// used by Etherscan (e.g. `GNU GPLv3`) or an SPDX identifier (e.g. `GPL-3.0-or-later`)
+pub(crate) fn etherscan_license_code(license: &str) -> Option<u8> {
+    let license = license.trim();
+    if license.is_empty() {
+        return None;
+    }
+    let spdx = license
+        .trim_end_matches("-only")
+        .trim_end_matches("-or-later");
+    LICENSES
+        .iter()
+        .find(|(_, name, spdx_id, _)| {
+            name.eq_ignore_ascii_case(license) || spdx_id.eq_ignore_ascii_case(spdx)
+        })
+        .map(|(code, ..)| *code)
+}
+
+/// License type of Blockscout's REST api for Etherscan's numeric code, `none` if unknown
+pub(crate) fn blockscout_license_type(code: Option<&str>) -> &'static str {
+    code.and_then(|code| code.parse::<u8>().ok())
+        .and_then(|code| LICENSES.iter().find(|(known, ..)| *known == code))
+        .map_or("none", |(.., license_type)| license_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_codes() {
+        assert_eq!(etherscan_license_code("MIT"), Some(3));
+        assert_eq!(etherscan_license_code("GNU GPLv3"), Some(5));
+        assert_eq!(etherscan_license_code("GPL-3.0-or-later"), Some(5));
+        assert_eq!(etherscan_license_code("None"), Some(1));
+        assert_eq!(etherscan_license_code(""), None);
+        assert_eq!(etherscan_license_code("WTFPL"), None);
+        assert_eq!(blockscout_license_type(Some("12")), "apache_2_0");
+        assert_eq!(blockscout_license_type(None), "none");
+    }
+}
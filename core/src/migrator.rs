@@ -0,0 +1,202 @@
+use crate::events::{EventHandler, ProgressObserver};
+use crate::input::Contract;
+use crate::poll::PollOptions;
+use crate::report::MigrationReport;
+use crate::retry::RetryPolicy;
+use crate::target::TargetType;
+use crate::{copy_tasks, CopyOptions, CopyOutcome, CopyRun};
+use eyre::{eyre, Result};
+use futures::stream::{BoxStream, FuturesUnordered, StreamExt};
+use std::fmt;
+use std::sync::Arc;
+
+/// Copies the verification of contracts from a source to a target block-explorer, configured via
+/// [`Migrator::builder`]
+///
+/// All runs of a migrator (and its clones) share the clients, rate limits and polling of the
+/// target, so contracts can be copied in batches without exceeding the rate limits. The
+/// [`deadline`](CopyOptions::deadline) counts from building the migrator.
+///
+/// # Examples
+///
+/// ```rust
+/// use contract_verification_migrator_core::Migrator;
+///
+/// # async fn run() -> eyre::Result<()> {
+/// let migrator = Migrator::builder()
+///     .source_url("https://api.etherscan.io/api")
+///     .source_api_key("<YOUR_ETHERSCAN_API_KEY>")
+///     .target_url("https://eth.blockscout.com/api")
+///     .target_api_key("<YOUR_BLOCKSCOUT_API_KEY>")
+///     .concurrency(10)
+///     .build()?;
+/// let outcomes = migrator
+///     .run(vec!["0xE592427A0AEce92De3Edee1F18E0157C05861564"])
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Migrator {
+    run: Arc<CopyRun>,
+}
+
+/// Builder of a [`Migrator`], see [`Migrator::builder`]
+#[derive(Debug, Clone, Default)]
+pub struct MigratorBuilder {
+    source_url: Option<String>,
+    source_api_key: String,
+    target_url: Option<String>,
+    target_api_key: String,
+    options: CopyOptions,
+}
+
+impl Migrator {
+    /// Start configuring a migrator, which needs at least the url of the source and target
+    /// block-explorer's api
+    pub fn builder() -> MigratorBuilder {
+        MigratorBuilder::default()
+    }
+
+    /// Copy the verification of the contracts, see
+    /// [`copy_etherscan_verification_with_options`](crate::copy_etherscan_verification_with_options)
+    pub async fn run(&self, contracts: Vec<impl Into<Contract>>) -> Result<Vec<CopyOutcome>> {
+        Ok(futures::future::join_all(copy_tasks(
+            contracts.into_iter().map(Into::into).collect(),
+            self.run.clone(),
+        ))
+        .await)
+    }
+
+    /// Copy the verification of the contracts, yielding the outcome of each contract as soon as
+    /// it completes, see
+    /// [`copy_etherscan_verification_stream`](crate::copy_etherscan_verification_stream)
+    pub fn stream(&self, contracts: Vec<impl Into<Contract>>) -> BoxStream<'static, CopyOutcome> {
+        copy_tasks(
+            contracts.into_iter().map(Into::into).collect(),
+            self.run.clone(),
+        )
+        .into_iter()
+        .collect::<FuturesUnordered<_>>()
+        .boxed()
+    }
+
+    /// Copy the verification of the contracts like [`Migrator::run`], summarizing the outcomes in a
+    /// report that can be written in any [`ReportFormat`](crate::ReportFormat)
+    pub async fn run_report(&self, contracts: Vec<impl Into<Contract>>) -> Result<MigrationReport> {
+        Ok(MigrationReport::new(&self.run(contracts).await?))
+    }
+
+    /// Options the contracts are copied with
+    pub fn options(&self) -> &CopyOptions {
+        &self.run.options
+    }
+}
+
+impl fmt::Debug for Migrator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrator")
+            .field("options", self.options())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MigratorBuilder {
+    /// The url of the source block-explorer's api
+    pub fn source_url(mut self, url: impl Into<String>) -> Self {
+        self.source_url = Some(url.into());
+        self
+    }
+
+    /// The api key for the source block-explorer's api
+    pub fn source_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.source_api_key = api_key.into();
+        self
+    }
+
+    /// The url of the target block-explorer's api
+    pub fn target_url(mut self, url: impl Into<String>) -> Self {
+        self.target_url = Some(url.into());
+        self
+    }
+
+    /// The api key for the target block-explorer's api
+    pub fn target_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.target_api_key = api_key.into();
+        self
+    }
+
+    /// Api of the target block-explorer, detected from the url if not set
+    pub fn target_type(mut self, target_type: TargetType) -> Self {
+        self.options.target_type = Some(target_type);
+        self
+    }
+
+    /// Maximum number of contracts processed concurrently, defaults to 5
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.options.concurrency = Some(concurrency);
+        self
+    }
+
+    /// Options for polling the target block-explorer for the outcome of submitted verifications
+    pub fn poll(mut self, poll: PollOptions) -> Self {
+        self.options.poll = poll;
+        self
+    }
+
+    /// Policy for retrying requests that failed with a transient error
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.options.retry = retry;
+        self
+    }
+
+    /// Display progress bars for the individual contracts, requires the `progress-bar` feature
+    pub fn progress_bar(mut self, progress_bar: bool) -> Self {
+        self.options.progress_bar = progress_bar;
+        self
+    }
+
+    /// Observer receiving the progress of each contract, in addition to the progress bars if
+    /// enabled
+    pub fn observer(mut self, observer: impl ProgressObserver + 'static) -> Self {
+        self.options.events = Some(EventHandler::observer(observer));
+        self
+    }
+
+    /// Replace all options, e.g. to set the ones without a method of their own; call it before
+    /// the other methods, which modify the options
+    pub fn options(mut self, options: CopyOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Build the migrator, failing if an url is missing or an option is invalid
+    ///
+    /// The source url is optional when reading from an export, Sourcify or a
+    /// [`SourceExplorer`](crate::SourceExplorer) and the target url when submitting to a
+    /// [`TargetExplorer`](crate::TargetExplorer).
+    pub fn build(self) -> Result<Migrator> {
+        let options = self.options;
+        let reads_from_source_url = options.source_export_dir.is_none()
+            && options.source_sourcify.is_none()
+            && options.source_explorer.is_none();
+        let source_url = match self.source_url {
+            Some(source_url) => source_url,
+            None if !reads_from_source_url => String::new(),
+            None => return Err(eyre!("The url of the source block-explorer is not set")),
+        };
+        let target_url = match self.target_url {
+            Some(target_url) => target_url,
+            None if options.target_explorer.is_some() => String::new(),
+            None => return Err(eyre!("The url of the target block-explorer is not set")),
+        };
+        let run = CopyRun::new(
+            self.source_api_key,
+            source_url,
+            self.target_api_key,
+            target_url,
+            &options,
+        )?;
+        Ok(Migrator { run })
+    }
+}
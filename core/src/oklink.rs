@@ -0,0 +1,93 @@
+use crate::http::ExplorerHttpOptions;
+use eyre::Result;
+use reqwest::header::{HeaderMap, HeaderValue};
+
+/// Header OKLink expects the api key in, the `apikey` parameter of Etherscan is not sufficient
+const ACCESS_KEY_HEADER: &str = "Ok-Access-Key";
+
+/// Website of OKLink, the pages of each chain are below its short name (e.g. `xlayer/`)
+const OKLINK_BROWSER_URL: &str = "https://www.oklink.com/";
+
+/// Returns whether the url belongs to the Etherscan compatible api of OKLink (e.g.
+/// `https://www.oklink.com/api/v5/explorer/contract/verify-source-code-plugin/XLAYER`)
+pub(crate) fn is_oklink_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(ToString::to_string))
+        .is_some_and(|host| host == "oklink.com" || host.ends_with(".oklink.com"))
+}
+
+/// Http client sending the api key in the header required by OKLink with every request
+pub(crate) fn http_client(
+    api_key: &str,
+    http_options: &ExplorerHttpOptions,
+) -> Result<reqwest::Client> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCESS_KEY_HEADER, HeaderValue::from_str(api_key)?);
+    Ok(http_options
+        .client_builder()?
+        .default_headers(headers)
+        .build()?)
+}
+
+/// Website of the chain whose short name is the last segment of the api url
+pub(crate) fn browser_url(api_url: &str) -> String {
+    let chain = reqwest::Url::parse(api_url)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()?
+                .rfind(|segment| !segment.is_empty())
+                .map(str::to_lowercase)
+        })
+        .filter(|chain| chain != "api");
+    match chain {
+        Some(chain) => format!("{}{}/", OKLINK_BROWSER_URL, chain),
+        None => OKLINK_BROWSER_URL.to_string(),
+    }
+}
+
+/// Map the status messages of OKLink (e.g. `Success`, `Fail` or `In progress`) to the ones of
+/// Etherscan
+pub(crate) fn normalize_status(status: &str) -> String {
+    let lowercase = status.trim().to_lowercase();
+    if lowercase.contains("already verified") {
+        "Already Verified".to_string()
+    } else if lowercase.starts_with("pass") || lowercase == "success" || lowercase == "verified" {
+        "Pass - Verified".to_string()
+    } else if lowercase.starts_with("fail") || lowercase.contains("unable to verify") {
+        format!("Fail - Unable to verify: {}", status)
+    } else if lowercase.contains("pending") || lowercase.contains("progress") {
+        "Pending in queue".to_string()
+    } else {
+        status.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_url() {
+        let api_url =
+            "https://www.oklink.com/api/v5/explorer/contract/verify-source-code-plugin/XLAYER";
+        assert!(is_oklink_url(api_url));
+        assert!(!is_oklink_url("https://api.etherscan.io/api"));
+        assert_eq!(browser_url(api_url), "https://www.oklink.com/xlayer/");
+        assert_eq!(
+            browser_url("https://www.oklink.com/api"),
+            "https://www.oklink.com/"
+        );
+    }
+
+    #[test]
+    fn test_normalize_status() {
+        assert_eq!(normalize_status("Success"), "Pass - Verified");
+        assert_eq!(normalize_status("In progress"), "Pending in queue");
+        assert_eq!(
+            normalize_status("Contract source code already verified"),
+            "Already Verified"
+        );
+        assert_eq!(normalize_status("Fail"), "Fail - Unable to verify: Fail");
+    }
+}
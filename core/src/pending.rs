@@ -0,0 +1,175 @@
+use crate::http::ExplorerHttpOptions;
+use crate::poll::PollOptions;
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::target::{additional_target_clients, AdditionalTarget, TargetClient, TargetType};
+use crate::verification::{
+    await_contract_verification, submit_to_additional_targets, CopyContext, VerificationDetails,
+    VerificationResult,
+};
+use eyre::{Context, Result};
+use foundry_block_explorers::verify::VerifyContract;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A verification that was submitted to the target block-explorer but whose outcome is unknown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingVerification {
+    /// The contract's address
+    pub address: String,
+    /// The GUID returned by the target block-explorer on submission
+    pub guid: String,
+    /// The submitted verification, recorded to submit it to the additional targets once it
+    /// passed on the target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_request: Option<VerifyContract>,
+}
+
+/// File backed list of pending verifications, updated whenever a verification is submitted or
+/// completes successfully
+pub(crate) struct PendingVerificationStore {
+    path: PathBuf,
+    entries: Mutex<Vec<PendingVerification>>,
+}
+
+impl PendingVerificationStore {
+    pub(crate) fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = if path.exists() {
+            read_pending_verifications(&path)?
+        } else {
+            Vec::new()
+        };
+        Ok(PendingVerificationStore {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    pub(crate) fn insert(
+        &self,
+        address: &str,
+        guid: &str,
+        verification_request: Option<&VerifyContract>,
+    ) -> Result<()> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("pending verifications lock poisoned");
+        entries.retain(|entry| !entry.address.eq_ignore_ascii_case(address));
+        entries.push(PendingVerification {
+            address: address.to_string(),
+            guid: guid.to_string(),
+            verification_request: verification_request.cloned(),
+        });
+        self.save(&entries)
+    }
+
+    pub(crate) fn remove(&self, guid: &str) -> Result<()> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("pending verifications lock poisoned");
+        entries.retain(|entry| entry.guid != guid);
+        self.save(&entries)
+    }
+
+    fn save(&self, entries: &[PendingVerification]) -> Result<()> {
+        std::fs::write(&self.path, serde_json::to_string_pretty(entries)?).wrap_err_with(|| {
+            format!(
+                "Failed to write pending verifications to {}",
+                self.path.display()
+            )
+        })
+    }
+}
+
+/// Read the pending verifications from a GUID file written during a previous run
+///
+/// # Arguments
+/// - `path` - Path to the GUID file
+pub fn read_pending_verifications(path: impl AsRef<Path>) -> Result<Vec<PendingVerification>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read pending verifications {}", path.display()))?;
+    serde_json::from_str(&content)
+        .wrap_err_with(|| format!("Failed to parse pending verifications {}", path.display()))
+}
+
+/// Resume polling the status of verifications submitted during a previous run
+///
+/// Reads the GUIDs from the given file, waits for the outcome of each verification and removes
+/// every verification that completed successfully from the file. Successful verifications are
+/// then submitted to the additional targets, whose failures are returned as warnings alongside
+/// the result.
+///
+/// # Arguments
+/// - `guid_file` - The GUID file written during the previous run
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `target_http` - Settings of the http clients connecting to the target block-explorer
+/// - `target_type` - Api of the target block-explorer the verifications were submitted to,
+///   detected from the url if `None`
+/// - `retry` - Policy for retrying status requests that failed with a transient error
+/// - `poll` - Options for polling the outcome of the verifications
+/// - `additional_targets` - Targets the verifications are submitted to once they passed on the
+///   target block-explorer, see [`CopyOptions::additional_targets`](crate::CopyOptions)
+#[allow(clippy::too_many_arguments)]
+pub async fn resume_etherscan_verification(
+    guid_file: impl AsRef<Path>,
+    target_api_key: String,
+    target_url: String,
+    target_http: &ExplorerHttpOptions,
+    target_type: Option<TargetType>,
+    retry: RetryPolicy,
+    poll: PollOptions,
+    additional_targets: &[AdditionalTarget],
+) -> Result<Vec<(PendingVerification, Result<VerificationResult>, Vec<String>)>> {
+    let store = PendingVerificationStore::open(guid_file)?;
+    let target_client =
+        TargetClient::new(target_type, target_api_key, target_url, None, target_http)?;
+    let additional_targets = additional_target_clients(additional_targets)?;
+    let pending = store
+        .entries
+        .lock()
+        .expect("pending verifications lock poisoned")
+        .clone();
+    let target_limiter = RateLimiter::unlimited();
+    let context = CopyContext {
+        retry,
+        poll,
+        target_limiter: Some(&target_limiter),
+        additional_targets: &additional_targets,
+        ..Default::default()
+    };
+    let tasks = pending.into_iter().map(|verification| {
+        let target_client = &target_client;
+        let store = &store;
+        async move {
+            let result =
+                await_contract_verification(verification.guid.clone(), target_client, context)
+                    .await;
+            let mut details = VerificationDetails::default();
+            if result.is_ok() {
+                if let Err(err) = store.remove(&verification.guid) {
+                    return (verification, Err(err), details.warnings);
+                }
+                match &verification.verification_request {
+                    Some(verification_request) => {
+                        submit_to_additional_targets(verification_request, context, &mut details)
+                            .await
+                    }
+                    None if !context.additional_targets.is_empty() => details.warnings.push(
+                        "The verification was not recorded for the additional targets, copy it \
+                         again to submit it to them"
+                            .to_string(),
+                    ),
+                    None => {}
+                }
+            }
+            (verification, result, details.warnings)
+        }
+    });
+    Ok(futures::future::join_all(tasks).await)
+}
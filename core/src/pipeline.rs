@@ -0,0 +1,90 @@
+use crate::failure::{FailureStage, MigrationError};
+use crate::http::ExplorerHttpOptions;
+use crate::poll::PollOptions;
+use crate::target::{TargetClient, TargetType};
+use crate::verification::{
+    await_contract_verification, build_client, fetch_verified_metadata, CopyContext,
+    VerificationRequestResponse, VerificationResult,
+};
+use eyre::Result;
+use foundry_block_explorers::contract::Metadata;
+use foundry_block_explorers::verify::VerifyContract;
+
+/// Fetch the verified metadata of a contract from the source block-explorer, `None` if the
+/// contract is not verified
+///
+/// Together with [`convert_metadata_to_verification_request`], [`submit_verification`] and
+/// [`await_verification`] this covers the steps of copying a verification one by one, e.g. to
+/// fetch on one machine and submit on another.
+///
+/// [`convert_metadata_to_verification_request`]: crate::convert_metadata_to_verification_request
+///
+/// # Arguments
+/// - `contract_address` - The contract address to fetch the metadata of
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+pub async fn fetch_source_metadata(
+    contract_address: &str,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+) -> Result<Option<Metadata>> {
+    let source_client = build_client(source_api_key, source_url, source_http)?;
+    fetch_verified_metadata(&source_client, contract_address)
+        .await
+        .map_err(|err| MigrationError::staged(FailureStage::Fetch, err))
+}
+
+/// Submit a verification request to the target block-explorer, returning the id to await the
+/// outcome with or `None` if the contract is verified already
+///
+/// # Arguments
+/// - `verification_request` - The verification request to submit
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `target_http` - Settings of the http clients connecting to the target block-explorer
+/// - `target_type` - Api of the target block-explorer, detected from `target_url` if not set
+pub async fn submit_verification(
+    verification_request: &VerifyContract,
+    target_api_key: String,
+    target_url: String,
+    target_http: &ExplorerHttpOptions,
+    target_type: Option<TargetType>,
+) -> Result<Option<String>> {
+    let target_client =
+        TargetClient::new(target_type, target_api_key, target_url, None, target_http)?;
+    match target_client.submit(verification_request).await {
+        Ok(VerificationRequestResponse::Submitted(id)) => Ok(Some(id)),
+        Ok(VerificationRequestResponse::AlreadyVerified) => Ok(None),
+        Err(err) => Err(MigrationError::staged(FailureStage::Submit, err)),
+    }
+}
+
+/// Poll the target block-explorer until the submitted verification succeeded or failed
+///
+/// # Arguments
+/// - `id` - The id returned by [`submit_verification`]
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `target_http` - Settings of the http clients connecting to the target block-explorer
+/// - `target_type` - Api of the target block-explorer, detected from `target_url` if not set
+/// - `poll` - Options for polling the outcome of the verification
+pub async fn await_verification(
+    id: String,
+    target_api_key: String,
+    target_url: String,
+    target_http: &ExplorerHttpOptions,
+    target_type: Option<TargetType>,
+    poll: PollOptions,
+) -> Result<VerificationResult> {
+    let target_client =
+        TargetClient::new(target_type, target_api_key, target_url, None, target_http)?;
+    let context = CopyContext {
+        poll,
+        ..Default::default()
+    };
+    await_contract_verification(id, &target_client, context)
+        .await
+        .map_err(|err| MigrationError::staged(FailureStage::Poll, err))
+}
@@ -0,0 +1,303 @@
+use crate::failure::{FailureReason, VerificationFailure};
+use crate::target::TargetType;
+use crate::verification::{verification_status, VerificationResult};
+use eyre::{eyre, Result, WrapErr};
+use futures::future::BoxFuture;
+use std::collections::VecDeque;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+/// Options for polling the target block-explorer for the outcome of a submitted verification
+///
+/// The time between two status requests starts at `interval` and is multiplied by `backoff`
+/// after every request until it reaches `max_interval`, so quick verifications are noticed fast
+/// without hammering the explorer while slow ones are pending.
+///
+/// Set as [`CopyOptions::poll`](crate::CopyOptions::poll) when copying and passed to
+/// [`await_verification`](crate::await_verification) to poll a single verification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollOptions {
+    /// Time to wait between the first two status requests
+    pub interval: Duration,
+    /// Maximum number of status requests before the verification is considered timed out
+    pub max_attempts: u32,
+    /// Time to wait after submission before the first status request
+    pub initial_delay: Duration,
+    /// Factor the interval is multiplied with after every status request, `1.0` polls at a fixed
+    /// interval
+    pub backoff: f64,
+    /// Upper bound for the time between two status requests
+    pub max_interval: Duration,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        PollOptions {
+            interval: Duration::from_secs(2),
+            max_attempts: 10,
+            initial_delay: Duration::ZERO,
+            backoff: 2.0,
+            max_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PollOptions {
+    /// Options suited to the api of the target block-explorer
+    ///
+    /// Blockscout v2 reports rejected verifications only once polling gives up and the zkSync Era
+    /// block explorer compiles the sources on submission, so both are polled for longer than the
+    /// Etherscan compatible apis.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use contract_verification_migrator_core::{PollOptions, TargetType};
+    ///
+    /// let poll = PollOptions {
+    ///     max_attempts: 30,
+    ///     ..PollOptions::for_target(TargetType::BlockscoutV2)
+    /// };
+    /// ```
+    pub fn for_target(target_type: TargetType) -> Self {
+        match target_type {
+            TargetType::Etherscan | TargetType::Routescan | TargetType::OkLink => {
+                PollOptions::default()
+            }
+            TargetType::BlockscoutV2 => PollOptions {
+                initial_delay: Duration::from_secs(5),
+                max_attempts: 15,
+                ..PollOptions::default()
+            },
+            TargetType::ZkSync => PollOptions {
+                interval: Duration::from_secs(5),
+                max_attempts: 20,
+                ..PollOptions::default()
+            },
+        }
+    }
+
+    /// Time to wait after the given (zero based) status request before the next one
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let factor = self
+            .backoff
+            .max(1.0)
+            .powi(attempt.min(i32::MAX as u32) as i32);
+        let delay = self.interval.as_secs_f64() * factor;
+        if delay.is_finite() && delay < self.max_interval.as_secs_f64() {
+            Duration::from_secs_f64(delay)
+        } else {
+            self.max_interval.max(self.interval)
+        }
+    }
+}
+
+/// Request returning the status message of the verification with the given GUID
+type StatusCheck = Arc<dyn Fn(String) -> BoxFuture<'static, Result<String>> + Send + Sync>;
+
+/// Polls the status of all pending verifications of a run from a single task
+///
+/// Instead of every contract polling its GUID on its own timer, the scheduler rotates over the
+/// pending GUIDs and checks one at a time in the order they are due. At most one status request
+/// is in flight, so many verifications in progress do not flood the target block-explorer.
+pub(crate) struct PollScheduler {
+    check: StatusCheck,
+    sender: OnceLock<mpsc::UnboundedSender<PendingPoll>>,
+}
+
+struct PendingPoll {
+    guid: String,
+    options: PollOptions,
+    /// Number of status requests sent so far
+    attempts: u32,
+    due: Instant,
+    result: oneshot::Sender<Result<VerificationResult>>,
+}
+
+impl PollScheduler {
+    pub(crate) fn new(
+        check: impl Fn(String) -> BoxFuture<'static, Result<String>> + Send + Sync + 'static,
+    ) -> Self {
+        PollScheduler {
+            check: Arc::new(check),
+            sender: OnceLock::new(),
+        }
+    }
+
+    /// Wait for the outcome of the verification with the given GUID
+    pub(crate) async fn await_verification(
+        &self,
+        guid: String,
+        options: PollOptions,
+    ) -> Result<VerificationResult> {
+        if options.max_attempts == 0 {
+            return Err(timed_out());
+        }
+        let (result, receiver) = oneshot::channel();
+        let poll = PendingPoll {
+            guid,
+            options,
+            attempts: 0,
+            due: Instant::now() + options.initial_delay,
+            result,
+        };
+        // The scheduler is started with the first submitted verification, so it runs on the
+        // runtime of the caller
+        let sender = self.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            tokio::spawn(run_scheduler(self.check.clone(), receiver));
+            sender
+        });
+        sender
+            .send(poll)
+            .map_err(|_| eyre!("Poll scheduler stopped"))?;
+        receiver
+            .await
+            .map_err(|_| eyre!("Poll scheduler stopped"))?
+    }
+}
+
+/// Check the due verifications one after the other until no more verifications can be added
+/// and all pending ones are finished
+async fn run_scheduler(check: StatusCheck, mut receiver: mpsc::UnboundedReceiver<PendingPoll>) {
+    let mut pending = VecDeque::new();
+    let mut open = true;
+    loop {
+        // Verifications of cancelled contracts (e.g. because of a timeout) are no longer polled
+        pending.retain(|poll: &PendingPoll| !poll.result.is_closed());
+        // Polls that were checked are moved to the back, so the first due one is picked in
+        // rotation
+        let next = pending
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, poll)| poll.due)
+            .map(|(index, poll)| (index, poll.due));
+        let Some((index, due)) = next else {
+            match receiver.recv().await {
+                Some(poll) => pending.push_back(poll),
+                None => return,
+            }
+            continue;
+        };
+        tokio::select! {
+            received = receiver.recv(), if open => {
+                match received {
+                    Some(poll) => pending.push_back(poll),
+                    None => open = false,
+                }
+                continue;
+            }
+            _ = tokio::time::sleep_until(due) => {}
+        }
+        let mut poll = pending.remove(index).expect("index of a pending poll");
+        let status = check(poll.guid.clone())
+            .await
+            .wrap_err("Failed to request verification status");
+        poll.attempts += 1;
+        let outcome = match status {
+            Ok(status) => verification_status(&status),
+            Err(err) => Some(Err(err)),
+        };
+        match outcome {
+            Some(result) => {
+                let _ = poll.result.send(result);
+            }
+            None if poll.attempts >= poll.options.max_attempts => {
+                let _ = poll.result.send(Err(timed_out()));
+            }
+            None => {
+                // Wait before checking again, backing off the longer the verification takes
+                poll.due = Instant::now() + poll.options.delay(poll.attempts - 1);
+                pending.push_back(poll);
+            }
+        }
+    }
+}
+
+pub(crate) fn timed_out() -> eyre::Report {
+    VerificationFailure::report(FailureReason::Timeout, "Verification timed out")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_delay() {
+        let options = PollOptions::default();
+        assert_eq!(options.delay(0), Duration::from_secs(2));
+        assert_eq!(options.delay(2), Duration::from_secs(8));
+        assert_eq!(options.delay(4), Duration::from_secs(30));
+        assert_eq!(options.delay(1000), Duration::from_secs(30));
+
+        let options = PollOptions {
+            interval: Duration::from_secs(10),
+            backoff: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(options.delay(5), Duration::from_secs(10));
+
+        assert_eq!(
+            PollOptions::for_target(TargetType::Etherscan),
+            PollOptions::default()
+        );
+        assert!(PollOptions::for_target(TargetType::ZkSync).max_attempts > options.max_attempts);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_checks_one_guid_at_a_time() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let checks = Arc::new(AtomicUsize::new(0));
+        let scheduler = PollScheduler::new({
+            let in_flight = in_flight.clone();
+            let checks = checks.clone();
+            move |guid| {
+                let in_flight = in_flight.clone();
+                let checks = checks.clone();
+                async move {
+                    assert_eq!(in_flight.fetch_add(1, Ordering::SeqCst), 0);
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    // Every verification is pending for its first check
+                    Ok(
+                        match (guid.as_str(), checks.fetch_add(1, Ordering::SeqCst) >= 3) {
+                            (_, false) => "Pending in queue".to_string(),
+                            ("rejected", true) => "Fail - Unable to verify".to_string(),
+                            (_, true) => "Pass - Verified".to_string(),
+                        },
+                    )
+                }
+                .boxed()
+            }
+        });
+        let options = PollOptions {
+            interval: Duration::from_millis(5),
+            ..Default::default()
+        };
+        let (verified, rejected, pending) = tokio::join!(
+            scheduler.await_verification("verified".to_string(), options),
+            scheduler.await_verification("rejected".to_string(), options),
+            scheduler.await_verification(
+                "pending".to_string(),
+                PollOptions {
+                    max_attempts: 1,
+                    ..options
+                }
+            ),
+        );
+        assert!(matches!(verified, Ok(VerificationResult::Success)));
+        assert_eq!(
+            FailureReason::of(&rejected.unwrap_err()),
+            FailureReason::TargetRejected
+        );
+        assert_eq!(
+            FailureReason::of(&pending.unwrap_err()),
+            FailureReason::Timeout
+        );
+        assert_eq!(checks.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::events::{EventHandler, VerificationEvent};
+use crate::input::Contract;
+use crate::verification::VerificationResult;
+use console::style;
+use eyre::Result;
+use indicatif::{MultiProgress, MultiProgressAlignment, ProgressBar, ProgressStyle};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub fn initialize_multi_progress(progress_bar: bool) -> Option<Arc<MultiProgress>> {
+    if progress_bar {
+        let mp = Arc::new(MultiProgress::new());
+        mp.set_alignment(MultiProgressAlignment::Top);
+        Some(mp)
+    } else {
+        None
+    }
+}
+
+pub fn initialize_progress_bar(
+    mp: Option<Arc<MultiProgress>>,
+    contract: &Contract,
+) -> Option<ProgressBar> {
+    if let Some(mp) = mp.clone() {
+        let pb = mp.add(ProgressBar::new_spinner());
+        pb.enable_steady_tick(Duration::from_millis(120));
+        pb.set_style(ProgressStyle::with_template("{prefix}{msg}{spinner:.yellow} ").unwrap());
+        pb.set_prefix(format!("{} - ", grouped_display_name(contract)));
+        pb.set_message(format!("{}", style("Copying ").yellow()));
+        Some(pb)
+    } else {
+        None
+    }
+}
+
+/// Wrap the given event handler so that the progress bar is updated on every state transition
+pub fn with_progress_bar_events(
+    pb: Option<ProgressBar>,
+    contract: &Contract,
+    events: Option<EventHandler>,
+) -> Option<EventHandler> {
+    let Some(pb) = pb else {
+        return events;
+    };
+    let display_name = grouped_display_name(contract);
+    Some(EventHandler::new(move |event| {
+        match event {
+            VerificationEvent::Fetched {
+                contract_name,
+                compiler_version,
+                ..
+            } => {
+                // Strip the commit hash to keep the line short (e.g. `v0.7.6+commit.7338295f`)
+                let compiler_version = compiler_version.split('+').next().unwrap_or_default();
+                pb.set_prefix(format!(
+                    "{} ({}, {}) - ",
+                    display_name, contract_name, compiler_version
+                ));
+                pb.set_message(format!("{}", style("Submitting ").yellow()));
+            }
+            VerificationEvent::Polling { .. } => {
+                pb.set_message(format!("{}", style("Waiting for verification ").yellow()));
+            }
+            _ => {}
+        }
+        if let Some(events) = &events {
+            events.emit(event.clone());
+        }
+    }))
+}
+
+pub fn update_progress_bar(
+    pb: Option<ProgressBar>,
+    result: &Result<VerificationResult>,
+    target_link: Option<&str>,
+) {
+    if let Some(pb) = pb {
+        let link = target_link
+            .map(|link| format!(" {}", link))
+            .unwrap_or_default();
+        match result {
+            Ok(VerificationResult::Success) => {
+                pb.finish_with_message(format!("{}{}", style("Success ✔").green(), link));
+            }
+            Ok(VerificationResult::AlreadyVerified) => {
+                pb.finish_with_message(format!("{}{}", style("Already Verified ✔").green(), link));
+            }
+            Err(err) => {
+                pb.finish_with_message(format!("{}", style(format!("Error: {}", err)).red(),));
+            }
+        }
+    }
+}
+
+/// Display name of the contract, indented below the contract it was added for if it belongs to a
+/// group (e.g. the implementation of a proxy)
+fn grouped_display_name(contract: &Contract) -> String {
+    match &contract.group {
+        Some(group) if !group.eq_ignore_ascii_case(&contract.address) => {
+            format!("  ↳ {}", contract.display_name())
+        }
+        _ => contract.display_name(),
+    }
+}
@@ -0,0 +1,31 @@
+use crate::events::EventHandler;
+use crate::input::Contract;
+use crate::verification::VerificationResult;
+use eyre::Result;
+
+/// Stand-in for the progress bars, which are never created
+#[derive(Clone)]
+pub enum Disabled {}
+
+pub fn initialize_multi_progress(_progress_bar: bool) -> Option<Disabled> {
+    None
+}
+
+pub fn initialize_progress_bar(_mp: Option<Disabled>, _contract: &Contract) -> Option<Disabled> {
+    None
+}
+
+pub fn with_progress_bar_events(
+    _pb: Option<Disabled>,
+    _contract: &Contract,
+    events: Option<EventHandler>,
+) -> Option<EventHandler> {
+    events
+}
+
+pub fn update_progress_bar(
+    _pb: Option<Disabled>,
+    _result: &Result<VerificationResult>,
+    _target_link: Option<&str>,
+) {
+}
@@ -0,0 +1,942 @@
+use crate::http::ExplorerHttpOptions;
+use crate::input::Contract;
+use crate::poll::PollOptions;
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use alloy_primitives::Address;
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::errors::EtherscanError;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::future::Future;
+
+/// Storage slot holding the implementation of EIP-1967 proxies
+/// (`keccak256("eip1967.proxy.implementation") - 1`)
+const IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// Storage slot holding the beacon of EIP-1967 beacon proxies
+/// (`keccak256("eip1967.proxy.beacon") - 1`)
+const BEACON_SLOT: &str = "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+
+/// Topic of `Upgraded(address indexed implementation)` emitted by EIP-1967 proxies and beacons
+const UPGRADED_TOPIC: &str = "0xbc7cd75a20ee27fd9adebab32041f755214dbc6bffa90cc0225b39da2e5c2d3b";
+
+/// Maximum number of logs Etherscan compatible apis return for a single request
+const MAX_LOGS_PER_REQUEST: usize = 1000;
+
+/// Selector of `implementation()` of beacons
+const IMPLEMENTATION_SELECTOR: &str = "0x5c60da1b";
+
+/// Selector of `facetAddresses()` of the EIP-2535 diamond loupe
+const FACET_ADDRESSES_SELECTOR: &str = "0x52ef6b2c";
+
+/// Runtime bytecode of EIP-1167 minimal proxies before and after the implementation address
+const MINIMAL_PROXY_PREFIX: [u8; 10] = alloy_primitives::hex!("363d3d373d3d3d363d73");
+const MINIMAL_PROXY_SUFFIX: [u8; 15] = alloy_primitives::hex!("5af43d82803e903d91602b57fd5bf3");
+
+/// Implementation of an EIP-1167 minimal proxy (clone) given its runtime bytecode, `None` if the
+/// bytecode is not the canonical minimal proxy bytecode
+pub(crate) fn minimal_proxy_implementation(code: &[u8]) -> Option<Address> {
+    let implementation = code
+        .strip_prefix(&MINIMAL_PROXY_PREFIX)?
+        .strip_suffix(&MINIMAL_PROXY_SUFFIX)?;
+    (implementation.len() == 20).then(|| Address::from_slice(implementation))
+}
+
+/// Where the state of contracts (storage and calls) is read from
+#[derive(Debug, Clone)]
+pub enum StorageReader {
+    /// JSON-RPC endpoint of a node of the source chain
+    Rpc {
+        /// The url of the node's JSON-RPC endpoint
+        url: String,
+        /// Settings of the http clients connecting to the chain's block-explorer, only their
+        /// proxy and certificates are used for the node
+        http: ExplorerHttpOptions,
+    },
+    /// `eth_getStorageAt` and `eth_call` proxy and the logs of the source block-explorer's
+    /// Etherscan compatible api
+    Explorer {
+        /// The api key for the source block-explorer's api
+        api_key: String,
+        /// The url of the source block-explorer's api
+        url: String,
+        /// Settings of the http clients connecting to the source block-explorer
+        http: ExplorerHttpOptions,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<serde_json::Value>,
+    error: Option<serde_json::Value>,
+    /// Set by Etherscan compatible apis, `0` together with a message if the request failed
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Options for reading the contracts related to many contracts at once, see
+/// [`with_proxy_implementations`]
+#[derive(Debug, Clone, Copy)]
+pub struct RelatedContractsOptions {
+    /// Number of contracts whose related contracts are read at the same time
+    pub concurrency: usize,
+    /// Maximum number of requests per second sent to the node or block-explorer, only slowing
+    /// down once it reports its rate limit if `None`
+    pub max_requests_per_second: Option<f64>,
+    /// Policy for retrying requests that failed with a transient error
+    pub retry: RetryPolicy,
+}
+
+impl RelatedContractsOptions {
+    /// Rate limiter shared by all requests reading the related contracts
+    fn limiter(&self) -> Result<RateLimiter> {
+        RateLimiter::optional(self.max_requests_per_second)
+    }
+
+    fn context<'a>(
+        &self,
+        client: &'a reqwest::Client,
+        limiter: &'a RateLimiter,
+    ) -> RequestContext<'a> {
+        RequestContext {
+            client,
+            limiter: Some(limiter),
+            retry: self.retry,
+        }
+    }
+}
+
+impl Default for RelatedContractsOptions {
+    fn default() -> Self {
+        RelatedContractsOptions {
+            concurrency: crate::DEFAULT_CONCURRENCY,
+            max_requests_per_second: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Client, rate limiter and retry policy of the requests to a node or block-explorer, each
+/// request is retried on its own
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RequestContext<'a> {
+    pub(crate) client: &'a reqwest::Client,
+    pub(crate) limiter: Option<&'a RateLimiter>,
+    pub(crate) retry: RetryPolicy,
+}
+
+impl<'a> RequestContext<'a> {
+    /// Context of a single read, retrying transient errors without limiting the rate
+    fn single(client: &'a reqwest::Client) -> Self {
+        RequestContext {
+            client,
+            limiter: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Node of the target chain read while copying, sharing the http client and rate limit between
+/// all contracts of a run
+#[derive(Debug)]
+pub(crate) struct TargetChain {
+    reader: StorageReader,
+    client: reqwest::Client,
+    limiter: RateLimiter,
+}
+
+impl TargetChain {
+    /// Only slows down once the node reports its rate limit if `max_requests_per_second` is
+    /// `None`, the requests are sent with the proxy and certificates of `http`
+    pub(crate) fn new(
+        rpc_url: String,
+        max_requests_per_second: Option<f64>,
+        http: &ExplorerHttpOptions,
+    ) -> Result<Self> {
+        Ok(TargetChain {
+            reader: StorageReader::Rpc {
+                url: rpc_url,
+                http: http.clone(),
+            },
+            client: http.base_client()?,
+            limiter: RateLimiter::optional(max_requests_per_second)?,
+        })
+    }
+
+    /// Runtime bytecode of the contract at the address, empty if there is none
+    pub(crate) async fn code_at(&self, address: &str, retry: RetryPolicy) -> Result<Vec<u8>> {
+        let context = RequestContext {
+            client: &self.client,
+            limiter: Some(&self.limiter),
+            retry,
+        };
+        self.reader.code_at(context, address).await
+    }
+}
+
+impl StorageReader {
+    /// Http client for the requests to the node or block-explorer
+    pub(crate) fn http_client(&self) -> Result<reqwest::Client> {
+        match self {
+            StorageReader::Rpc { http, .. } => http.base_client(),
+            StorageReader::Explorer { http, .. } => http.client(),
+        }
+    }
+
+    /// Send a JSON-RPC request, the explorer's api is sent the `query` (including its module and
+    /// action) instead
+    async fn request(
+        &self,
+        context: RequestContext<'_>,
+        method: &str,
+        params: serde_json::Value,
+        query: &[(&str, &str)],
+    ) -> Result<RpcResponse> {
+        context
+            .retry
+            .retry(context.limiter, || async {
+                let request = match self {
+                    StorageReader::Rpc { url, .. } => context.client.post(url).json(&json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "method": method,
+                        "params": params,
+                    })),
+                    StorageReader::Explorer { api_key, url, .. } => context
+                        .client
+                        .get(url)
+                        .query(query)
+                        .query(&[("apikey", api_key)]),
+                };
+                let response: RpcResponse =
+                    request.send().await?.error_for_status()?.json().await?;
+                explorer_error(&response).map_or(Ok(response), |err| Err(err.into()))
+            })
+            .await
+    }
+
+    async fn storage_at(
+        &self,
+        context: RequestContext<'_>,
+        address: &str,
+        slot: &str,
+    ) -> Result<String> {
+        let response = self
+            .request(
+                context,
+                "eth_getStorageAt",
+                json!([address, slot, "latest"]),
+                &[
+                    ("module", "proxy"),
+                    ("action", "eth_getStorageAt"),
+                    ("address", address),
+                    ("position", slot),
+                    ("tag", "latest"),
+                ],
+            )
+            .await?;
+        match (response.result, response.error) {
+            (Some(serde_json::Value::String(result)), None) => Ok(result),
+            (_, error) => Err(eyre!(
+                "Failed to read storage: {}",
+                error.unwrap_or_default()
+            )),
+        }
+    }
+
+    /// Runtime bytecode of the contract, empty if there is no contract at the address
+    pub(crate) async fn code_at(
+        &self,
+        context: RequestContext<'_>,
+        address: &str,
+    ) -> Result<Vec<u8>> {
+        let response = self
+            .request(
+                context,
+                "eth_getCode",
+                json!([address, "latest"]),
+                &[
+                    ("module", "proxy"),
+                    ("action", "eth_getCode"),
+                    ("address", address),
+                    ("tag", "latest"),
+                ],
+            )
+            .await?;
+        match (response.result, response.error) {
+            (Some(serde_json::Value::String(code)), None) if code.starts_with("0x") => {
+                Ok(hex::decode(&code[2..])?)
+            }
+            (result, error) => Err(eyre!(
+                "Failed to read bytecode: {}",
+                error.or(result).unwrap_or_default()
+            )),
+        }
+    }
+
+    /// Return data of a call to the contract, `None` if the call reverted
+    async fn call(
+        &self,
+        context: RequestContext<'_>,
+        address: &str,
+        data: &str,
+    ) -> Result<Option<String>> {
+        let response = self
+            .request(
+                context,
+                "eth_call",
+                json!([{ "to": address, "data": data }, "latest"]),
+                &[
+                    ("module", "proxy"),
+                    ("action", "eth_call"),
+                    ("to", address),
+                    ("data", data),
+                    ("tag", "latest"),
+                ],
+            )
+            .await?;
+        Ok(response
+            .result
+            .and_then(|result| result.as_str().map(ToString::to_string))
+            .filter(|result| response.error.is_none() && result != "0x"))
+    }
+
+    /// First topics after the event signature of all logs of the event emitted by the contract,
+    /// in the order they were emitted
+    ///
+    /// The logs are read in block ranges, a range is halved whenever the node or api rejects it
+    /// (e.g. because it spans too many blocks) or returns as many logs as it can at once.
+    async fn indexed_event_arguments(
+        &self,
+        context: RequestContext<'_>,
+        address: &str,
+        topic: &str,
+    ) -> Result<Vec<String>> {
+        let latest = self.block_number(context).await?;
+        let mut logs = Vec::new();
+        let (mut from, mut blocks) = (0, latest.saturating_add(1));
+        while from <= latest {
+            let to = latest.min(from.saturating_add(blocks - 1));
+            match self.logs(context, address, topic, from, to).await {
+                Ok(range_logs) if range_logs.len() < MAX_LOGS_PER_REQUEST || from == to => {
+                    logs.extend(range_logs);
+                    from = to + 1;
+                    // Ranges only need to be small around blocks with many logs
+                    blocks = blocks.saturating_mul(2);
+                }
+                Err(err) if from == to => return Err(err),
+                // Too many logs or a rejected range, read in halves instead
+                _ => blocks = (to - from).div_ceil(2),
+            }
+        }
+        Ok(logs
+            .iter()
+            .filter_map(|log| log["topics"][1].as_str().map(ToString::to_string))
+            .collect())
+    }
+
+    /// Logs of the event emitted by the contract between the blocks (inclusive)
+    async fn logs(
+        &self,
+        context: RequestContext<'_>,
+        address: &str,
+        topic: &str,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let (from_block, to_block) = (from.to_string(), to.to_string());
+        let response = self
+            .request(
+                context,
+                "eth_getLogs",
+                json!([{
+                    "address": address,
+                    "topics": [topic],
+                    "fromBlock": format!("{:#x}", from),
+                    "toBlock": format!("{:#x}", to),
+                }]),
+                &[
+                    ("module", "logs"),
+                    ("action", "getLogs"),
+                    ("address", address),
+                    ("topic0", topic),
+                    ("fromBlock", &from_block),
+                    ("toBlock", &to_block),
+                ],
+            )
+            .await?;
+        match (response.result, response.error) {
+            (Some(serde_json::Value::Array(logs)), None) => Ok(logs),
+            (result, error) => Err(eyre!(
+                "Failed to read logs: {}",
+                error.or(result).unwrap_or_default()
+            )),
+        }
+    }
+
+    /// Number of the latest block
+    async fn block_number(&self, context: RequestContext<'_>) -> Result<u64> {
+        let response = self
+            .request(
+                context,
+                "eth_blockNumber",
+                json!([]),
+                &[("module", "proxy"), ("action", "eth_blockNumber")],
+            )
+            .await?;
+        match (response.result, response.error) {
+            (Some(serde_json::Value::String(number)), None) => {
+                u64::from_str_radix(number.trim_start_matches("0x"), 16)
+                    .wrap_err_with(|| format!("Invalid block number {}", number))
+            }
+            (result, error) => Err(eyre!(
+                "Failed to read the block number: {}",
+                error.or(result).unwrap_or_default()
+            )),
+        }
+    }
+}
+
+/// Error reported by an Etherscan compatible api via its status and message (e.g. that the rate
+/// limit was reached), `None` if the request succeeded
+///
+/// Apis report the absence of logs the same way, which is not an error.
+fn explorer_error(response: &RpcResponse) -> Option<EtherscanError> {
+    let message = response.message.clone().unwrap_or_default();
+    match (&response.status, &response.result) {
+        (Some(status), result)
+            if status == "0" && !matches!(result, Some(serde_json::Value::Array(_))) =>
+        {
+            Some(EtherscanError::ErrorResponse {
+                status: status.clone(),
+                message,
+                result: result.as_ref().map(|result| {
+                    result
+                        .as_str()
+                        .map_or_else(|| result.to_string(), ToString::to_string)
+                }),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Read the implementation of an EIP-1967 proxy, `None` if the contract is not a proxy
+///
+/// # Arguments
+/// - `address` - Address of the contract
+/// - `reader` - Where the storage of the contract is read from
+pub async fn read_proxy_implementation(
+    address: &str,
+    reader: &StorageReader,
+) -> Result<Option<String>> {
+    proxy_implementation(
+        address,
+        reader,
+        RequestContext::single(&reader.http_client()?),
+    )
+    .await
+}
+
+async fn proxy_implementation(
+    address: &str,
+    reader: &StorageReader,
+    context: RequestContext<'_>,
+) -> Result<Option<String>> {
+    let word = reader
+        .storage_at(context, address, IMPLEMENTATION_SLOT)
+        .await?;
+    implementation_from_word(&word)
+}
+
+fn implementation_from_word(word: &str) -> Result<Option<String>> {
+    let word = hex::decode(word.trim_start_matches("0x"))
+        .wrap_err_with(|| format!("Invalid storage value {}", word))?;
+    // The address occupies the lower 20 bytes of the slot
+    let address = &word[word.len().saturating_sub(20)..];
+    if address.iter().all(|byte| *byte == 0) {
+        return Ok(None);
+    }
+    Ok(Some(format!("0x{}", hex::encode(address))))
+}
+
+/// Read the beacon of an EIP-1967 beacon proxy, `None` if the contract is not a beacon proxy
+///
+/// # Arguments
+/// - `address` - Address of the contract
+/// - `reader` - Where the storage of the contract is read from
+pub async fn read_proxy_beacon(address: &str, reader: &StorageReader) -> Result<Option<String>> {
+    proxy_beacon(
+        address,
+        reader,
+        RequestContext::single(&reader.http_client()?),
+    )
+    .await
+}
+
+async fn proxy_beacon(
+    address: &str,
+    reader: &StorageReader,
+    context: RequestContext<'_>,
+) -> Result<Option<String>> {
+    let word = reader.storage_at(context, address, BEACON_SLOT).await?;
+    implementation_from_word(&word)
+}
+
+/// Read the current implementation of a beacon via its `implementation()` function
+async fn beacon_implementation(
+    beacon: &str,
+    reader: &StorageReader,
+    context: RequestContext<'_>,
+) -> Result<Option<String>> {
+    match reader
+        .call(context, beacon, IMPLEMENTATION_SELECTOR)
+        .await?
+    {
+        Some(word) => implementation_from_word(&word),
+        None => Ok(None),
+    }
+}
+
+/// Add the implementation of each EIP-1967 proxy among the contracts right after the proxy, so
+/// the verification of the actual logic is migrated along with the proxy
+///
+/// Beacon proxies are followed to their beacon, both the beacon and its current implementation
+/// are added. Contracts already among the contracts are not added again.
+///
+/// # Arguments
+/// - `contracts` - Contracts that may include proxies
+/// - `reader` - Where the storage of the contracts is read from
+/// - `options` - Concurrency, rate limit and retry policy of the requests to `reader`
+pub async fn with_proxy_implementations(
+    contracts: Vec<Contract>,
+    reader: &StorageReader,
+    options: RelatedContractsOptions,
+) -> Result<Vec<Contract>> {
+    let (client, limiter) = (reader.http_client()?, options.limiter()?);
+    let context = options.context(&client, &limiter);
+    add_related_contracts(
+        contracts,
+        options,
+        "proxy implementation",
+        |address| async move {
+            if let Some(implementation) = proxy_implementation(&address, reader, context).await? {
+                return Ok(vec![("implementation", implementation)]);
+            }
+            let Some(beacon) = proxy_beacon(&address, reader, context).await? else {
+                return Ok(Vec::new());
+            };
+            let implementation = beacon_implementation(&beacon, reader, context)
+                .await
+                .wrap_err_with(|| {
+                    format!("Failed to read the implementation of beacon {}", beacon)
+                })?;
+            let mut related = vec![("beacon", beacon)];
+            related.extend(implementation.map(|implementation| ("implementation", implementation)));
+            Ok(related)
+        },
+    )
+    .await
+}
+
+/// Read all implementations an upgradeable proxy (or beacon) was ever upgraded to from its
+/// `Upgraded` events, oldest first
+///
+/// # Arguments
+/// - `address` - Address of the proxy or beacon
+/// - `reader` - Where the logs of the contract are read from
+pub async fn read_past_implementations(
+    address: &str,
+    reader: &StorageReader,
+) -> Result<Vec<String>> {
+    past_implementations(
+        address,
+        reader,
+        RequestContext::single(&reader.http_client()?),
+    )
+    .await
+}
+
+async fn past_implementations(
+    address: &str,
+    reader: &StorageReader,
+    context: RequestContext<'_>,
+) -> Result<Vec<String>> {
+    let topics = reader
+        .indexed_event_arguments(context, address, UPGRADED_TOPIC)
+        .await?;
+    let mut seen = HashSet::new();
+    let mut implementations = Vec::new();
+    for topic in topics {
+        if let Some(implementation) = implementation_from_word(&topic)? {
+            if seen.insert(implementation.clone()) {
+                implementations.push(implementation);
+            }
+        }
+    }
+    Ok(implementations)
+}
+
+/// Add every implementation each upgradeable proxy (or beacon) among the contracts was ever
+/// upgraded to right after it, so the target block-explorer has the full history of its logic
+///
+/// Implementations already among the contracts (e.g. the current one) are not added again.
+///
+/// # Arguments
+/// - `contracts` - Contracts that may include proxies or beacons
+/// - `reader` - Where the logs of the contracts are read from
+/// - `options` - Concurrency, rate limit and retry policy of the requests to `reader`
+pub async fn with_past_proxy_implementations(
+    contracts: Vec<Contract>,
+    reader: &StorageReader,
+    options: RelatedContractsOptions,
+) -> Result<Vec<Contract>> {
+    let (client, limiter) = (reader.http_client()?, options.limiter()?);
+    let context = options.context(&client, &limiter);
+    add_related_contracts(
+        contracts,
+        options,
+        "past implementations",
+        |address| async move {
+            let implementations = past_implementations(&address, reader, context).await?;
+            Ok(implementations
+                .into_iter()
+                .map(|implementation| ("past implementation", implementation))
+                .collect())
+        },
+    )
+    .await
+}
+
+/// Add the contracts related to each contract (e.g. the implementation of a proxy) right after
+/// it, labeled with their relation, skipping contracts that are among the contracts already
+///
+/// `read` returns the related contracts with their relation, `description` describes them in
+/// errors. The contracts are read `options.concurrency` at a time.
+async fn add_related_contracts<F, Fut>(
+    contracts: Vec<Contract>,
+    options: RelatedContractsOptions,
+    description: &str,
+    read: F,
+) -> Result<Vec<Contract>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<(&'static str, String)>>>,
+{
+    let mut related: Vec<_> =
+        futures::stream::iter(contracts.iter().enumerate().map(|(index, contract)| {
+            let related = read(contract.address.clone());
+            async move {
+                let related = related.await.wrap_err_with(|| {
+                    format!("Failed to read the {} of {}", description, contract.address)
+                });
+                (index, related)
+            }
+        }))
+        .buffer_unordered(options.concurrency.max(1))
+        .collect()
+        .await;
+    related.sort_by_key(|(index, _)| *index);
+    let mut addresses: HashSet<String> = contracts
+        .iter()
+        .map(|contract| contract.address.to_lowercase())
+        .collect();
+    let mut expanded = Vec::new();
+    for (contract, (_, related)) in contracts.into_iter().zip(related) {
+        let related = related?;
+        let label = |relation: &str| match &contract.label {
+            Some(label) => format!("{} {}", label, relation),
+            None => format!("{} of {}", capitalize(relation), contract.address),
+        };
+        // Contracts related to a member of a group (e.g. the implementation of a beacon) join it
+        let group = contract
+            .group
+            .clone()
+            .unwrap_or_else(|| contract.address.clone());
+        let related: Vec<Contract> = related
+            .into_iter()
+            .map(|(relation, address)| Contract {
+                label: Some(label(relation)),
+                group: Some(group.clone()),
+                address,
+            })
+            .collect();
+        expanded.push(contract);
+        for related in related {
+            if addresses.insert(related.address.to_lowercase()) {
+                expanded.push(related);
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Read the facets of an EIP-2535 diamond via its `facetAddresses()` loupe function, empty if the
+/// contract is not a diamond
+///
+/// # Arguments
+/// - `address` - Address of the contract
+/// - `reader` - Where the state of the contract is read from
+pub async fn read_diamond_facets(address: &str, reader: &StorageReader) -> Result<Vec<String>> {
+    diamond_facets(
+        address,
+        reader,
+        RequestContext::single(&reader.http_client()?),
+    )
+    .await
+}
+
+async fn diamond_facets(
+    address: &str,
+    reader: &StorageReader,
+    context: RequestContext<'_>,
+) -> Result<Vec<String>> {
+    let Some(data) = reader
+        .call(context, address, FACET_ADDRESSES_SELECTOR)
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+    decode_address_array(&data)
+}
+
+/// Decode the ABI encoded return data of a function returning `address[]`
+fn decode_address_array(data: &str) -> Result<Vec<String>> {
+    let data = hex::decode(data.trim_start_matches("0x"))
+        .wrap_err_with(|| format!("Invalid return data {}", data))?;
+    let word = |index: usize| -> Result<&[u8]> {
+        data.get(index * 32..(index + 1) * 32)
+            .ok_or_else(|| eyre!("Return data is not an address array"))
+    };
+    let as_usize = |word: &[u8]| -> Result<usize> {
+        let (high, low) = word.split_at(24);
+        if high.iter().any(|byte| *byte != 0) {
+            return Err(eyre!("Return data is not an address array"));
+        }
+        Ok(u64::from_be_bytes(low.try_into()?) as usize)
+    };
+    let offset = as_usize(word(0)?)?;
+    if offset % 32 != 0 {
+        return Err(eyre!("Return data is not an address array"));
+    }
+    let start = offset / 32;
+    let length = as_usize(word(start)?)?;
+    (0..length)
+        .map(|index| {
+            Ok(format!(
+                "0x{}",
+                hex::encode(&word(start + 1 + index)?[12..])
+            ))
+        })
+        .collect()
+}
+
+/// Add the facets of each EIP-2535 diamond among the contracts right after the diamond, so the
+/// verification of all of its logic is migrated along with it
+///
+/// Facets already among the contracts are not added again.
+///
+/// # Arguments
+/// - `contracts` - Contracts that may include diamonds
+/// - `reader` - Where the state of the contracts is read from
+/// - `options` - Concurrency, rate limit and retry policy of the requests to `reader`
+pub async fn with_diamond_facets(
+    contracts: Vec<Contract>,
+    reader: &StorageReader,
+    options: RelatedContractsOptions,
+) -> Result<Vec<Contract>> {
+    let (client, limiter) = (reader.http_client()?, options.limiter()?);
+    let context = options.context(&client, &limiter);
+    add_related_contracts(contracts, options, "diamond facets", |address| async move {
+        let facets = diamond_facets(&address, reader, context).await?;
+        Ok(facets.into_iter().map(|facet| ("facet", facet)).collect())
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+struct EtherscanResponse {
+    status: String,
+    #[serde(default)]
+    message: String,
+    result: String,
+}
+
+/// Mark a verified contract as proxy of the implementation on an Etherscan compatible api, so it
+/// shows the "Read as Proxy" tab
+///
+/// The check is polled at the poll interval until the api stops reporting it as pending. The
+/// submission and every status request are retried on their own, the check is submitted once.
+pub(crate) async fn verify_etherscan_proxy(
+    context: RequestContext<'_>,
+    api_url: &reqwest::Url,
+    api_key: &str,
+    address: &str,
+    implementation: &str,
+    poll: PollOptions,
+) -> Result<()> {
+    let guid = context
+        .retry
+        .retry(context.limiter, || async {
+            let response: EtherscanResponse = context
+                .client
+                .post(api_url.clone())
+                .form(&[
+                    ("module", "contract"),
+                    ("action", "verifyproxycontract"),
+                    ("address", address),
+                    ("expectedimplementation", implementation),
+                    ("apikey", api_key),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            if response.status != "1" {
+                return Err(eyre::Report::new(EtherscanError::ErrorResponse {
+                    status: response.status,
+                    message: response.message,
+                    result: Some(response.result),
+                })
+                .wrap_err("Failed to submit proxy verification"));
+            }
+            Ok(response.result)
+        })
+        .await?;
+    for _ in 0..poll.max_attempts {
+        tokio::time::sleep(poll.interval).await;
+        let response: EtherscanResponse = context
+            .retry
+            .retry(context.limiter, || async {
+                Ok(context
+                    .client
+                    .get(api_url.clone())
+                    .query(&[
+                        ("module", "contract"),
+                        ("action", "checkproxyverification"),
+                        ("guid", guid.as_str()),
+                        ("apikey", api_key),
+                    ])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?)
+            })
+            .await?;
+        if response.status == "1" {
+            return Ok(());
+        }
+        if !response.result.to_lowercase().contains("pending") {
+            return Err(eyre!("Proxy verification failed: {}", response.result));
+        }
+    }
+    Err(eyre!("Proxy verification {} is still pending", guid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_related_contracts_limiter() {
+        let options = |max_requests_per_second| RelatedContractsOptions {
+            max_requests_per_second,
+            ..Default::default()
+        };
+        assert!(options(None).limiter().is_ok());
+        assert!(options(Some(2.0)).limiter().is_ok());
+        assert!(options(Some(0.0)).limiter().is_err());
+        assert!(options(Some(f64::NAN)).limiter().is_err());
+    }
+
+    #[test]
+    fn test_minimal_proxy_implementation() {
+        let code = hex::decode(
+            "363d3d373d3d3d363d73e592427a0aece92de3edee1f18e0157c058615645af43d82803e903d91602b57fd5bf3",
+        )
+        .unwrap();
+        assert_eq!(
+            minimal_proxy_implementation(&code),
+            Some(
+                "0xE592427A0AEce92De3Edee1F18E0157C05861564"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert_eq!(minimal_proxy_implementation(&code[..44]), None);
+        assert_eq!(minimal_proxy_implementation(&[0x60, 0x80]), None);
+    }
+
+    #[test]
+    fn test_decode_address_array() {
+        let data = format!(
+            "0x{:0>64}{:0>64}{:0>64}{:0>64}",
+            "20", "2", "e592427a0aece92de3edee1f18e0157c05861564", "01"
+        );
+        assert_eq!(
+            decode_address_array(&data).unwrap(),
+            [
+                "0xe592427a0aece92de3edee1f18e0157c05861564",
+                "0x0000000000000000000000000000000000000001"
+            ]
+        );
+        assert!(decode_address_array(&format!("0x{:0>64}{:0>64}", "20", "2")).is_err());
+    }
+
+    #[test]
+    fn test_explorer_error() {
+        let response = |value| serde_json::from_value::<RpcResponse>(value).unwrap();
+        let rate_limited = response(json!({
+            "status": "0",
+            "message": "NOTOK",
+            "result": "Max rate limit reached",
+        }));
+        let err = eyre::Report::from(explorer_error(&rate_limited).unwrap());
+        assert_eq!(
+            crate::FailureReason::of(&err),
+            crate::FailureReason::RateLimited
+        );
+        let no_logs = response(json!({
+            "status": "0",
+            "message": "No records found",
+            "result": [],
+        }));
+        assert!(explorer_error(&no_logs).is_none());
+        let storage = response(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        assert!(explorer_error(&storage).is_none());
+    }
+
+    #[test]
+    fn test_implementation_from_word() {
+        assert_eq!(
+            implementation_from_word(
+                "0x000000000000000000000000e592427a0aece92de3edee1f18e0157c05861564"
+            )
+            .unwrap()
+            .as_deref(),
+            Some("0xe592427a0aece92de3edee1f18e0157c05861564")
+        );
+        assert_eq!(
+            implementation_from_word(&format!("0x{}", "0".repeat(64))).unwrap(),
+            None
+        );
+        assert_eq!(implementation_from_word("0x").unwrap(), None);
+        assert!(implementation_from_word("0xzz").is_err());
+    }
+}
@@ -0,0 +1,478 @@
+use crate::events::{EventHandler, VerificationEvent};
+use crate::failure::{FailureReason, VerificationFailure};
+use crate::input::Contract;
+use crate::verification::{VerificationDetails, VerificationResult};
+use crate::{copy_tasks, CopyOptions, CopyOutcome, CopyRun};
+use eyre::{eyre, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Time after which a contract claimed by a worker that did not record its outcome (e.g. because
+/// it crashed) is handed out to another worker
+const DEFAULT_LEASE: Duration = Duration::from_secs(60 * 60);
+
+/// State of a contract in the [`JobQueue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobStatus {
+    /// Waiting to be claimed by a worker
+    Pending,
+    /// Claimed by a worker that is copying the verification
+    InProgress,
+    /// The verification was submitted to the target block-explorer, its outcome is not known yet
+    Submitted,
+    /// The contract is verified on the target block-explorer
+    Verified,
+    /// Copying the verification failed
+    Failed,
+}
+
+impl JobStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::InProgress => "in_progress",
+            JobStatus::Submitted => "submitted",
+            JobStatus::Verified => "verified",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "pending" => Ok(JobStatus::Pending),
+            "in_progress" => Ok(JobStatus::InProgress),
+            "submitted" => Ok(JobStatus::Submitted),
+            "verified" => Ok(JobStatus::Verified),
+            "failed" => Ok(JobStatus::Failed),
+            _ => Err(format!("Unknown job status {}", value)),
+        }
+    }
+}
+
+/// Contract claimed from the [`JobQueue`] by a worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimedJob {
+    /// The contract to copy the verification for
+    pub contract: Contract,
+    /// GUID of the verification submitted by a worker whose lease expired before it recorded the
+    /// outcome, which is polled instead of submitting the verification again
+    pub guid: Option<String>,
+}
+
+/// Persistent queue of contracts to copy the verification for, stored in a SQLite database
+///
+/// The state of every contract is updated as soon as it changes, so a long migration survives
+/// crashes of the process. Multiple workers (also in different processes) can share one queue,
+/// every contract is only handed out to a single worker at a time.
+pub struct JobQueue {
+    connection: Mutex<Connection>,
+    lease: Duration,
+}
+
+impl JobQueue {
+    /// Open the queue stored in the given database file, creating it if it does not exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let connection = Connection::open(path)
+            .wrap_err_with(|| format!("Failed to open job queue {}", path.display()))?;
+        Self::from_connection(connection)
+    }
+
+    /// Open a queue that only lives in memory, e.g. for testing
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self> {
+        // Wait for other workers instead of failing while they hold the write lock
+        connection.busy_timeout(Duration::from_secs(30))?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS jobs (
+                address TEXT PRIMARY KEY COLLATE NOCASE,
+                label TEXT,
+                status TEXT NOT NULL,
+                guid TEXT,
+                reason TEXT,
+                error TEXT,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS jobs_status ON jobs (status, updated_at);",
+        )?;
+        Ok(JobQueue {
+            connection: Mutex::new(connection),
+            lease: DEFAULT_LEASE,
+        })
+    }
+
+    /// Set the time after which contracts claimed by a worker that did not record their outcome
+    /// are handed out again (defaults to one hour)
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Add contracts to the queue, contracts that are queued already are left untouched
+    ///
+    /// Returns the number of newly added contracts.
+    pub fn enqueue(&self, contracts: &[Contract]) -> Result<usize> {
+        let mut connection = self.lock();
+        let transaction = connection.transaction()?;
+        let mut added = 0;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT OR IGNORE INTO jobs (address, label, status, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for contract in contracts {
+                added += statement.execute(params![
+                    contract.address,
+                    contract.label,
+                    JobStatus::Pending.as_str(),
+                    now()
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(added)
+    }
+
+    /// Claim up to `limit` contracts for the calling worker
+    ///
+    /// Besides pending contracts this includes contracts claimed by another worker whose lease
+    /// expired without recording an outcome. Contracts whose verification was submitted already
+    /// stay submitted and are returned with the GUID of the submission.
+    pub fn claim(&self, limit: usize) -> Result<Vec<ClaimedJob>> {
+        let mut connection = self.lock();
+        // Take the write lock right away, so no other worker can claim the same contracts
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let now = now();
+        let expired = now - self.lease.as_secs() as i64;
+        let jobs = {
+            let mut statement = transaction.prepare(
+                "SELECT address, label, status, guid FROM jobs
+                 WHERE status = ?1 OR (status IN (?2, ?3) AND updated_at <= ?4)
+                 ORDER BY updated_at, address LIMIT ?5",
+            )?;
+            let rows = statement.query_map(
+                params![
+                    JobStatus::Pending.as_str(),
+                    JobStatus::InProgress.as_str(),
+                    JobStatus::Submitted.as_str(),
+                    expired,
+                    limit as i64
+                ],
+                |row| {
+                    let submitted = row.get::<_, String>(2)? == JobStatus::Submitted.as_str();
+                    Ok(ClaimedJob {
+                        contract: Contract {
+                            address: row.get(0)?,
+                            label: row.get(1)?,
+                            group: None,
+                        },
+                        guid: if submitted { row.get(3)? } else { None },
+                    })
+                },
+            )?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for job in &jobs {
+            let status = match job.guid {
+                Some(_) => JobStatus::Submitted,
+                None => JobStatus::InProgress,
+            };
+            transaction.execute(
+                "UPDATE jobs SET status = ?1, updated_at = ?2 WHERE address = ?3",
+                params![status.as_str(), now, job.contract.address],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(jobs)
+    }
+
+    /// Record that the verification of a contract was submitted to the target block-explorer
+    pub fn mark_submitted(&self, address: &str, guid: &str) -> Result<()> {
+        self.lock().execute(
+            "UPDATE jobs SET status = ?1, guid = ?2, updated_at = ?3 WHERE address = ?4",
+            params![JobStatus::Submitted.as_str(), guid, now(), address],
+        )?;
+        Ok(())
+    }
+
+    /// Record the outcome of copying the verification of a contract
+    ///
+    /// Skipped contracts (e.g. because the run was interrupted) are queued again, the ones whose
+    /// verification was submitted already stay submitted.
+    pub fn record(&self, outcome: &CopyOutcome) -> Result<()> {
+        let (status, reason, error) = match &outcome.result {
+            Ok(VerificationResult::Success | VerificationResult::AlreadyVerified) => {
+                (JobStatus::Verified, None, None)
+            }
+            Err(err) => match FailureReason::of(err) {
+                FailureReason::Skipped if outcome.details.guid.is_some() => {
+                    (JobStatus::Submitted, None, None)
+                }
+                FailureReason::Skipped => (JobStatus::Pending, None, None),
+                reason => (
+                    JobStatus::Failed,
+                    Some(reason.to_string()),
+                    Some(format!("{:#}", err)),
+                ),
+            },
+        };
+        self.lock().execute(
+            "UPDATE jobs SET status = ?1, guid = COALESCE(?2, guid), reason = ?3, error = ?4,
+             updated_at = ?5 WHERE address = ?6",
+            params![
+                status.as_str(),
+                outcome.details.guid,
+                reason,
+                error,
+                now(),
+                outcome.contract.address
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the status of a contract, `None` if it is not queued
+    pub fn status(&self, address: &str) -> Result<Option<JobStatus>> {
+        let status: Option<String> = self
+            .lock()
+            .query_row(
+                "SELECT status FROM jobs WHERE address = ?1",
+                params![address],
+                |row| row.get(0),
+            )
+            .optional()?;
+        status
+            .map(|status| status.parse().map_err(|err| eyre!("{}", err)))
+            .transpose()
+    }
+
+    /// Returns the number of contracts per status
+    pub fn counts(&self) -> Result<Vec<(JobStatus, usize)>> {
+        let connection = self.lock();
+        let mut statement = connection
+            .prepare("SELECT status, COUNT(*) FROM jobs GROUP BY status ORDER BY status")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        rows.map(|row| {
+            let (status, count) = row?;
+            Ok((
+                status.parse().map_err(|err| eyre!("{}", err))?,
+                count as usize,
+            ))
+        })
+        .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.connection.lock().expect("job queue lock poisoned")
+    }
+}
+
+/// Copy the verification of the contracts in the queue until no pending contract is left
+///
+/// Claims `batch_size` contracts at a time and records the state of every contract in the queue
+/// as it changes, so multiple workers can process the same queue concurrently and an aborted run
+/// continues where it left off. The outcome of verifications submitted by a worker that did not
+/// record it is polled with the GUID stored in the queue.
+///
+/// Returns an error if the queue can not be updated.
+///
+/// # Arguments
+/// - `queue` - The queue to take the contracts from
+/// - `batch_size` - Number of contracts claimed at a time
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `options` - Additional options, see [`CopyOptions`]
+pub async fn copy_etherscan_verification_from_queue(
+    queue: Arc<JobQueue>,
+    batch_size: usize,
+    source_api_key: String,
+    source_url: String,
+    target_api_key: String,
+    target_url: String,
+    options: &CopyOptions,
+) -> Result<Vec<CopyOutcome>> {
+    // Events can not fail, so the first error recording a submission is returned after the batch
+    let record_error = Arc::new(Mutex::new(None));
+    let events = {
+        let queue = queue.clone();
+        let record_error = record_error.clone();
+        let events = options.events.clone();
+        EventHandler::new(move |event| {
+            if let VerificationEvent::Submitted { address, guid } = event {
+                if let Err(err) = queue.mark_submitted(address, guid) {
+                    record_error
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .get_or_insert(
+                            err.wrap_err(format!("Failed to record submission of {}", address)),
+                        );
+                }
+            }
+            if let Some(events) = &events {
+                events.emit(event.clone());
+            }
+        })
+    };
+    let options = CopyOptions {
+        events: Some(events),
+        ..options.clone()
+    };
+    // All batches share the rate limits and the polling of the target
+    let run = CopyRun::new(
+        source_api_key,
+        source_url,
+        target_api_key,
+        target_url,
+        &options,
+    )?;
+    let mut outcomes = Vec::new();
+    loop {
+        if options
+            .shutdown
+            .as_ref()
+            .is_some_and(|shutdown| shutdown.is_triggered())
+        {
+            break;
+        }
+        let batch = queue.claim(batch_size.max(1))?;
+        if batch.is_empty() {
+            break;
+        }
+        let (submitted, claimed): (Vec<_>, Vec<_>) =
+            batch.into_iter().partition(|job| job.guid.is_some());
+        let claimed = claimed.into_iter().map(|job| job.contract).collect();
+        let resumed = submitted
+            .into_iter()
+            .filter_map(|job| Some(await_submitted(run.clone(), job.contract, job.guid?)));
+        let (mut batch_outcomes, resumed) = futures::future::join(
+            futures::future::join_all(copy_tasks(claimed, run.clone())),
+            futures::future::join_all(resumed),
+        )
+        .await;
+        batch_outcomes.extend(resumed);
+        if let Some(err) = record_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            return Err(err);
+        }
+        for outcome in &batch_outcomes {
+            queue.record(outcome)?;
+        }
+        outcomes.extend(batch_outcomes);
+    }
+    Ok(outcomes)
+}
+
+/// Wait for the outcome of a verification submitted by another worker, copying it to the
+/// additional targets once it passed
+async fn await_submitted(run: Arc<CopyRun>, contract: Contract, guid: String) -> CopyOutcome {
+    let start = Instant::now();
+    let poll = run
+        .poll_scheduler
+        .await_verification(guid.clone(), run.options.poll);
+    let result = match &run.options.shutdown {
+        Some(shutdown) => tokio::select! {
+            result = poll => result,
+            _ = shutdown.triggered() => Err(VerificationFailure::report(
+                FailureReason::Skipped,
+                "Cancelled because the run was interrupted",
+            )),
+        },
+        None => poll.await,
+    };
+    let mut details = VerificationDetails {
+        guid: Some(guid),
+        duration: start.elapsed(),
+        ..Default::default()
+    };
+    details.timings.poll = details.duration;
+    if result.is_ok() && (!run.additional_targets.is_empty() || run.options.mark_proxies) {
+        // The target verified the contract, so copying it only submits to the additional targets
+        // and marks proxies
+        for copied in futures::future::join_all(copy_tasks(vec![contract.clone()], run)).await {
+            match copied.result {
+                Ok(_) => details.warnings.extend(copied.details.warnings),
+                Err(err) => details.warnings.push(format!(
+                    "Failed to verify on the additional targets: {:#}",
+                    err
+                )),
+            }
+        }
+    }
+    CopyOutcome {
+        contract,
+        result,
+        details,
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::VerificationDetails;
+
+    #[test]
+    fn test_job_queue() {
+        let queue = JobQueue::open_in_memory().unwrap();
+        let contracts = [
+            Contract::from("0x01".to_string()),
+            Contract::from("0x02".to_string()),
+        ];
+        assert_eq!(queue.enqueue(&contracts).unwrap(), 2);
+        assert_eq!(queue.enqueue(&contracts[..1]).unwrap(), 0);
+
+        let claimed = queue.claim(1).unwrap();
+        assert_eq!(claimed[0].contract, contracts[0]);
+        assert_eq!(claimed[0].guid, None);
+        assert_eq!(queue.claim(5).unwrap()[0].contract, contracts[1]);
+        assert!(queue.claim(5).unwrap().is_empty());
+
+        queue.mark_submitted("0x01", "guid").unwrap();
+        assert_eq!(queue.status("0x01").unwrap(), Some(JobStatus::Submitted));
+        queue
+            .record(&CopyOutcome {
+                contract: contracts[0].clone(),
+                result: Ok(VerificationResult::Success),
+                details: VerificationDetails::default(),
+            })
+            .unwrap();
+        assert_eq!(queue.status("0x01").unwrap(), Some(JobStatus::Verified));
+
+        // Contracts whose worker did not record an outcome are handed out again after the lease
+        let queue = queue.with_lease(Duration::ZERO);
+        queue.mark_submitted("0x02", "guid").unwrap();
+        assert_eq!(
+            queue.claim(5).unwrap(),
+            vec![ClaimedJob {
+                contract: contracts[1].clone(),
+                guid: Some("guid".to_string()),
+            }]
+        );
+        assert_eq!(
+            queue.counts().unwrap(),
+            vec![(JobStatus::Submitted, 1), (JobStatus::Verified, 1)]
+        );
+    }
+}
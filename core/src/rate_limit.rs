@@ -0,0 +1,168 @@
+use eyre::{eyre, Result};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Minimum interval between two requests once a block-explorer reported its rate limit
+const MIN_RATE_LIMITED_INTERVAL: Duration = Duration::from_millis(250);
+/// Maximum interval between two requests, no matter how often the rate limit was hit
+const MAX_INTERVAL: Duration = Duration::from_secs(30);
+/// Maximum configurable interval between two requests
+const MAX_BASE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Minimum time all requests to a block-explorer are paused after it reported its rate limit
+const MIN_RATE_LIMITED_PAUSE: Duration = Duration::from_secs(1);
+
+/// Limits the number of requests per second sent to a block-explorer, shared between all
+/// concurrently processed contracts
+///
+/// The interval between requests adapts to the block-explorer: it is doubled whenever the
+/// block-explorer reports that its rate limit was reached and slowly decreases again to the
+/// configured interval with every successful request.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    base_interval: Duration,
+    state: Mutex<RateLimiterState>,
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    interval: Duration,
+    next_slot: Instant,
+}
+
+impl RateLimiter {
+    /// Fails unless `max_requests_per_second` is a positive number allowing at least one request
+    /// per day
+    pub(crate) fn new(max_requests_per_second: f64) -> Result<Self> {
+        if !(max_requests_per_second.is_finite() && max_requests_per_second > 0.0) {
+            return Err(eyre!(
+                "Maximum requests per second must be a positive number"
+            ));
+        }
+        let interval = Duration::try_from_secs_f64(1.0 / max_requests_per_second)
+            .ok()
+            .filter(|interval| *interval <= MAX_BASE_INTERVAL)
+            .ok_or_else(|| {
+                eyre!(
+                    "Maximum requests per second {} is too low, at least one request per day must be allowed",
+                    max_requests_per_second
+                )
+            })?;
+        Ok(Self::with_interval(interval))
+    }
+
+    /// Rate limiter that is unlimited if `max_requests_per_second` is `None`
+    pub(crate) fn optional(max_requests_per_second: Option<f64>) -> Result<Self> {
+        max_requests_per_second.map_or_else(|| Ok(Self::unlimited()), Self::new)
+    }
+
+    /// Rate limiter that only slows down once the block-explorer reports its rate limit
+    pub(crate) fn unlimited() -> Self {
+        Self::with_interval(Duration::ZERO)
+    }
+
+    fn with_interval(interval: Duration) -> Self {
+        RateLimiter {
+            base_interval: interval,
+            state: Mutex::new(RateLimiterState {
+                interval,
+                next_slot: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until the next request may be sent
+    pub(crate) async fn acquire(&self) {
+        let slot = {
+            let mut state = self.lock();
+            let slot = state.next_slot.max(Instant::now());
+            state.next_slot = slot + state.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Slow down after the block-explorer reported that its rate limit was reached
+    pub(crate) fn on_rate_limited(&self) {
+        let mut state = self.lock();
+        // A configured interval above the maximum is never shortened
+        state.interval = (state.interval * 2)
+            .clamp(MIN_RATE_LIMITED_INTERVAL, MAX_INTERVAL)
+            .max(self.base_interval);
+        let resume_at = Instant::now() + state.interval.max(MIN_RATE_LIMITED_PAUSE);
+        state.next_slot = state.next_slot.max(resume_at);
+    }
+
+    /// Pause all requests for the given time, e.g. as requested by a `Retry-After` header
+    pub(crate) fn pause_for(&self, delay: Duration) {
+        let mut state = self.lock();
+        state.next_slot = state.next_slot.max(Instant::now() + delay);
+    }
+
+    /// Speed up again after a successful request
+    pub(crate) fn on_success(&self) {
+        let mut state = self.lock();
+        if state.interval > self.base_interval {
+            state.interval = (state.interval * 9 / 10).max(self.base_interval);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RateLimiterState> {
+        self.state.lock().expect("rate limiter lock poisoned")
+    }
+
+    #[cfg(test)]
+    fn interval(&self) -> Duration {
+        self.lock().interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_spaces_out_requests() {
+        let limiter = RateLimiter::new(100.0).unwrap();
+        let start = Instant::now();
+        futures::future::join_all((0..6).map(|_| limiter.acquire())).await;
+        // The first request is sent immediately, the remaining five 10ms apart
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_adapts_to_rate_limit() {
+        let limiter = RateLimiter::new(5.0).unwrap();
+        limiter.on_rate_limited();
+        assert_eq!(limiter.interval(), Duration::from_millis(400));
+        limiter.on_success();
+        assert_eq!(limiter.interval(), Duration::from_millis(360));
+        for _ in 0..100 {
+            limiter.on_success();
+        }
+        assert_eq!(limiter.interval(), Duration::from_millis(200));
+
+        let limiter = RateLimiter::unlimited();
+        limiter.on_rate_limited();
+        assert_eq!(limiter.interval(), MIN_RATE_LIMITED_INTERVAL);
+    }
+
+    #[test]
+    fn test_rate_limited_keeps_long_interval() {
+        let limiter = RateLimiter::new(0.01).unwrap();
+        limiter.on_rate_limited();
+        assert_eq!(limiter.interval(), Duration::from_secs(100));
+    }
+
+    #[test]
+    fn test_invalid_max_requests_per_second() {
+        assert!(RateLimiter::new(0.0).is_err());
+        assert!(RateLimiter::new(-1.0).is_err());
+        assert!(RateLimiter::new(f64::NAN).is_err());
+        assert!(RateLimiter::new(f64::INFINITY).is_err());
+        assert!(RateLimiter::new(1e-320).is_err());
+        assert!(RateLimiter::new(1e-6).is_err());
+        assert!(RateLimiter::optional(None).is_ok());
+        assert!(RateLimiter::optional(Some(0.5)).is_ok());
+    }
+}
@@ -0,0 +1,275 @@
+use crate::http::ExplorerHttpOptions;
+use crate::verification::{api_url_with_chain_id, is_etherscan_v2_chain, ETHERSCAN_V2_API_URL};
+use eyre::{eyre, Result};
+use serde_json::Value;
+use std::time::Duration;
+
+/// List of the chains with a Blockscout instance, keyed by chain id
+const BLOCKSCOUT_CHAINS_URL: &str = "https://chains.blockscout.com/api/chains";
+/// List of all chains with the block-explorers known for them (`explorers`)
+const CHAINID_NETWORK_CHAINS_URL: &str = "https://chainid.network/chains.json";
+/// Time after which a candidate api is considered not working
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Public block-explorer of a network with a known api url, selected via
+/// `<explorer>:<network>` (e.g. `etherscan:mainnet` or `blockscout:gnosis`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExplorerPreset {
+    /// Name of the block-explorer (e.g. `etherscan`)
+    pub explorer: &'static str,
+    /// Name of the network (e.g. `mainnet`)
+    pub network: &'static str,
+    /// Chain id of the network
+    pub chain_id: u64,
+    /// Url of the block-explorer's Etherscan compatible api for the network
+    pub api_url: &'static str,
+}
+
+const fn preset(
+    explorer: &'static str,
+    network: &'static str,
+    chain_id: u64,
+    api_url: &'static str,
+) -> ExplorerPreset {
+    ExplorerPreset {
+        explorer,
+        network,
+        chain_id,
+        api_url,
+    }
+}
+
+/// The common public block-explorers, Etherscan is served by its multichain (V2) api
+#[rustfmt::skip]
+pub const EXPLORER_PRESETS: &[ExplorerPreset] = &[
+    preset("etherscan", "mainnet", 1, "https://api.etherscan.io/v2/api?chainid=1"),
+    preset("etherscan", "optimism", 10, "https://api.etherscan.io/v2/api?chainid=10"),
+    preset("etherscan", "bsc", 56, "https://api.etherscan.io/v2/api?chainid=56"),
+    preset("etherscan", "polygon", 137, "https://api.etherscan.io/v2/api?chainid=137"),
+    preset("etherscan", "base", 8453, "https://api.etherscan.io/v2/api?chainid=8453"),
+    preset("etherscan", "holesky", 17000, "https://api.etherscan.io/v2/api?chainid=17000"),
+    preset("etherscan", "arbitrum", 42161, "https://api.etherscan.io/v2/api?chainid=42161"),
+    preset("etherscan", "linea", 59144, "https://api.etherscan.io/v2/api?chainid=59144"),
+    preset("etherscan", "scroll", 534352, "https://api.etherscan.io/v2/api?chainid=534352"),
+    preset("etherscan", "sepolia", 11155111, "https://api.etherscan.io/v2/api?chainid=11155111"),
+    preset("blockscout", "mainnet", 1, "https://eth.blockscout.com/api"),
+    preset("blockscout", "optimism", 10, "https://optimism.blockscout.com/api"),
+    preset("blockscout", "gnosis", 100, "https://gnosis.blockscout.com/api"),
+    preset("blockscout", "polygon", 137, "https://polygon.blockscout.com/api"),
+    preset("blockscout", "base", 8453, "https://base.blockscout.com/api"),
+    preset("blockscout", "arbitrum", 42161, "https://arbitrum.blockscout.com/api"),
+    preset("blockscout", "sepolia", 11155111, "https://eth-sepolia.blockscout.com/api"),
+    preset("routescan", "avalanche", 43114, "https://api.routescan.io/v2/network/mainnet/evm/43114/etherscan/api"),
+    preset("routescan", "fuji", 43113, "https://api.routescan.io/v2/network/testnet/evm/43113/etherscan/api"),
+    preset("zksync", "mainnet", 324, "https://block-explorer-api.mainnet.zksync.io/api"),
+];
+
+/// Look up a block-explorer preset given as `<explorer>:<network>`, the network can also be
+/// given as chain id (e.g. `blockscout:100`)
+///
+/// # Examples
+///
+/// ```rust
+/// use contract_verification_migrator_core::resolve_explorer_preset;
+///
+/// let preset = resolve_explorer_preset("blockscout:gnosis").unwrap();
+/// assert_eq!(preset.api_url, "https://gnosis.blockscout.com/api");
+/// ```
+pub fn resolve_explorer_preset(preset: &str) -> Result<&'static ExplorerPreset> {
+    let (explorer, network) = preset.split_once(':').ok_or_else(|| {
+        eyre!(
+            "Invalid explorer preset {} (expected <explorer>:<network>)",
+            preset
+        )
+    })?;
+    let explorer = explorer.trim().to_lowercase();
+    let network = network.trim().to_lowercase();
+    EXPLORER_PRESETS
+        .iter()
+        .find(|preset| {
+            preset.explorer == explorer
+                && (preset.network == network || preset.chain_id.to_string() == network)
+        })
+        .ok_or_else(|| {
+            let known: Vec<String> = EXPLORER_PRESETS
+                .iter()
+                .filter(|preset| preset.explorer == explorer)
+                .map(|preset| preset.network.to_string())
+                .collect();
+            if known.is_empty() {
+                let mut explorers: Vec<&str> = EXPLORER_PRESETS
+                    .iter()
+                    .map(|preset| preset.explorer)
+                    .collect();
+                explorers.dedup();
+                eyre!(
+                    "Unknown explorer {} (expected one of {})",
+                    explorer,
+                    explorers.join(", ")
+                )
+            } else {
+                eyre!(
+                    "Unknown network {} of {} (expected one of {})",
+                    network,
+                    explorer,
+                    known.join(", ")
+                )
+            }
+        })
+}
+
+/// Find the url of a working Etherscan compatible api for the chain
+///
+/// Chains served by Etherscan's multichain api use it, followed by the presets of the other
+/// explorers for the chain, the Blockscout chains list and the explorers listed on
+/// chainid.network. Candidates from the lists are only used if their api responds, the lists and
+/// candidates are requested with the proxy and certificates of `http`.
+pub async fn discover_api_url(chain_id: u64, http: &ExplorerHttpOptions) -> Result<String> {
+    if is_etherscan_v2_chain(chain_id) {
+        return api_url_with_chain_id(ETHERSCAN_V2_API_URL, chain_id);
+    }
+    if let Some(preset) = EXPLORER_PRESETS
+        .iter()
+        .find(|preset| preset.chain_id == chain_id)
+    {
+        return Ok(preset.api_url.to_string());
+    }
+    let client = http.base_client_builder()?.timeout(PROBE_TIMEOUT).build()?;
+    let mut candidates = Vec::new();
+    // An unavailable list only removes its candidates
+    if let Ok(chains) = fetch_json(&client, BLOCKSCOUT_CHAINS_URL).await {
+        candidates.extend(blockscout_candidates(&chains, chain_id));
+    }
+    if let Ok(chains) = fetch_json(&client, CHAINID_NETWORK_CHAINS_URL).await {
+        candidates.extend(chainid_network_candidates(&chains, chain_id));
+    }
+    for candidate in candidates {
+        if is_working_api(&client, &candidate).await {
+            return Ok(candidate);
+        }
+    }
+    Err(eyre!(
+        "Found no working explorer api for chain {}",
+        chain_id
+    ))
+}
+
+async fn fetch_json(client: &reqwest::Client, url: &str) -> Result<Value> {
+    Ok(client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Returns whether the url answers like an Etherscan compatible api, which reports a message
+/// even for requests it rejects
+async fn is_working_api(client: &reqwest::Client, url: &str) -> bool {
+    let response = client
+        .get(url)
+        .query(&[
+            ("module", "contract"),
+            ("action", "getabi"),
+            ("address", "0x0000000000000000000000000000000000000000"),
+        ])
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => response
+            .json::<Value>()
+            .await
+            .is_ok_and(|body| body.get("message").is_some()),
+        _ => false,
+    }
+}
+
+/// Api urls of the explorers of the chain in the Blockscout chains list
+/// (`{"<chain id>": {"explorers": [{"url": ...}]}}`)
+fn blockscout_candidates(chains: &Value, chain_id: u64) -> Vec<String> {
+    explorer_api_urls(&chains[chain_id.to_string()]["explorers"])
+}
+
+/// Api urls of the explorers of the chain on chainid.network
+/// (`[{"chainId": ..., "explorers": [{"url": ...}]}]`), Blockscout instances first
+fn chainid_network_candidates(chains: &Value, chain_id: u64) -> Vec<String> {
+    let Some(chain) = chains.as_array().and_then(|chains| {
+        chains
+            .iter()
+            .find(|chain| chain["chainId"].as_u64() == Some(chain_id))
+    }) else {
+        return Vec::new();
+    };
+    let mut candidates = explorer_api_urls(&chain["explorers"]);
+    candidates.sort_by_key(|url| !url.contains("blockscout"));
+    candidates
+}
+
+fn explorer_api_urls(explorers: &Value) -> Vec<String> {
+    explorers
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|explorer| explorer["url"].as_str())
+        .map(|url| format!("{}/api", url.trim_end_matches('/')))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_explorer_preset() {
+        assert_eq!(
+            resolve_explorer_preset("etherscan:mainnet")
+                .unwrap()
+                .api_url,
+            "https://api.etherscan.io/v2/api?chainid=1"
+        );
+        assert_eq!(
+            resolve_explorer_preset("Blockscout:100").unwrap().network,
+            "gnosis"
+        );
+        assert!(resolve_explorer_preset("etherscan")
+            .unwrap_err()
+            .to_string()
+            .contains("<explorer>:<network>"));
+        assert!(resolve_explorer_preset("etherscan:gnosis")
+            .unwrap_err()
+            .to_string()
+            .contains("mainnet"));
+        assert!(resolve_explorer_preset("unknown:mainnet")
+            .unwrap_err()
+            .to_string()
+            .contains("blockscout"));
+    }
+
+    #[test]
+    fn test_discovery_candidates() {
+        let chains = serde_json::json!({
+            "7777777": { "explorers": [{ "url": "https://explorer.zora.energy/", "hostedBy": "blockscout" }] }
+        });
+        assert_eq!(
+            blockscout_candidates(&chains, 7777777),
+            ["https://explorer.zora.energy/api"]
+        );
+        assert!(blockscout_candidates(&chains, 1).is_empty());
+
+        let chains = serde_json::json!([{
+            "chainId": 7777777,
+            "explorers": [
+                { "name": "Zora Scan", "url": "https://zorascan.xyz" },
+                { "name": "blockscout", "url": "https://explorer.zora.blockscout.com" }
+            ]
+        }]);
+        assert_eq!(
+            chainid_network_candidates(&chains, 7777777),
+            [
+                "https://explorer.zora.blockscout.com/api",
+                "https://zorascan.xyz/api"
+            ]
+        );
+    }
+}
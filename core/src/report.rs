@@ -0,0 +1,782 @@
+use crate::failure::FailureReason;
+use crate::input::Contract;
+use crate::verification::VerificationResult;
+use crate::CopyOutcome;
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Page layout of the html report, `{rows}` is replaced with the table rows
+const HTML_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Contract verification migration report</title>
+<style>
+  body { font-family: sans-serif; margin: 2em; }
+  table { border-collapse: collapse; width: 100%; }
+  th, td { border: 1px solid #ddd; padding: 6px 10px; text-align: left; vertical-align: top; }
+  th { background: #f4f4f4; cursor: pointer; user-select: none; }
+  td.address { font-family: monospace; }
+  tr.Success td.outcome, tr.AlreadyVerified td.outcome { color: #1a7f37; }
+  tr.Failed td.outcome { color: #cf222e; }
+  tr.Skipped td.outcome { color: #9a6700; }
+  td.error { white-space: pre-wrap; font-size: 0.9em; }
+</style>
+</head>
+<body>
+<h1>Contract verification migration report</h1>
+<p>{summary}</p>
+<table id="report">
+<thead>
+<tr><th>Address</th><th>Label</th><th>Contract</th><th>Compiler</th><th>Result</th><th>Reason</th><th>Error</th><th>Duration (ms)</th><th>Fetch (ms)</th><th>Submit (ms)</th><th>Poll (ms)</th><th>Link</th></tr>
+</thead>
+<tbody>
+{rows}</tbody>
+</table>
+<script>
+document.querySelectorAll("#report th").forEach((th, column) => {
+  th.addEventListener("click", () => {
+    const tbody = document.querySelector("#report tbody");
+    const ascending = th.dataset.order !== "asc";
+    document.querySelectorAll("#report th").forEach((other) => delete other.dataset.order);
+    th.dataset.order = ascending ? "asc" : "desc";
+    const rows = Array.from(tbody.rows).sort((a, b) => {
+      const x = a.cells[column].innerText;
+      const y = b.cells[column].innerText;
+      return x.localeCompare(y, undefined, { numeric: true }) * (ascending ? 1 : -1);
+    });
+    rows.forEach((row) => tbody.appendChild(row));
+  });
+});
+</script>
+</body>
+</html>
+"##;
+
+/// File format of a [`MigrationReport`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Json document that can be read back with [`MigrationReport::read_json`]
+    #[default]
+    Json,
+    /// Comma separated values with one row per contract
+    Csv,
+    /// Markdown table suitable for pasting into a pull request or issue
+    Markdown,
+    /// Standalone html page with a sortable table
+    Html,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(format!(
+                "Unknown report format {} (expected json, csv, markdown or html)",
+                s
+            )),
+        }
+    }
+}
+
+/// Outcome of copying the verification of a single contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportOutcome {
+    /// The contract was successfully verified on the target
+    Success,
+    /// The contract had been verified on the target already
+    AlreadyVerified,
+    /// Copying the verification failed
+    Failed,
+    /// The contract was skipped (e.g. because the deadline of the run was reached)
+    Skipped,
+}
+
+impl fmt::Display for ReportOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportOutcome::Success => write!(f, "Success"),
+            ReportOutcome::AlreadyVerified => write!(f, "Already Verified"),
+            ReportOutcome::Failed => write!(f, "Failed"),
+            ReportOutcome::Skipped => write!(f, "Skipped"),
+        }
+    }
+}
+
+/// Report entry of a single contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractReport {
+    /// The contract's address
+    pub address: String,
+    /// Optional human-readable label of the contract
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Address of the contract this contract was added for (e.g. the proxy of an
+    /// implementation), the entries of a group are listed together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Outcome of copying the verification
+    pub outcome: ReportOutcome,
+    /// Machine-readable reason if copying the verification failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<FailureReason>,
+    /// Whether copying the verification failed temporarily and may succeed when retried later
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub transient: bool,
+    /// Error message if copying the verification failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the contract is not verified on the source and was skipped instead of failing
+    /// the run, see [`VerificationDetails::skipped`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skipped: bool,
+    /// Name of the contract as reported by the source block-explorer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_name: Option<String>,
+    /// Compiler version the contract was compiled with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler_version: Option<String>,
+    /// Link to the contract's page on the target block-explorer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_link: Option<String>,
+    /// GUID of the verification on the target block-explorer, can be used to resume polling if
+    /// the run was interrupted after submitting the verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guid: Option<String>,
+    /// Time it took to copy the verification in milliseconds
+    #[serde(default)]
+    pub duration_ms: u64,
+    /// Time spent asking the target whether it verified the contract already in milliseconds
+    #[serde(default)]
+    pub check_ms: u64,
+    /// Time spent fetching the metadata from the source in milliseconds
+    #[serde(default)]
+    pub fetch_ms: u64,
+    /// Time spent submitting the verification request to the target in milliseconds
+    #[serde(default)]
+    pub submit_ms: u64,
+    /// Time spent polling the target for the outcome in milliseconds
+    #[serde(default)]
+    pub poll_ms: u64,
+    /// Problems that did not fail copying the verification, e.g. an additional target rejecting
+    /// it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl ContractReport {
+    /// Build the report entry of a single outcome
+    pub fn new(outcome: &CopyOutcome) -> Self {
+        let CopyOutcome {
+            contract,
+            result,
+            details,
+        } = outcome;
+        let (outcome, reason, error) = match result {
+            Ok(VerificationResult::Success) => (ReportOutcome::Success, None, None),
+            Ok(VerificationResult::AlreadyVerified) => (ReportOutcome::AlreadyVerified, None, None),
+            Err(err) => {
+                let reason = FailureReason::of(err);
+                let outcome = match reason {
+                    FailureReason::Skipped => ReportOutcome::Skipped,
+                    _ => ReportOutcome::Failed,
+                };
+                (outcome, Some(reason), Some(format!("{:#}", err)))
+            }
+        };
+        ContractReport {
+            address: contract.address.clone(),
+            label: contract.label.clone(),
+            group: contract.group.clone(),
+            outcome,
+            reason,
+            transient: reason.is_some_and(FailureReason::is_transient),
+            error,
+            skipped: details.skipped,
+            contract_name: details.contract_name.clone(),
+            compiler_version: details.compiler_version.clone(),
+            target_link: details.target_link.clone(),
+            guid: details.guid.clone(),
+            duration_ms: details.duration.as_millis() as u64,
+            check_ms: details.timings.check.as_millis() as u64,
+            fetch_ms: details.timings.fetch.as_millis() as u64,
+            submit_ms: details.timings.submit.as_millis() as u64,
+            poll_ms: details.timings.poll.as_millis() as u64,
+            warnings: details.warnings.clone(),
+        }
+    }
+
+    /// Append the entry as a single json line to the given state file, creating it if necessary
+    pub fn append_jsonl(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .wrap_err_with(|| format!("Failed to write state {}", path.display()))
+    }
+}
+
+/// Machine-readable report of a migration run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// One entry per processed contract
+    pub contracts: Vec<ContractReport>,
+}
+
+impl MigrationReport {
+    /// Build a report from the outcomes returned by
+    /// [`copy_etherscan_verification_with_options`](crate::copy_etherscan_verification_with_options)
+    pub fn new(outcomes: &[CopyOutcome]) -> Self {
+        MigrationReport {
+            contracts: outcomes.iter().map(ContractReport::new).collect(),
+        }
+    }
+
+    /// Read a state file written with [`ContractReport::append_jsonl`], one entry per line
+    ///
+    /// A truncated last line (e.g. because the process was killed while writing it) is ignored.
+    pub fn read_jsonl(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read state {}", path.display()))?;
+        let lines: Vec<&str> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let mut contracts = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(entry) => contracts.push(entry),
+                Err(_) if index == lines.len() - 1 => {}
+                Err(err) => {
+                    return Err(err).wrap_err_with(|| {
+                        format!(
+                            "Failed to parse line {} of state {}",
+                            index + 1,
+                            path.display()
+                        )
+                    })
+                }
+            }
+        }
+        Ok(MigrationReport { contracts })
+    }
+
+    /// Read a report previously written with [`MigrationReport::write_json`]
+    pub fn read_json(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read report {}", path.display()))?;
+        serde_json::from_str(&content)
+            .wrap_err_with(|| format!("Failed to parse report {}", path.display()))
+    }
+
+    /// Write the report as json to the given file
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .wrap_err_with(|| format!("Failed to write report {}", path.display()))
+    }
+
+    /// Write the report as csv to the given file
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_csv())
+            .wrap_err_with(|| format!("Failed to write report {}", path.display()))
+    }
+
+    /// Write the report as a markdown table to the given file
+    pub fn write_markdown(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_markdown())
+            .wrap_err_with(|| format!("Failed to write report {}", path.display()))
+    }
+
+    /// Write the report as a standalone html page to the given file
+    pub fn write_html(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_html())
+            .wrap_err_with(|| format!("Failed to write report {}", path.display()))
+    }
+
+    /// Write the report in the given format to the given file
+    pub fn write(&self, path: impl AsRef<Path>, format: ReportFormat) -> Result<()> {
+        match format {
+            ReportFormat::Json => self.write_json(path),
+            ReportFormat::Csv => self.write_csv(path),
+            ReportFormat::Markdown => self.write_markdown(path),
+            ReportFormat::Html => self.write_html(path),
+        }
+    }
+
+    /// The entries with the members of each group right after the contract they were added for
+    fn grouped(&self) -> Vec<&ContractReport> {
+        let addresses: HashSet<String> = self
+            .contracts
+            .iter()
+            .map(|entry| entry.address.to_lowercase())
+            .collect();
+        // Members of a group listed in the report, by the address of the contract of the group
+        let mut members: HashMap<String, Vec<&ContractReport>> = HashMap::new();
+        let mut roots = Vec::new();
+        for entry in &self.contracts {
+            match entry.group.as_ref().map(|group| group.to_lowercase()) {
+                Some(group)
+                    if group != entry.address.to_lowercase() && addresses.contains(&group) =>
+                {
+                    members.entry(group).or_default().push(entry);
+                }
+                _ => roots.push(entry),
+            }
+        }
+        let mut entries = Vec::with_capacity(self.contracts.len());
+        for root in roots {
+            entries.push(root);
+            entries.extend(
+                members
+                    .remove(&root.address.to_lowercase())
+                    .unwrap_or_default(),
+            );
+        }
+        entries
+    }
+
+    fn to_html(&self) -> String {
+        let mut rows = String::new();
+        for entry in self.grouped() {
+            let link = entry
+                .target_link
+                .as_ref()
+                .map(|link| {
+                    format!(
+                        "<a href=\"{}\" target=\"_blank\">View</a>",
+                        escape_html(link)
+                    )
+                })
+                .unwrap_or_default();
+            let cells = [
+                ("address", escape_html(&entry.address)),
+                (
+                    "label",
+                    escape_html(&grouped_name(entry, entry.label.as_ref())),
+                ),
+                (
+                    "contract",
+                    escape_html(entry.contract_name.as_deref().unwrap_or_default()),
+                ),
+                (
+                    "compiler",
+                    escape_html(entry.compiler_version.as_deref().unwrap_or_default()),
+                ),
+                ("outcome", entry.outcome.to_string()),
+                (
+                    "reason",
+                    entry
+                        .reason
+                        .map(|reason| reason.to_string())
+                        .unwrap_or_default(),
+                ),
+                (
+                    "error",
+                    escape_html(entry.error.as_deref().unwrap_or_default()),
+                ),
+                ("duration", entry.duration_ms.to_string()),
+                ("fetch", entry.fetch_ms.to_string()),
+                ("submit", entry.submit_ms.to_string()),
+                ("poll", entry.poll_ms.to_string()),
+                ("link", link),
+            ];
+            rows.push_str(&format!("<tr class=\"{:?}\">", entry.outcome));
+            for (class, content) in cells {
+                rows.push_str(&format!("<td class=\"{}\">{}</td>", class, content));
+            }
+            rows.push_str("</tr>\n");
+        }
+        let count = |outcomes: &[ReportOutcome]| {
+            self.contracts
+                .iter()
+                .filter(|entry| outcomes.contains(&entry.outcome))
+                .count()
+        };
+        let summary = format!(
+            "{} contracts, {} verified, {} failed, {} skipped",
+            self.contracts.len(),
+            count(&[ReportOutcome::Success, ReportOutcome::AlreadyVerified]),
+            count(&[ReportOutcome::Failed]),
+            count(&[ReportOutcome::Skipped]),
+        );
+        HTML_TEMPLATE
+            .replace("{summary}", &summary)
+            .replace("{rows}", &rows)
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut markdown =
+            String::from("| Address | Name | Result | Link |\n| --- | --- | --- | --- |\n");
+        for entry in self.grouped() {
+            let name = grouped_name(entry, entry.label.as_ref().or(entry.contract_name.as_ref()));
+            let result = match (&entry.outcome, &entry.error) {
+                (ReportOutcome::Skipped, Some(error)) => format!("⏭️ {}", error),
+                (_, Some(error)) => format!("❌ {}", error),
+                (_, None) => format!("✅ {}", entry.outcome),
+            };
+            let link = entry
+                .target_link
+                .as_ref()
+                .map(|link| format!("[View]({})", link))
+                .unwrap_or_default();
+            markdown.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                entry.address,
+                escape_markdown_cell(&name),
+                escape_markdown_cell(&result),
+                link
+            ));
+        }
+        markdown
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "address,label,outcome,reason,error,contract_name,compiler_version,target_link,duration_ms,fetch_ms,submit_ms,poll_ms,group\n",
+        );
+        for entry in self.grouped() {
+            let fields = [
+                entry.address.clone(),
+                entry.label.clone().unwrap_or_default(),
+                entry.outcome.to_string(),
+                entry
+                    .reason
+                    .map(|reason| reason.to_string())
+                    .unwrap_or_default(),
+                entry.error.clone().unwrap_or_default(),
+                entry.contract_name.clone().unwrap_or_default(),
+                entry.compiler_version.clone().unwrap_or_default(),
+                entry.target_link.clone().unwrap_or_default(),
+                entry.duration_ms.to_string(),
+                entry.fetch_ms.to_string(),
+                entry.submit_ms.to_string(),
+                entry.poll_ms.to_string(),
+                entry.group.clone().unwrap_or_default(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| escape_csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Returns whether the report contains an entry for the given address
+    pub fn contains(&self, address: &str) -> bool {
+        self.contracts
+            .iter()
+            .any(|entry| entry.address.eq_ignore_ascii_case(address))
+    }
+
+    /// Returns the contracts whose verification could not be copied
+    pub fn failed_contracts(&self) -> Vec<Contract> {
+        self.contracts
+            .iter()
+            .filter(|entry| {
+                !matches!(
+                    entry.outcome,
+                    ReportOutcome::Success | ReportOutcome::AlreadyVerified
+                )
+            })
+            .map(|entry| Contract {
+                address: entry.address.clone(),
+                label: entry.label.clone(),
+                group: entry.group.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Name of an entry, marked as member of the group listed above it
+fn grouped_name(entry: &ContractReport, name: Option<&String>) -> String {
+    let name = name.cloned().unwrap_or_default();
+    match &entry.group {
+        Some(group) if !group.eq_ignore_ascii_case(&entry.address) => format!("↳ {}", name),
+        _ => name,
+    }
+}
+
+/// Quote a csv field if it contains a separator, quote or line break
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escape the characters with a special meaning in html
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Escape pipes and line breaks that would otherwise break the markdown table
+fn escape_markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StageTimings, VerificationDetails};
+    use eyre::eyre;
+
+    #[test]
+    fn test_failed_contracts_roundtrip() {
+        let contracts = [
+            Contract::from("0xE592427A0AEce92De3Edee1F18E0157C05861564"),
+            Contract {
+                address: "0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84".to_string(),
+                label: Some("icETH".to_string()),
+                group: None,
+            },
+        ];
+        let outcomes = vec![
+            CopyOutcome {
+                contract: contracts[0].clone(),
+                result: Ok(VerificationResult::Success),
+                details: VerificationDetails {
+                    contract_name: Some("SwapRouter".to_string()),
+                    compiler_version: Some("v0.7.6+commit.7338295f".to_string()),
+                    target_link: Some(
+                        "https://eth.blockscout.com/address/0xE592427A0AEce92De3Edee1F18E0157C05861564"
+                            .to_string(),
+                    ),
+                    similar_to: None,
+                    guid: Some("guid".to_string()),
+                    duration: std::time::Duration::from_millis(1500),
+                    timings: StageTimings {
+                        check: std::time::Duration::ZERO,
+                        fetch: std::time::Duration::from_millis(300),
+                        submit: std::time::Duration::from_millis(200),
+                        poll: std::time::Duration::from_millis(1000),
+                    },
+                    warnings: Vec::new(),
+                    skipped: false,
+                },
+            },
+            CopyOutcome {
+                contract: contracts[1].clone(),
+                result: Err(eyre!("Unable to verify.")),
+                details: VerificationDetails::default(),
+            },
+        ];
+        let report = MigrationReport::new(&outcomes);
+        let json = serde_json::to_string(&report).unwrap();
+        let report: MigrationReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            report.contracts[0].contract_name.as_deref(),
+            Some("SwapRouter")
+        );
+        assert_eq!(report.contracts[0].duration_ms, 1500);
+        assert_eq!(report.contracts[0].poll_ms, 1000);
+        assert_eq!(report.contracts[1].reason, Some(FailureReason::Unknown));
+        assert_eq!(
+            report.contracts[1].error.as_deref(),
+            Some("Unable to verify.")
+        );
+        assert_eq!(report.failed_contracts(), vec![contracts[1].clone()]);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let report = MigrationReport {
+            contracts: vec![ContractReport {
+                address: "0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84".to_string(),
+                label: Some("icETH".to_string()),
+                group: None,
+                outcome: ReportOutcome::Failed,
+                reason: Some(FailureReason::TargetRejected),
+                transient: false,
+                error: Some("Verification returned non-ok response: \"a\", b".to_string()),
+                skipped: false,
+                contract_name: None,
+                compiler_version: None,
+                target_link: None,
+                guid: None,
+                duration_ms: 42,
+                check_ms: 0,
+                fetch_ms: 40,
+                submit_ms: 2,
+                poll_ms: 0,
+                warnings: Vec::new(),
+            }],
+        };
+        assert_eq!(
+            report.to_csv(),
+            "address,label,outcome,reason,error,contract_name,compiler_version,target_link,duration_ms,fetch_ms,submit_ms,poll_ms,group\n\
+             0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84,icETH,Failed,TargetRejected,\
+             \"Verification returned non-ok response: \"\"a\"\", b\",,,,42,40,2,0,\n"
+        );
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let report = MigrationReport {
+            contracts: vec![ContractReport {
+                address: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+                label: None,
+                group: None,
+                outcome: ReportOutcome::Success,
+                reason: None,
+                transient: false,
+                error: None,
+                skipped: false,
+                contract_name: Some("SwapRouter".to_string()),
+                compiler_version: Some("v0.7.6+commit.7338295f".to_string()),
+                target_link: Some("https://eth.blockscout.com/address/0xE592".to_string()),
+                guid: None,
+                duration_ms: 42,
+                check_ms: 0,
+                fetch_ms: 40,
+                submit_ms: 2,
+                poll_ms: 0,
+                warnings: Vec::new(),
+            }],
+        };
+        assert_eq!(
+            report.to_markdown(),
+            "| Address | Name | Result | Link |\n| --- | --- | --- | --- |\n\
+             | `0xE592427A0AEce92De3Edee1F18E0157C05861564` | SwapRouter | ✅ Success | \
+             [View](https://eth.blockscout.com/address/0xE592) |\n"
+        );
+    }
+
+    #[test]
+    fn test_grouped() {
+        let entry = |address: &str, group: Option<&str>| ContractReport {
+            address: address.to_string(),
+            label: Some(address.to_string()),
+            group: group.map(ToString::to_string),
+            outcome: ReportOutcome::Success,
+            reason: None,
+            transient: false,
+            error: None,
+            skipped: false,
+            contract_name: None,
+            compiler_version: None,
+            target_link: None,
+            guid: None,
+            duration_ms: 0,
+            check_ms: 0,
+            fetch_ms: 0,
+            submit_ms: 0,
+            poll_ms: 0,
+            warnings: Vec::new(),
+        };
+        let report = MigrationReport {
+            contracts: vec![
+                entry("0x03", Some("0x01")),
+                entry("0x02", None),
+                entry("0x01", None),
+            ],
+        };
+        let addresses: Vec<&str> = report
+            .grouped()
+            .iter()
+            .map(|entry| entry.address.as_str())
+            .collect();
+        assert_eq!(addresses, ["0x02", "0x01", "0x03"]);
+        assert!(report.to_markdown().contains("| `0x03` | ↳ 0x03 |"));
+    }
+
+    #[test]
+    fn test_state_jsonl() {
+        // Unique per process, so concurrent test runs do not share the file
+        let path = std::env::temp_dir().join(format!(
+            "contract-verification-migrator-state-test-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let report = MigrationReport::new(&[
+            CopyOutcome {
+                contract: Contract::from("0xE592427A0AEce92De3Edee1F18E0157C05861564"),
+                result: Ok(VerificationResult::AlreadyVerified),
+                details: VerificationDetails::default(),
+            },
+            CopyOutcome {
+                contract: Contract::from("0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84"),
+                result: Err(eyre!("Unable to verify.")),
+                details: VerificationDetails::default(),
+            },
+        ]);
+        for entry in &report.contracts {
+            entry.append_jsonl(&path).unwrap();
+        }
+        // Simulate a process killed while writing the next entry
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"address\":\"0x")
+            .unwrap();
+        assert_eq!(MigrationReport::read_jsonl(&path).unwrap(), report);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_to_html() {
+        let report = MigrationReport {
+            contracts: vec![ContractReport {
+                address: "0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84".to_string(),
+                label: Some("<icETH>".to_string()),
+                group: None,
+                outcome: ReportOutcome::Failed,
+                reason: Some(FailureReason::TargetRejected),
+                transient: false,
+                error: Some("Unable to verify.".to_string()),
+                skipped: false,
+                contract_name: None,
+                compiler_version: None,
+                target_link: None,
+                guid: None,
+                duration_ms: 42,
+                check_ms: 0,
+                fetch_ms: 40,
+                submit_ms: 2,
+                poll_ms: 0,
+                warnings: Vec::new(),
+            }],
+        };
+        let html = report.to_html();
+        assert!(html.contains("<p>1 contracts, 0 verified, 1 failed, 0 skipped</p>"));
+        assert!(html.contains(
+            "<tr class=\"Failed\"><td class=\"address\">0x7C07F7aBe10CE8e33DC6C5aD68FE033085256A84</td>\
+             <td class=\"label\">&lt;icETH&gt;</td>"
+        ));
+        assert!(html.contains("<td class=\"reason\">TargetRejected</td>"));
+    }
+
+    #[test]
+    fn test_report_format_from_str() {
+        assert_eq!("json".parse(), Ok(ReportFormat::Json));
+        assert_eq!("CSV".parse(), Ok(ReportFormat::Csv));
+        assert_eq!("markdown".parse(), Ok(ReportFormat::Markdown));
+        assert_eq!("html".parse(), Ok(ReportFormat::Html));
+        assert!("pdf".parse::<ReportFormat>().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use crate::failure::FailureReason;
+use crate::rate_limit::RateLimiter;
+use eyre::Result;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Upper bound for the delay requested by a `Retry-After` header, so a misbehaving explorer can
+/// not stall the migration indefinitely
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Error returned when a block-explorer responded with `429 Too Many Requests` and a
+/// `Retry-After` header
+#[derive(Debug)]
+pub(crate) struct RetryAfter(pub(crate) Duration);
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rate limit reached, retry after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for RetryAfter {}
+
+impl RetryAfter {
+    /// Build the error from a `429 Too Many Requests` response, returning `None` for other
+    /// responses or if the response has no `Retry-After` header in seconds
+    ///
+    /// Only responses of requests sent by this crate itself can be inspected, the client of
+    /// `foundry-block-explorers` does not expose the response headers.
+    pub(crate) fn from_response(response: &reqwest::Response) -> Option<Self> {
+        if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+        let header = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)?
+            .to_str()
+            .ok()?;
+        parse_retry_after(header).map(RetryAfter)
+    }
+}
+
+/// Parse the delay of a `Retry-After` header given in seconds (HTTP dates are not supported)
+fn parse_retry_after(header: &str) -> Option<Duration> {
+    let seconds: u64 = header.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+/// Policy for retrying requests to the block-explorers that failed with a transient error (see
+/// [`FailureReason::is_transient`]), e.g. network errors, 5xx responses or because the
+/// block-explorer's rate limit was reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of retries after the initial attempt, `0` disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every subsequent retry
+    pub base_delay: Duration,
+    /// Upper bound of the random delay added to every retry so that concurrent requests do not
+    /// retry in lockstep
+    pub jitter: Duration,
+    /// Number of retries after the block-explorer reported that its rate limit was reached,
+    /// counted separately from `max_retries`
+    pub max_rate_limit_retries: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            jitter: Duration::from_millis(500),
+            max_rate_limit_retries: 10,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            max_rate_limit_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Run the given request, waiting for the rate limiter before every attempt and retrying it
+    /// according to this policy while it fails with a transient error
+    ///
+    /// If the block-explorer reports that its rate limit was reached the rate limiter is slowed
+    /// down, which pauses all requests sharing it, before the request is retried. If the
+    /// block-explorer specified how long to wait (`Retry-After`) that delay is honored instead.
+    pub(crate) async fn retry<T, F, Fut>(
+        &self,
+        limiter: Option<&RateLimiter>,
+        mut request: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retries = 0;
+        let mut rate_limit_retries = 0;
+        loop {
+            if let Some(limiter) = limiter {
+                limiter.acquire().await;
+            }
+            let result = request().await;
+            match &result {
+                Ok(_) => {
+                    if let Some(limiter) = limiter {
+                        limiter.on_success();
+                    }
+                }
+                Err(err) if FailureReason::of(err) == FailureReason::RateLimited => {
+                    if rate_limit_retries < self.max_rate_limit_retries {
+                        let retry_after = err
+                            .chain()
+                            .find_map(|cause| cause.downcast_ref::<RetryAfter>())
+                            .map(|retry_after| retry_after.0);
+                        match (limiter, retry_after) {
+                            (Some(limiter), Some(delay)) => limiter.pause_for(delay),
+                            (Some(limiter), None) => limiter.on_rate_limited(),
+                            (None, Some(delay)) => tokio::time::sleep(delay).await,
+                            (None, None) => {
+                                tokio::time::sleep(self.delay(rate_limit_retries)).await
+                            }
+                        }
+                        rate_limit_retries += 1;
+                        continue;
+                    }
+                }
+                Err(err) if retries < self.max_retries && FailureReason::of(err).is_transient() => {
+                    tokio::time::sleep(self.delay(retries)).await;
+                    retries += 1;
+                    continue;
+                }
+                Err(_) => {}
+            }
+            return result;
+        }
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(retry));
+        let jitter_millis = self.jitter.as_millis() as u64;
+        let jitter = if jitter_millis == 0 {
+            Duration::ZERO
+        } else {
+            // RandomState is seeded randomly, which is good enough for spreading out retries
+            let random = RandomState::new().build_hasher().finish();
+            Duration::from_millis(random % (jitter_millis + 1))
+        };
+        backoff + jitter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_block_explorers::errors::EtherscanError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            jitter: Duration::ZERO,
+            max_rate_limit_retries: 0,
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+
+        let policy = RetryPolicy {
+            jitter: Duration::from_millis(50),
+            ..policy
+        };
+        let delay = policy.delay(1);
+        assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("5"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after("86400"), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_only_transient_errors() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::ZERO,
+            jitter: Duration::ZERO,
+            max_rate_limit_retries: 1,
+        };
+
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .retry(None, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(EtherscanError::BadStatusCode("502 Bad Gateway".to_string()).into())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .retry(None, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(eyre::eyre!("Unable to verify."))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let limiter = RateLimiter::unlimited();
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .retry(Some(&limiter), || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(EtherscanError::RateLimitExceeded.into())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,121 @@
+use eyre::{eyre, Result, WrapErr};
+use std::path::Path;
+
+/// Service the api keys are stored under in the OS keyring
+pub const KEYRING_SERVICE: &str = "contract-verification-migrator";
+
+/// Read an api key from a file, ignoring surrounding whitespace (e.g. the trailing newline)
+pub fn read_api_key_file(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read api key file {}", path.display()))?;
+    match content.trim() {
+        "" => Err(eyre!("Api key file {} is empty", path.display())),
+        api_key => Ok(api_key.to_string()),
+    }
+}
+
+/// Look up the api key of the block-explorer at `api_url` in the OS keyring, stored under the
+/// [`KEYRING_SERVICE`] with the host of the url as account
+///
+/// Requires the `keyring` feature, which reads the Keychain on macOS, the Secret Service on Linux
+/// and the Credential Manager on Windows, e.g. on Linux a key is stored with `secret-tool store
+/// --label="Etherscan" service contract-verification-migrator account api.etherscan.io`.
+pub async fn keyring_api_key(api_url: &str) -> Result<String> {
+    let account = keyring_account(api_url)?;
+    match keyring_password(account.clone()).await? {
+        Some(api_key) if !api_key.trim().is_empty() => Ok(api_key.trim().to_string()),
+        _ => Err(eyre!(
+            "No api key in the OS keyring for service {} and account {}",
+            KEYRING_SERVICE,
+            account
+        )),
+    }
+}
+
+/// Password stored in the OS keyring for the account, `None` if there is none
+#[cfg(feature = "keyring")]
+async fn keyring_password(account: String) -> Result<Option<String>> {
+    // The platform apis block, e.g. while the keyring asks to be unlocked
+    tokio::task::spawn_blocking(move || {
+        match keyring::Entry::new(KEYRING_SERVICE, &account)?.get_password() {
+            Ok(password) => Ok(Some(password)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(eyre::Report::new(err).wrap_err("Failed to query the OS keyring")),
+        }
+    })
+    .await?
+}
+
+/// Without the `keyring` feature the OS keyring can not be read
+#[cfg(not(feature = "keyring"))]
+async fn keyring_password(_account: String) -> Result<Option<String>> {
+    Err(eyre!("Reading the OS keyring requires the keyring feature"))
+}
+
+/// Api keys of a pool given as comma separated list, at least one (possibly empty) key
+pub(crate) fn api_keys(api_key: &str) -> Vec<String> {
+    let api_keys: Vec<String> = api_key
+        .split(',')
+        .map(str::trim)
+        .filter(|api_key| !api_key.is_empty())
+        .map(ToString::to_string)
+        .collect();
+    if api_keys.is_empty() {
+        return vec![String::new()];
+    }
+    api_keys
+}
+
+/// Account the api key of the block-explorer is stored under in the OS keyring
+fn keyring_account(api_url: &str) -> Result<String> {
+    reqwest::Url::parse(api_url)
+        .ok()
+        .and_then(|url| url.host_str().map(ToString::to_string))
+        .ok_or_else(|| eyre!("Invalid api url {}", api_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_api_key_file() {
+        let path = std::env::temp_dir().join(format!("api-key-{}", std::process::id()));
+        std::fs::write(&path, "ABC123\n").unwrap();
+        assert_eq!(read_api_key_file(&path).unwrap(), "ABC123");
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_api_key_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_empty_api_key_file() {
+        let path = std::env::temp_dir().join(format!("empty-api-key-{}", std::process::id()));
+        std::fs::write(&path, " \n").unwrap();
+        assert!(read_api_key_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_keyring_account() {
+        assert_eq!(
+            keyring_account("https://api.etherscan.io/v2/api?chainid=1").unwrap(),
+            "api.etherscan.io"
+        );
+        assert!(keyring_account("not a url").is_err());
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    #[tokio::test]
+    async fn test_keyring_requires_feature() {
+        assert!(keyring_api_key("https://api.etherscan.io/api")
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_api_keys() {
+        assert_eq!(api_keys("A, B,"), vec!["A", "B"]);
+        assert_eq!(api_keys(""), vec![""]);
+    }
+}
@@ -0,0 +1,400 @@
+use crate::verification::contract_source_path;
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::contract::SourceCodeEntry;
+use foundry_block_explorers::verify::VerifyContract;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// External library the contract was linked against, in the format of forge's `--libraries`
+/// (`[<path>:]<name>:<address>`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkedLibrary {
+    /// Path of the source file declaring the library, looked up in the sources if not set
+    pub path: Option<String>,
+    /// Name of the library
+    pub name: String,
+    /// Address the library is deployed at
+    pub address: String,
+}
+
+impl FromStr for LinkedLibrary {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = s.rsplitn(3, ':');
+        let (Some(address), Some(name)) = (parts.next(), parts.next()) else {
+            return Err(format!(
+                "Invalid library {} (expected [<path>:]<name>:<address>)",
+                s
+            ));
+        };
+        Ok(LinkedLibrary {
+            path: parts.next().map(ToString::to_string),
+            name: name.trim().to_string(),
+            address: normalize_library_address(address),
+        })
+    }
+}
+
+/// Path of the source file declaring a contract, in the format `<path>[:<name>]` (e.g.
+/// `contracts/core/Vault.sol:Vault`), applying to contracts of any name if the name is not set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractPath {
+    /// Path of the source file in the standard-json
+    pub path: String,
+    /// Name of the contract the path applies to
+    pub name: Option<String>,
+}
+
+impl FromStr for ContractPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (path, name) = match s.rsplit_once(':') {
+            Some((path, name)) => (path, Some(name.trim().to_string())),
+            None => (s, None),
+        };
+        if path.trim().is_empty() || name.as_ref().is_some_and(String::is_empty) {
+            return Err(format!(
+                "Invalid contract path {} (expected <path>[:<name>])",
+                s
+            ));
+        }
+        Ok(ContractPath {
+            path: path.trim().to_string(),
+            name,
+        })
+    }
+}
+
+/// Parse the `Library` field of the Etherscan metadata (e.g.
+/// `SafeMath:c0a47dfe034b400b47bdad5fecda2621de6c4d95;Strings:0x...`)
+pub(crate) fn parse_library_field(library: &str) -> Vec<LinkedLibrary> {
+    library
+        .split([';', ','])
+        .filter_map(|library| {
+            let (name, address) = library.split_once(':')?;
+            Some(LinkedLibrary {
+                path: None,
+                name: name.trim().to_string(),
+                address: normalize_library_address(address),
+            })
+        })
+        .filter(|library| !library.name.is_empty())
+        .collect()
+}
+
+fn normalize_library_address(address: &str) -> String {
+    let address = address.trim();
+    let address = address.strip_prefix("0x").unwrap_or(address);
+    format!("0x{}", address)
+}
+
+/// Standard-json `libraries` setting (`{"<path>": {"<name>": "<address>"}}`) linking the
+/// libraries declared in the sources
+pub(crate) fn libraries_setting(
+    libraries: &[LinkedLibrary],
+    sources: &HashMap<String, SourceCodeEntry>,
+) -> Value {
+    let mut setting = json!({});
+    for library in libraries {
+        let path = library
+            .path
+            .clone()
+            .unwrap_or_else(|| contract_source_path(sources, &library.name));
+        setting[path][&library.name] = json!(library.address);
+    }
+    setting
+}
+
+/// Link the libraries in the standard-json of a verification request, replacing the addresses
+/// of libraries that are linked already
+///
+/// # Arguments
+/// - `verification_request` - Verification request with `solidity-standard-json-input` source
+/// - `libraries` - Libraries to link, their path is looked up in the sources if not set
+pub fn link_libraries(
+    verification_request: &mut VerifyContract,
+    libraries: &[LinkedLibrary],
+) -> Result<()> {
+    if libraries.is_empty() {
+        return Ok(());
+    }
+    update_settings(verification_request, |settings, sources| {
+        let setting = libraries_setting(libraries, sources);
+        if !settings["libraries"].is_object() {
+            settings["libraries"] = json!({});
+        }
+        for (path, linked) in setting.as_object().into_iter().flatten() {
+            for (name, address) in linked.as_object().into_iter().flatten() {
+                settings["libraries"][path][name] = address.clone();
+            }
+        }
+    })
+}
+
+/// Set the remappings (e.g. `@openzeppelin/=lib/openzeppelin-contracts/`) in the standard-json
+/// of a verification request, unless it has remappings already
+///
+/// # Arguments
+/// - `verification_request` - Verification request with `solidity-standard-json-input` source
+/// - `remappings` - Remappings used if the original settings have none
+pub fn add_remappings(
+    verification_request: &mut VerifyContract,
+    remappings: &[String],
+) -> Result<()> {
+    if remappings.is_empty() {
+        return Ok(());
+    }
+    update_settings(verification_request, |settings, _| {
+        let has_remappings = settings["remappings"]
+            .as_array()
+            .is_some_and(|remappings| !remappings.is_empty());
+        if !has_remappings {
+            settings["remappings"] = json!(remappings);
+        }
+    })
+}
+
+/// Replace the path in the fully qualified contract name (`<path>:<name>`) of a verification
+/// request with the first contract path applying to the contract
+///
+/// # Arguments
+/// - `verification_request` - Verification request with `solidity-standard-json-input` source
+/// - `contract_paths` - Paths overriding the ones derived from the sources
+pub fn set_contract_path(
+    verification_request: &mut VerifyContract,
+    contract_paths: &[ContractPath],
+) -> Result<()> {
+    let name = verification_request
+        .contract_name
+        .rsplit(':')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let Some(contract_path) = contract_paths.iter().find(|contract_path| {
+        contract_path
+            .name
+            .as_ref()
+            .is_none_or(|path_name| *path_name == name)
+    }) else {
+        return Ok(());
+    };
+    let standard_json: Value = serde_json::from_str(&verification_request.source)
+        .wrap_err("Contract paths can only be set for standard-json sources")?;
+    if standard_json["sources"].get(&contract_path.path).is_none() {
+        return Err(eyre!(
+            "Contract path {} is not among the sources of {}",
+            contract_path.path,
+            name
+        ));
+    }
+    verification_request.contract_name = format!("{}:{}", contract_path.path, name);
+    Ok(())
+}
+
+/// Merge the overrides (e.g. `{"viaIR": true, "metadata": {"bytecodeHash": "none"}}`) into the
+/// settings of the standard-json of a verification request, nested objects are merged key by key
+///
+/// An overridden `evmVersion` is also set as evm version of the request itself.
+///
+/// # Arguments
+/// - `verification_request` - Verification request with `solidity-standard-json-input` source
+/// - `overrides` - Settings replacing the corresponding original settings
+pub fn override_settings(
+    verification_request: &mut VerifyContract,
+    overrides: &Value,
+) -> Result<()> {
+    if let Some(evm_version) = overrides["evmVersion"].as_str() {
+        verification_request.evm_version = Some(evm_version.to_string());
+    }
+    update_settings(verification_request, |settings, _| {
+        merge(settings, overrides)
+    })
+}
+
+fn merge(target: &mut Value, overrides: &Value) {
+    match (target, overrides) {
+        (Value::Object(target), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, overrides) => *target = overrides.clone(),
+    }
+}
+
+/// Update the settings of the standard-json of a verification request, which are passed to the
+/// update together with the sources
+fn update_settings(
+    verification_request: &mut VerifyContract,
+    update: impl FnOnce(&mut Value, &HashMap<String, SourceCodeEntry>),
+) -> Result<()> {
+    let mut standard_json: Value = serde_json::from_str(&verification_request.source)
+        .wrap_err("Compiler settings can only be changed in standard-json sources")?;
+    let sources: HashMap<String, SourceCodeEntry> =
+        serde_json::from_value(standard_json["sources"].clone())
+            .wrap_err("Failed to parse the sources of the standard-json")?;
+    let settings = standard_json
+        .as_object_mut()
+        .ok_or_else(|| eyre!("Standard-json is not an object"))?
+        .entry("settings")
+        .or_insert_with(|| json!({}));
+    update(settings, &sources);
+    verification_request.source = serde_json::to_string(&standard_json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Address;
+    use foundry_block_explorers::verify::CodeFormat;
+
+    #[test]
+    fn test_parse_libraries() {
+        assert_eq!(
+            parse_library_field("SafeMath:c0a47dfe034b400b47bdad5fecda2621de6c4d95;Strings:0x01"),
+            vec![
+                LinkedLibrary {
+                    path: None,
+                    name: "SafeMath".to_string(),
+                    address: "0xc0a47dfe034b400b47bdad5fecda2621de6c4d95".to_string(),
+                },
+                LinkedLibrary {
+                    path: None,
+                    name: "Strings".to_string(),
+                    address: "0x01".to_string(),
+                },
+            ]
+        );
+        assert!(parse_library_field("").is_empty());
+        assert_eq!(
+            "src/Math.sol:Math:0x02".parse::<LinkedLibrary>().unwrap(),
+            LinkedLibrary {
+                path: Some("src/Math.sol".to_string()),
+                name: "Math".to_string(),
+                address: "0x02".to_string(),
+            }
+        );
+        assert!("Math".parse::<LinkedLibrary>().is_err());
+    }
+
+    fn standard_json_request(settings: Value) -> VerifyContract {
+        VerifyContract {
+            address: Address::ZERO,
+            source: json!({
+                "language": "Solidity",
+                "sources": {
+                    "src/Counter.sol": { "content": "contract Counter {}" },
+                    "src/Math.sol": { "content": "library Math {}" }
+                },
+                "settings": settings
+            })
+            .to_string(),
+            code_format: CodeFormat::StandardJsonInput,
+            contract_name: "src/Counter.sol:Counter".to_string(),
+            compiler_version: "v0.8.19+commit.7dd6d404".to_string(),
+            optimization_used: None,
+            runs: None,
+            constructor_arguments: None,
+            blockscout_constructor_arguments: None,
+            evm_version: None,
+            other: HashMap::new(),
+        }
+    }
+
+    fn settings(verification_request: &VerifyContract) -> Value {
+        let standard_json: Value = serde_json::from_str(&verification_request.source).unwrap();
+        standard_json["settings"].clone()
+    }
+
+    #[test]
+    fn test_link_libraries() {
+        let mut verification_request =
+            standard_json_request(json!({ "libraries": { "src/Old.sol": { "Old": "0x03" } } }));
+        link_libraries(&mut verification_request, &["Math:0x02".parse().unwrap()]).unwrap();
+        assert_eq!(
+            settings(&verification_request)["libraries"],
+            json!({
+                "src/Old.sol": { "Old": "0x03" },
+                "src/Math.sol": { "Math": "0x02" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_remappings() {
+        let remappings = ["@oz/=lib/oz/".to_string()];
+        let mut verification_request = standard_json_request(json!({ "remappings": [] }));
+        add_remappings(&mut verification_request, &remappings).unwrap();
+        assert_eq!(
+            settings(&verification_request)["remappings"],
+            json!(["@oz/=lib/oz/"])
+        );
+
+        let mut verification_request =
+            standard_json_request(json!({ "remappings": ["ds-test/=lib/ds-test/src/"] }));
+        add_remappings(&mut verification_request, &remappings).unwrap();
+        assert_eq!(
+            settings(&verification_request)["remappings"],
+            json!(["ds-test/=lib/ds-test/src/"])
+        );
+    }
+
+    #[test]
+    fn test_set_contract_path() {
+        let mut verification_request = standard_json_request(json!({}));
+        set_contract_path(
+            &mut verification_request,
+            &["src/Other.sol:Other".parse().unwrap()],
+        )
+        .unwrap();
+        assert_eq!(
+            verification_request.contract_name,
+            "src/Counter.sol:Counter"
+        );
+        set_contract_path(
+            &mut verification_request,
+            &["src/Math.sol".parse().unwrap()],
+        )
+        .unwrap();
+        assert_eq!(verification_request.contract_name, "src/Math.sol:Counter");
+        assert!(set_contract_path(
+            &mut verification_request,
+            &["src/Missing.sol:Counter".parse().unwrap()],
+        )
+        .is_err());
+        assert!(":Counter".parse::<ContractPath>().is_err());
+    }
+
+    #[test]
+    fn test_override_settings() {
+        let mut verification_request = standard_json_request(json!({
+            "optimizer": { "enabled": true, "runs": 200 },
+            "remappings": []
+        }));
+        override_settings(
+            &mut verification_request,
+            &json!({
+                "viaIR": true,
+                "optimizer": { "details": { "yul": true } },
+                "metadata": { "bytecodeHash": "none" },
+                "evmVersion": "paris"
+            }),
+        )
+        .unwrap();
+        assert_eq!(verification_request.evm_version.as_deref(), Some("paris"));
+        assert_eq!(
+            settings(&verification_request),
+            json!({
+                "optimizer": { "enabled": true, "runs": 200, "details": { "yul": true } },
+                "remappings": [],
+                "viaIR": true,
+                "metadata": { "bytecodeHash": "none" },
+                "evmVersion": "paris"
+            })
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Signal to stop copying verifications early, e.g. when the process receives `SIGINT`
+///
+/// Once triggered no new requests are sent and all contracts that are still in progress are
+/// cancelled and reported as failed with [`FailureReason::Skipped`](crate::FailureReason::Skipped).
+#[derive(Clone, Default)]
+pub struct ShutdownSignal(Arc<ShutdownState>);
+
+#[derive(Default)]
+struct ShutdownState {
+    triggered: AtomicBool,
+    notify: Notify,
+}
+
+impl ShutdownSignal {
+    /// Create a signal that has not been triggered yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop copying verifications
+    pub fn trigger(&self) {
+        self.0.triggered.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    /// Returns whether the signal has been triggered
+    pub fn is_triggered(&self) -> bool {
+        self.0.triggered.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the signal is triggered
+    pub(crate) async fn triggered(&self) {
+        let notified = self.0.notify.notified();
+        tokio::pin!(notified);
+        // Register for notifications before checking the flag so a concurrent trigger is not missed
+        notified.as_mut().enable();
+        if self.is_triggered() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+impl fmt::Debug for ShutdownSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ShutdownSignal")
+            .field(&self.is_triggered())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_triggered() {
+        let signal = ShutdownSignal::new();
+        let waiting = tokio::spawn({
+            let signal = signal.clone();
+            async move { signal.triggered().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiting.is_finished());
+        signal.trigger();
+        waiting.await.unwrap();
+
+        // Waiting for a signal that was triggered before returns immediately
+        signal.triggered().await;
+    }
+}
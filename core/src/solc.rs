@@ -0,0 +1,116 @@
+use crate::http::ExplorerHttpOptions;
+use crate::vyper;
+use foundry_block_explorers::verify::VerifyContract;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// List of all solc builds published by the Solidity team
+const SOLC_RELEASES_URL: &str = "https://binaries.soliditylang.org/bin/list.json";
+/// Time after which the release list is considered unavailable
+const RELEASES_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Full versions of the solc builds (`0.8.19+commit.7dd6d404`) keyed by their version without the
+/// commit, fetched once per process (retried by the next lookup if fetching failed)
+static RELEASES: OnceCell<HashMap<String, String>> = OnceCell::const_new();
+
+#[derive(Debug, Deserialize)]
+struct ReleaseList {
+    builds: Vec<Build>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Build {
+    long_version: String,
+}
+
+/// The list of releases, `None` if it can not be fetched
+async fn releases(http: &ExplorerHttpOptions) -> Option<&'static HashMap<String, String>> {
+    RELEASES.get_or_try_init(|| fetch_releases(http)).await.ok()
+}
+
+async fn fetch_releases(http: &ExplorerHttpOptions) -> eyre::Result<HashMap<String, String>> {
+    let client = http
+        .base_client_builder()?
+        .timeout(RELEASES_TIMEOUT)
+        .build()?;
+    let list: ReleaseList = client
+        .get(SOLC_RELEASES_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(release_map(list))
+}
+
+fn release_map(list: ReleaseList) -> HashMap<String, String> {
+    list.builds
+        .into_iter()
+        .map(|build| {
+            let version = build
+                .long_version
+                .split('+')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            (version, build.long_version)
+        })
+        .collect()
+}
+
+/// Add the commit of the official release to the compiler version of a verification request
+/// that lacks it (e.g. `v0.8.19` becomes `v0.8.19+commit.7dd6d404`), which Etherscan requires
+///
+/// The release list is fetched with the proxy and certificates of `http`.
+pub(crate) async fn resolve_compiler_version(
+    verification_request: &mut VerifyContract,
+    http: &ExplorerHttpOptions,
+) {
+    let compiler_version = &verification_request.compiler_version;
+    if vyper::is_vyper_request(verification_request) || compiler_version.contains("+commit.") {
+        return;
+    }
+    // Without the list versions are submitted as reported by the source
+    let Some(releases) = releases(http).await else {
+        return;
+    };
+    if let Some(long_version) = lookup(releases, compiler_version) {
+        verification_request.compiler_version = long_version;
+    }
+}
+
+fn lookup(releases: &HashMap<String, String>, compiler_version: &str) -> Option<String> {
+    let version = compiler_version.trim_start_matches('v');
+    releases
+        .get(version)
+        .map(|long_version| format!("v{}", long_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let list: ReleaseList = serde_json::from_value(serde_json::json!({
+            "builds": [
+                { "path": "soljson-v0.8.19+commit.7dd6d404.js", "version": "0.8.19", "longVersion": "0.8.19+commit.7dd6d404" },
+                { "path": "soljson-v0.4.11-nightly.2017.3.15+commit.0c8c2091.js", "version": "0.4.11", "prerelease": "nightly.2017.3.15", "longVersion": "0.4.11-nightly.2017.3.15+commit.0c8c2091" }
+            ]
+        }))
+        .unwrap();
+        let releases = release_map(list);
+        assert_eq!(
+            lookup(&releases, "v0.8.19").as_deref(),
+            Some("v0.8.19+commit.7dd6d404")
+        );
+        assert_eq!(
+            lookup(&releases, "v0.4.11-nightly.2017.3.15").as_deref(),
+            Some("v0.4.11-nightly.2017.3.15+commit.0c8c2091")
+        );
+        assert_eq!(lookup(&releases, "v0.4.11"), None);
+    }
+}
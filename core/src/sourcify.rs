@@ -0,0 +1,206 @@
+use crate::http::ExplorerHttpOptions;
+use crate::import::{standard_json_from_metadata, verification_request_from_standard_json};
+use crate::rate_limit::RateLimiter;
+use crate::retry::{RetryAfter, RetryPolicy};
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::verify::VerifyContract;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Url of the public Sourcify server
+pub const DEFAULT_SOURCIFY_URL: &str = "https://sourcify.dev/server";
+
+/// Name of the file in the Sourcify repository containing the contract's metadata
+const METADATA_FILE_NAME: &str = "metadata.json";
+/// Name of the file in the Sourcify repository containing the hex encoded constructor arguments
+const CONSTRUCTOR_ARGS_FILE_NAME: &str = "constructor-args.txt";
+
+/// Sourcify repository to read the verified sources from instead of a source block-explorer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcifySource {
+    /// Url of the Sourcify server (e.g. `https://sourcify.dev/server`)
+    pub url: String,
+    /// Chain id of the network the contracts are deployed on
+    pub chain_id: u64,
+}
+
+impl SourcifySource {
+    /// The public Sourcify server for the given chain id
+    pub fn new(chain_id: u64) -> Self {
+        SourcifySource {
+            url: DEFAULT_SOURCIFY_URL.to_string(),
+            chain_id,
+        }
+    }
+}
+
+/// Client fetching verified contracts from the Sourcify repository
+pub(crate) struct SourcifyClient {
+    client: reqwest::Client,
+    source: SourcifySource,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourcifyFiles {
+    files: Vec<SourcifyFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourcifyFile {
+    name: String,
+    /// Path of the file in the repository, the sources are stored below
+    /// `<match>/<chain id>/<address>/sources/`
+    path: String,
+    content: String,
+}
+
+impl SourcifyClient {
+    /// Requests are sent with the proxy and certificates of `http_options`
+    pub(crate) fn new(source: SourcifySource, http_options: &ExplorerHttpOptions) -> Result<Self> {
+        Ok(SourcifyClient {
+            client: http_options.base_client()?,
+            source,
+        })
+    }
+
+    /// Fetch the files of a contract (full or partial match) and convert them into a
+    /// verification request, returning `None` if the contract is not verified on Sourcify
+    pub(crate) async fn fetch_verification_request(
+        &self,
+        contract_address: &str,
+        retry: RetryPolicy,
+        limiter: Option<&RateLimiter>,
+    ) -> Result<Option<VerifyContract>> {
+        let url = format!(
+            "{}/files/any/{}/{}",
+            self.source.url.trim_end_matches('/'),
+            self.source.chain_id,
+            contract_address
+        );
+        let files: Option<SourcifyFiles> = retry
+            .retry(limiter, || async {
+                let response = self.client.get(&url).send().await?;
+                if let Some(retry_after) = RetryAfter::from_response(&response) {
+                    return Err(retry_after.into());
+                }
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                Ok(Some(response.error_for_status()?.json().await?))
+            })
+            .await
+            .wrap_err("Failed to fetch files from Sourcify")?;
+        files
+            .map(|files| verification_request_from_files(contract_address, &files.files))
+            .transpose()
+    }
+}
+
+/// Build a verification request from the files of a contract in the Sourcify repository
+fn verification_request_from_files(
+    contract_address: &str,
+    files: &[SourcifyFile],
+) -> Result<VerifyContract> {
+    let metadata = files
+        .iter()
+        .find(|file| file.name == METADATA_FILE_NAME)
+        .ok_or_else(|| eyre!("Sourcify did not return the metadata of the contract"))?;
+    let metadata: Value =
+        serde_json::from_str(&metadata.content).wrap_err("Failed to parse Sourcify metadata")?;
+    let (standard_json, contract_name) = standard_json_from_metadata(&metadata, |source_path| {
+        files
+            .iter()
+            .find(|file| {
+                file.path
+                    .split_once("/sources/")
+                    .is_some_and(|(_, path)| path == source_path)
+            })
+            .map(|file| file.content.clone())
+            .ok_or_else(|| eyre!("Sourcify did not return the source {}", source_path))
+    })?;
+    let compiler_version = metadata["compiler"]["version"]
+        .as_str()
+        .ok_or_else(|| eyre!("Sourcify metadata is missing the compiler version"))?
+        .to_string();
+    let constructor_arguments = files
+        .iter()
+        .find(|file| file.name == CONSTRUCTOR_ARGS_FILE_NAME)
+        .map(|file| file.content.trim().to_string());
+    verification_request_from_standard_json(
+        contract_address,
+        serde_json::to_string(&standard_json)?,
+        contract_name,
+        compiler_version,
+        constructor_arguments,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verification_request_from_files() {
+        let metadata = json!({
+            "compiler": { "version": "0.8.19+commit.7dd6d404" },
+            "language": "Solidity",
+            "settings": {
+                "compilationTarget": { "src/Counter.sol": "Counter" },
+                "evmVersion": "paris",
+                "libraries": {},
+                "optimizer": { "enabled": true, "runs": 200 },
+                "remappings": []
+            },
+            "sources": {
+                "src/Counter.sol": { "keccak256": "0x00", "urls": [] }
+            }
+        });
+        let directory =
+            "/data/repository/contracts/full_match/1/0x5FbDB2315678afecb367f032d93F642f64180aa3";
+        let files = [
+            SourcifyFile {
+                name: METADATA_FILE_NAME.to_string(),
+                path: format!("{}/metadata.json", directory),
+                content: metadata.to_string(),
+            },
+            SourcifyFile {
+                name: "Counter.sol".to_string(),
+                path: format!("{}/sources/src/Counter.sol", directory),
+                content: "contract Counter {}".to_string(),
+            },
+            SourcifyFile {
+                name: CONSTRUCTOR_ARGS_FILE_NAME.to_string(),
+                path: format!("{}/constructor-args.txt", directory),
+                content: "0x01\n".to_string(),
+            },
+        ];
+        let verification_request =
+            verification_request_from_files("0x5FbDB2315678afecb367f032d93F642f64180aa3", &files)
+                .unwrap();
+        assert_eq!(
+            verification_request.contract_name,
+            "src/Counter.sol:Counter"
+        );
+        assert_eq!(
+            verification_request.compiler_version,
+            "v0.8.19+commit.7dd6d404"
+        );
+        assert_eq!(
+            verification_request.constructor_arguments.as_deref(),
+            Some("01")
+        );
+        let standard_json: Value = serde_json::from_str(&verification_request.source).unwrap();
+        assert_eq!(
+            standard_json["sources"],
+            json!({ "src/Counter.sol": { "content": "contract Counter {}" } })
+        );
+
+        assert!(verification_request_from_files(
+            "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            &files[..1]
+        )
+        .is_err());
+    }
+}
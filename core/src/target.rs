@@ -0,0 +1,506 @@
+use crate::blockscout::BlockscoutV2Client;
+use crate::explorer::TargetExplorer;
+use crate::http::ExplorerHttpOptions;
+use crate::oklink;
+use crate::poll::PollOptions;
+use crate::proxy::{self, RequestContext};
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::secrets::api_keys;
+use crate::verification::{
+    build_client_with_browser_url, fetch_verified_metadata, send_verification_request,
+    VerificationRequestResponse,
+};
+use crate::vyper;
+use crate::zksync::ZkSyncClient;
+use alloy_primitives::Address;
+use eyre::Result;
+use foundry_block_explorers::verify::VerifyContract;
+use foundry_block_explorers::Client;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Api of the target block-explorer the verifications are submitted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetType {
+    /// Etherscan compatible api (`?module=contract&action=verifysourcecode`), also offered by
+    /// Blockscout and most other block-explorers
+    #[default]
+    Etherscan,
+    /// REST api of Blockscout v2 (`/api/v2/smart-contracts/{address}/verification/...`)
+    BlockscoutV2,
+    /// Etherscan compatible api of Routescan, which reports the status of verifications with
+    /// slightly different messages (see [`routescan_api_url`](crate::routescan_api_url))
+    Routescan,
+    /// Verification api of the zkSync Era block explorer (`/contract_verification`), which
+    /// requires the zksolc version reported by a zkSync source explorer
+    ZkSync,
+    /// Etherscan compatible api of OKLink, which expects the api key in a header and reports the
+    /// status of verifications with different messages
+    OkLink,
+}
+
+/// Website of Routescan, used for the links to verified contracts
+const ROUTESCAN_BROWSER_URL: &str = "https://routescan.io/";
+
+/// Url of the Etherscan compatible api of Routescan for the given chain
+///
+/// # Examples
+///
+/// ```rust
+/// use contract_verification_migrator_core::routescan_api_url;
+///
+/// assert_eq!(
+///     routescan_api_url(43114, false),
+///     "https://api.routescan.io/v2/network/mainnet/evm/43114/etherscan/api"
+/// );
+/// ```
+pub fn routescan_api_url(chain_id: u64, testnet: bool) -> String {
+    let network = if testnet { "testnet" } else { "mainnet" };
+    format!(
+        "https://api.routescan.io/v2/network/{}/evm/{}/etherscan/api",
+        network, chain_id
+    )
+}
+
+impl TargetType {
+    /// Detect the api from its url: urls ending in `/api/v2` belong to the REST api of
+    /// Blockscout v2, urls ending in `/contract_verification` to zkSync, urls on `routescan.io`
+    /// to Routescan, urls on `oklink.com` to OKLink and all others are expected to be Etherscan
+    /// compatible
+    pub fn detect(url: &str) -> Self {
+        let Ok(url) = reqwest::Url::parse(url) else {
+            return TargetType::Etherscan;
+        };
+        let path = url.path().trim_end_matches('/');
+        if path.ends_with("/api/v2") {
+            TargetType::BlockscoutV2
+        } else if path.ends_with("/contract_verification") {
+            TargetType::ZkSync
+        } else if url
+            .host_str()
+            .is_some_and(|host| host == "routescan.io" || host.ends_with(".routescan.io"))
+        {
+            TargetType::Routescan
+        } else if oklink::is_oklink_url(url.as_str()) {
+            TargetType::OkLink
+        } else {
+            TargetType::Etherscan
+        }
+    }
+}
+
+impl FromStr for TargetType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "etherscan" => Ok(TargetType::Etherscan),
+            "blockscout-v2" => Ok(TargetType::BlockscoutV2),
+            "routescan" => Ok(TargetType::Routescan),
+            "zksync" => Ok(TargetType::ZkSync),
+            "oklink" => Ok(TargetType::OkLink),
+            _ => Err(format!(
+                "Unknown target type {} (expected etherscan, blockscout-v2, routescan, zksync or oklink)",
+                s
+            )),
+        }
+    }
+}
+
+/// Block-explorer or service the verifications are submitted to in addition to the target
+/// block-explorer, e.g. Tenderly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdditionalTarget {
+    /// The api key for the api
+    pub api_key: String,
+    /// The url of the api
+    pub url: String,
+    /// Api of the target, detected from `url` if not set
+    pub target_type: Option<TargetType>,
+}
+
+impl AdditionalTarget {
+    /// Tenderly project receiving the verifications via its Etherscan compatible api
+    ///
+    /// # Arguments
+    /// - `access_key` - Access key of the Tenderly account
+    /// - `account` - Slug of the Tenderly account
+    /// - `project` - Slug of the Tenderly project
+    /// - `chain_id` - Chain id of the network the contracts are deployed on
+    pub fn tenderly(access_key: String, account: &str, project: &str, chain_id: u64) -> Self {
+        AdditionalTarget {
+            api_key: access_key,
+            url: format!(
+                "https://api.tenderly.co/api/v1/account/{}/project/{}/etherscan/verify/network/{}",
+                account, project, chain_id
+            ),
+            target_type: Some(TargetType::Etherscan),
+        }
+    }
+}
+
+/// Clients of the additional targets with their urls
+pub(crate) fn additional_target_clients(
+    additional_targets: &[AdditionalTarget],
+) -> Result<Vec<(String, TargetClient)>> {
+    additional_targets
+        .iter()
+        .map(|target| {
+            let client = TargetClient::new(
+                target.target_type,
+                target.api_key.clone(),
+                target.url.clone(),
+                None,
+                &ExplorerHttpOptions::default(),
+            )?;
+            Ok((target.url.clone(), client))
+        })
+        .collect()
+}
+
+/// Client for an Etherscan compatible api, which also submits the Vyper verifications the
+/// `foundry_block_explorers` client can not express
+#[derive(Debug, Clone)]
+pub(crate) struct EtherscanTarget {
+    client: Client,
+    /// Http client sending the requests `client` does not support
+    http: reqwest::Client,
+    api_key: String,
+}
+
+impl EtherscanTarget {
+    fn new(
+        api_key: String,
+        url: String,
+        browser_url: Option<String>,
+        http_options: &ExplorerHttpOptions,
+    ) -> Result<Self> {
+        let http = if oklink::is_oklink_url(&url) {
+            oklink::http_client(&api_key, http_options)?
+        } else {
+            http_options.client()?
+        };
+        let client =
+            build_client_with_browser_url(api_key.clone(), url, browser_url, http_options)?;
+        Ok(EtherscanTarget {
+            client,
+            http,
+            api_key,
+        })
+    }
+
+    async fn submit(
+        &self,
+        verification_request: &VerifyContract,
+    ) -> Result<VerificationRequestResponse> {
+        if vyper::is_vyper_request(verification_request) {
+            return vyper::send_verification_request(
+                verification_request,
+                &self.http,
+                self.client.etherscan_api_url(),
+                &self.api_key,
+            )
+            .await;
+        }
+        send_verification_request(verification_request, &self.client).await
+    }
+
+    async fn mark_proxy(
+        &self,
+        address: &str,
+        implementation: &str,
+        poll: PollOptions,
+        retry: RetryPolicy,
+        limiter: Option<&RateLimiter>,
+    ) -> Result<()> {
+        let context = RequestContext {
+            client: &self.http,
+            limiter,
+            retry,
+        };
+        proxy::verify_etherscan_proxy(
+            context,
+            self.client.etherscan_api_url(),
+            &self.api_key,
+            address,
+            implementation,
+            poll,
+        )
+        .await
+    }
+
+    async fn is_verified(&self, address: &str) -> Result<bool> {
+        Ok(fetch_verified_metadata(&self.client, address)
+            .await?
+            .is_some())
+    }
+
+    async fn verification_status(&self, id: &str) -> Result<String> {
+        Ok(self
+            .client
+            .check_contract_verification_status(id)
+            .await?
+            .result)
+    }
+}
+
+/// Client submitting verifications to the target block-explorer via one of the supported apis
+///
+/// The status of submitted verifications is reported in the format of the Etherscan compatible
+/// api (e.g. `Pass - Verified`), so all apis share the same polling.
+#[derive(Debug, Clone)]
+pub(crate) enum TargetClient {
+    Etherscan(EtherscanTarget),
+    BlockscoutV2(BlockscoutV2Client),
+    Routescan(EtherscanTarget),
+    ZkSync(ZkSyncClient),
+    OkLink(EtherscanTarget),
+    /// Adapter set by the user of the library
+    Custom(Arc<dyn TargetExplorer>),
+}
+
+impl TargetClient {
+    /// Build the client for the api of the given type, detected from the url if not set
+    pub(crate) fn new(
+        target_type: Option<TargetType>,
+        api_key: String,
+        url: String,
+        browser_url: Option<String>,
+        http_options: &ExplorerHttpOptions,
+    ) -> Result<Self> {
+        // Pools of api keys are only rotated between when copying, otherwise the first key is used
+        let api_key = api_keys(&api_key).swap_remove(0);
+        let client = match target_type.unwrap_or_else(|| TargetType::detect(&url)) {
+            TargetType::Etherscan => TargetClient::Etherscan(EtherscanTarget::new(
+                api_key,
+                url,
+                browser_url,
+                http_options,
+            )?),
+            TargetType::BlockscoutV2 => TargetClient::BlockscoutV2(BlockscoutV2Client::new(
+                api_key,
+                url,
+                browser_url,
+                http_options,
+            )?),
+            TargetType::Routescan => {
+                // The website can not be derived from the api url, which contains the chain
+                let browser_url = browser_url.unwrap_or_else(|| ROUTESCAN_BROWSER_URL.to_string());
+                let client = EtherscanTarget::new(api_key, url, Some(browser_url), http_options)?;
+                TargetClient::Routescan(client)
+            }
+            TargetType::ZkSync => {
+                TargetClient::ZkSync(ZkSyncClient::new(url, browser_url, http_options)?)
+            }
+            TargetType::OkLink => TargetClient::OkLink(EtherscanTarget::new(
+                api_key,
+                url,
+                browser_url,
+                http_options,
+            )?),
+        };
+        Ok(client)
+    }
+
+    /// Link to the contract's page on the block-explorer's website
+    pub(crate) fn address_url(&self, address: Address) -> String {
+        match self {
+            TargetClient::Etherscan(client)
+            | TargetClient::Routescan(client)
+            | TargetClient::OkLink(client) => client.client.address_url(address),
+            TargetClient::BlockscoutV2(client) => client.address_url(address),
+            TargetClient::ZkSync(client) => client.address_url(address),
+            TargetClient::Custom(explorer) => explorer.address_url(address),
+        }
+    }
+
+    /// Submit a verification request, returning the id to poll its status with
+    pub(crate) async fn submit(
+        &self,
+        verification_request: &VerifyContract,
+    ) -> Result<VerificationRequestResponse> {
+        match self {
+            TargetClient::Etherscan(client)
+            | TargetClient::Routescan(client)
+            | TargetClient::OkLink(client) => client.submit(verification_request).await,
+            TargetClient::BlockscoutV2(client) => {
+                // Blockscout rejects the verification of contracts that are verified already
+                let address = format!("{:?}", verification_request.address);
+                if client.is_verified(&address).await? {
+                    return Ok(VerificationRequestResponse::AlreadyVerified);
+                }
+                client.submit(verification_request).await?;
+                // Blockscout returns no GUID, the verification is polled by address
+                Ok(VerificationRequestResponse::Submitted(address))
+            }
+            TargetClient::ZkSync(_) if vyper::is_vyper_request(verification_request) => {
+                Err(vyper::unsupported())
+            }
+            TargetClient::ZkSync(client) => Ok(match client.submit(verification_request).await? {
+                Some(id) => VerificationRequestResponse::Submitted(id),
+                None => VerificationRequestResponse::AlreadyVerified,
+            }),
+            TargetClient::Custom(explorer) => {
+                Ok(match explorer.submit(verification_request).await? {
+                    Some(id) => VerificationRequestResponse::Submitted(id),
+                    None => VerificationRequestResponse::AlreadyVerified,
+                })
+            }
+        }
+    }
+
+    /// Returns whether the contract is verified on the block-explorer already
+    ///
+    /// The zkSync Era block explorer can not be asked, its contracts are reported as unverified.
+    pub(crate) async fn is_verified(&self, address: Address) -> Result<bool> {
+        if let TargetClient::Custom(explorer) = self {
+            return explorer.is_verified(address).await;
+        }
+        let address = format!("{:?}", address);
+        match self {
+            TargetClient::Etherscan(client)
+            | TargetClient::Routescan(client)
+            | TargetClient::OkLink(client) => client.is_verified(&address).await,
+            TargetClient::BlockscoutV2(client) => client.is_verified(&address).await,
+            TargetClient::ZkSync(_) | TargetClient::Custom(_) => Ok(false),
+        }
+    }
+
+    /// Returns whether the block-explorer can verify contracts with the compiler version of the
+    /// request, which only Blockscout v2 reports (others are assumed to support every version)
+    pub(crate) async fn supports_compiler(
+        &self,
+        verification_request: &VerifyContract,
+    ) -> Result<bool> {
+        match self {
+            TargetClient::BlockscoutV2(client) => {
+                client.supports_compiler(verification_request).await
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// Request the status of the verification with the given id
+    pub(crate) async fn verification_status(&self, id: &str) -> Result<String> {
+        match self {
+            TargetClient::Etherscan(client) => client.verification_status(id).await,
+            TargetClient::BlockscoutV2(client) => client.verification_status(id).await,
+            TargetClient::Routescan(client) => {
+                let status = client.verification_status(id).await?;
+                Ok(normalize_routescan_status(&status))
+            }
+            TargetClient::ZkSync(client) => client.verification_status(id).await,
+            TargetClient::OkLink(client) => {
+                let status = client.verification_status(id).await?;
+                Ok(oklink::normalize_status(&status))
+            }
+            TargetClient::Custom(explorer) => explorer.verification_status(id).await,
+        }
+    }
+
+    /// Mark a verified contract as proxy of the implementation, so the block-explorer shows it
+    /// like the source does
+    ///
+    /// Blockscout has no api for this, it detects the standard proxy patterns on its own, so
+    /// there it is only checked that the contract was detected as proxy of the implementation.
+    /// Each request is retried according to `retry`, waiting for `limiter` before every attempt.
+    pub(crate) async fn mark_proxy(
+        &self,
+        address: Address,
+        implementation: Address,
+        poll: PollOptions,
+        retry: RetryPolicy,
+        limiter: Option<&RateLimiter>,
+    ) -> Result<()> {
+        let address = format!("{:?}", address);
+        let implementation = format!("{:?}", implementation);
+        match self {
+            TargetClient::Etherscan(client)
+            | TargetClient::Routescan(client)
+            | TargetClient::OkLink(client) => {
+                client
+                    .mark_proxy(&address, &implementation, poll, retry, limiter)
+                    .await
+            }
+            TargetClient::BlockscoutV2(client) => {
+                retry
+                    .retry(limiter, || client.check_proxy(&address, &implementation))
+                    .await
+            }
+            // The zkSync Era block explorer has no api for marking proxies
+            TargetClient::ZkSync(_) | TargetClient::Custom(_) => Ok(()),
+        }
+    }
+}
+
+/// Map the status messages of Routescan (e.g. `Pass - Verified` in varying case, `Verified` or
+/// `Fail - ...` without Etherscan's `Unable to verify`) to the ones of Etherscan
+fn normalize_routescan_status(status: &str) -> String {
+    let lowercase = status.trim().to_lowercase();
+    if lowercase.contains("already verified") {
+        "Already Verified".to_string()
+    } else if lowercase.starts_with("pass") || lowercase == "verified" {
+        "Pass - Verified".to_string()
+    } else if lowercase.starts_with("fail") || lowercase.contains("unable to verify") {
+        format!("Fail - Unable to verify: {}", status)
+    } else {
+        status.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            TargetType::detect("https://eth.blockscout.com/api/v2/"),
+            TargetType::BlockscoutV2
+        );
+        assert_eq!(
+            TargetType::detect("https://eth.blockscout.com/api"),
+            TargetType::Etherscan
+        );
+        assert_eq!(
+            TargetType::detect("https://api.etherscan.io/v2/api?chainid=1"),
+            TargetType::Etherscan
+        );
+        assert_eq!(
+            TargetType::detect(&routescan_api_url(43114, false)),
+            TargetType::Routescan
+        );
+        assert_eq!(
+            TargetType::detect("https://zksync2-mainnet-explorer.zksync.io/contract_verification"),
+            TargetType::ZkSync
+        );
+        assert_eq!(
+            TargetType::detect(
+                "https://www.oklink.com/api/v5/explorer/contract/verify-source-code-plugin/XLAYER"
+            ),
+            TargetType::OkLink
+        );
+    }
+
+    #[test]
+    fn test_normalize_routescan_status() {
+        assert_eq!(normalize_routescan_status("Verified"), "Pass - Verified");
+        assert_eq!(
+            normalize_routescan_status("pass - verified"),
+            "Pass - Verified"
+        );
+        assert_eq!(
+            normalize_routescan_status("Contract source code already verified"),
+            "Already Verified"
+        );
+        assert_eq!(
+            normalize_routescan_status("Fail - Bytecode mismatch"),
+            "Fail - Unable to verify: Fail - Bytecode mismatch"
+        );
+        assert_eq!(
+            normalize_routescan_status("Pending in queue"),
+            "Pending in queue"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+use foundry_block_explorers::contract::{Metadata, SourceCodeMetadata};
+
+/// Metadata of a verified Solidity contract `Counter` as reported by an Etherscan compatible
+/// api, with the given source code
+pub(crate) fn metadata(source_code: &str) -> Metadata {
+    let mut metadata: Metadata =
+        serde_json::from_str(include_str!("fixtures/metadata.json")).unwrap();
+    metadata.source_code = SourceCodeMetadata::SourceCode(source_code.to_string());
+    metadata
+}
@@ -0,0 +1,1628 @@
+use crate::bytecode;
+use crate::compile;
+use crate::dedupe::{CloneImplementations, ImplementationCopy, RuntimeCodeFetcher};
+use crate::events::{EventHandler, VerificationEvent};
+use crate::explorer::SourceExplorer;
+use crate::failure::{FailureReason, FailureStage, MigrationError, VerificationFailure};
+use crate::http::ExplorerHttpOptions;
+use crate::import::verification_request_from_export;
+use crate::license::{self, LICENSE_TYPE_PARAMETER};
+use crate::oklink;
+use crate::pending::PendingVerificationStore;
+use crate::poll::{timed_out, PollOptions, PollScheduler};
+use crate::proxy::{self, TargetChain};
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::secrets::api_keys;
+use crate::settings::{
+    add_remappings, libraries_setting, link_libraries, override_settings, parse_library_field,
+    set_contract_path, ContractPath, LinkedLibrary,
+};
+use crate::solc;
+use crate::sourcify::SourcifyClient;
+use crate::target::{TargetClient, TargetType};
+use crate::vyper;
+use crate::yul;
+use crate::zksync::{ZkSyncSource, ZKSOLC_VERSION_PARAMETER};
+use alloy_primitives::Address;
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::contract::{
+    Metadata, SourceCodeEntry, SourceCodeLanguage, SourceCodeMetadata,
+};
+use foundry_block_explorers::errors::EtherscanError;
+use foundry_block_explorers::verify::{CodeFormat, VerifyContract};
+use foundry_block_explorers::Client;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+/// Enum containing different verification outcomes that result in the contract being subsequently
+/// verified on the target block-explorer
+/// Note that failure / errors are accounted for by wrapping this enum in a  standard Result
+pub enum VerificationResult {
+    /// Indicates successful verification of previously unverified contract
+    Success,
+    /// Indicates that the given contract had been verified already
+    AlreadyVerified,
+}
+
+/// Details gathered while copying the verification of a single contract, used for reporting
+#[derive(Debug, Clone, Default)]
+pub struct VerificationDetails {
+    /// Name of the contract as reported by the source block-explorer
+    pub contract_name: Option<String>,
+    /// Compiler version the contract was compiled with
+    pub compiler_version: Option<String>,
+    /// Link to the contract's page on the target block-explorer, set once it is verified there
+    pub target_link: Option<String>,
+    /// Contract the target block-explorer matches this contract to instead of verifying it (e.g.
+    /// the implementation of a minimal proxy), set if its verification was copied instead
+    pub similar_to: Option<String>,
+    /// GUID returned by the target block-explorer once the verification was submitted
+    pub guid: Option<String>,
+    /// Time it took to copy the verification
+    pub duration: Duration,
+    /// Time spent in the individual stages of copying the verification
+    pub timings: StageTimings,
+    /// Problems that did not fail copying the verification, e.g. an additional target (see
+    /// [`CopyOptions::additional_targets`](crate::CopyOptions::additional_targets)) rejecting it
+    pub warnings: Vec<String>,
+    /// Whether the contract is not verified on the source and skipped because of
+    /// [`CopyOptions::skip_unverified`](crate::CopyOptions::skip_unverified)
+    pub skipped: bool,
+}
+
+/// Time spent in the individual stages of copying the verification of a single contract
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StageTimings {
+    /// Asking the target block-explorer whether it verified the contract already
+    pub check: Duration,
+    /// Fetching the metadata from the source block-explorer
+    pub fetch: Duration,
+    /// Submitting the verification request to the target block-explorer
+    pub submit: Duration,
+    /// Polling the target block-explorer until the verification finished
+    pub poll: Duration,
+}
+
+/// Optional settings and hooks used while copying the verification of a contract
+#[derive(Default, Clone, Copy)]
+pub(crate) struct CopyContext<'a> {
+    /// Store recording the GUIDs of submitted verifications until their outcome is known
+    pub(crate) pending: Option<&'a PendingVerificationStore>,
+    /// Handler receiving the state transitions of the verification
+    pub(crate) events: Option<&'a EventHandler>,
+    /// Policy for retrying requests that failed with a transient error
+    pub(crate) retry: RetryPolicy,
+    /// Options for polling the outcome of submitted verifications
+    pub(crate) poll: PollOptions,
+    /// Scheduler polling the outcome of submitted verifications, each verification is polled on
+    /// its own if not set
+    pub(crate) poll_scheduler: Option<&'a PollScheduler>,
+    /// Settings of the http clients connecting to the source block-explorer, whose proxy and
+    /// certificates are also used for fetching the solc release list
+    pub(crate) source_http: Option<&'a ExplorerHttpOptions>,
+    /// Rate limiter for requests to the source block-explorer
+    pub(crate) source_limiter: Option<&'a RateLimiter>,
+    /// Rate limiter for requests to the target block-explorer
+    pub(crate) target_limiter: Option<&'a RateLimiter>,
+    /// Clients of the targets the verification is submitted to after the target block-explorer,
+    /// with the url of their api
+    pub(crate) additional_targets: &'a [(String, TargetClient)],
+    /// Libraries linked in the settings of every verification request
+    pub(crate) libraries: &'a [LinkedLibrary],
+    /// Remappings set in the settings of verification requests that have none
+    pub(crate) remappings: &'a [String],
+    /// Compiler settings merged into the settings of every verification request
+    pub(crate) settings_overrides: Option<&'a serde_json::Value>,
+    /// Fetches the runtime bytecode from the source block-explorer, used to detect minimal
+    /// proxies whose implementation is verified instead if set
+    pub(crate) runtime_code: Option<&'a RuntimeCodeFetcher>,
+    /// Implementations of the minimal proxies copied during the run, each implementation is
+    /// copied for every minimal proxy if not set
+    pub(crate) clone_implementations: Option<&'a CloneImplementations>,
+    /// Mark contracts the source reports as proxies as proxies on the target once verified
+    pub(crate) mark_proxies: bool,
+    /// Fetch and submit every contract without asking the target whether it verified it already
+    pub(crate) skip_verified_check: bool,
+    /// Paths overriding the path in the fully qualified contract names
+    pub(crate) contract_paths: &'a [ContractPath],
+    /// Node of the target chain, checked for the contract's bytecode before anything else if set
+    pub(crate) target_chain: Option<&'a TargetChain>,
+    /// Compare the bytecode on the target chain with the source's before submitting
+    pub(crate) compare_bytecode: bool,
+    /// Compile the verification request locally and compare it with the deployed bytecode
+    /// before submitting
+    pub(crate) compile_locally: bool,
+    /// Check the constructor arguments against the creation transaction before submitting
+    pub(crate) check_constructor_arguments: bool,
+    /// Stop right before submitting the verification request, see
+    /// [`prepare_contract_verification`]
+    pub(crate) dry_run: bool,
+}
+
+impl CopyContext<'_> {
+    fn emit(&self, event: VerificationEvent) {
+        if let Some(events) = self.events {
+            events.emit(event);
+        }
+    }
+}
+
+pub(crate) enum VerificationRequestResponse {
+    Submitted(String),
+    AlreadyVerified,
+}
+
+/// Copy contract verification of a single contract from one block-explorer to another
+///
+/// # Arguments
+/// - `contract_address` - The contract address for which to copy the source code verification
+///   verification
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+///
+/// # Examples
+///
+/// ```rust
+///    let results = contract_verification_migrator_core::copy_etherscan_verification_for_contract(
+///        "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+///        "<YOUR_ETHERSCAN_API_KEY>".to_string(),
+///        "https://api.etherscan.io/api".to_string(),
+///        "<YOUR_BLOCKSCOUT_API_KEY>".to_string(),
+///        "https://eth.blockscout.com/api".to_string(),
+///     );
+///
+/// ```
+pub async fn copy_etherscan_verification_for_contract(
+    contract_address: String,
+    source_api_key: String,
+    source_url: String,
+    target_api_key: String,
+    target_url: String,
+) -> Result<VerificationResult> {
+    let source_client = build_client(source_api_key, source_url, &ExplorerHttpOptions::default())?;
+    let target_client = TargetClient::new(
+        Some(TargetType::Etherscan),
+        target_api_key,
+        target_url,
+        None,
+        &ExplorerHttpOptions::default(),
+    )?;
+    copy_contract_verification(
+        contract_address,
+        MetadataSource::Explorer(&source_client),
+        &target_client,
+        CopyContext::default(),
+        &mut VerificationDetails::default(),
+    )
+    .await
+}
+
+/// Where the verified metadata of the contracts is read from
+#[derive(Clone, Copy)]
+pub(crate) enum MetadataSource<'a> {
+    /// Fetch the metadata from the source block-explorer
+    Explorer(&'a Client),
+    /// Read the metadata from a directory written by
+    /// [`export_etherscan_verification`](crate::export_etherscan_verification)
+    Export(&'a Path),
+    /// Fetch the sources and metadata from the Sourcify repository
+    Sourcify(&'a SourcifyClient),
+    /// Fetch the metadata and zksolc version from the zkSync Era block explorer
+    ZkSync(&'a ZkSyncSource),
+    /// Fetch the verification requests from an adapter set by the user of the library
+    Custom(&'a dyn SourceExplorer),
+}
+
+/// Copy contract verification of a single contract using the hooks of the given `context` and
+/// filling in the `details` as they become available
+///
+/// Errors contain a [`MigrationError`] recording the stage they occurred in.
+pub(crate) async fn copy_contract_verification(
+    contract_address: String,
+    source: MetadataSource<'_>,
+    target_client: &TargetClient,
+    context: CopyContext<'_>,
+    details: &mut VerificationDetails,
+) -> Result<VerificationResult> {
+    let mut stage = FailureStage::Check;
+    copy_stages(
+        contract_address,
+        source,
+        target_client,
+        context,
+        details,
+        &mut stage,
+        false,
+        &mut None,
+    )
+    .await
+    .map_err(|err| MigrationError::staged(stage, err))
+}
+
+/// Build the verification request of a single contract the way
+/// [`copy_contract_verification`] does, stopping right before it would be submitted
+///
+/// Minimal proxies yield the request of their implementation, which is submitted instead. Returns
+/// `None` if nothing would be submitted because the target verified the contract (or the
+/// implementation) already. Errors contain a [`MigrationError`] recording the
+/// stage they occurred in.
+pub(crate) async fn prepare_contract_verification(
+    contract_address: String,
+    source: MetadataSource<'_>,
+    target_client: &TargetClient,
+    context: CopyContext<'_>,
+) -> Result<Option<VerifyContract>> {
+    let mut stage = FailureStage::Check;
+    let mut built_request = None;
+    copy_stages(
+        contract_address,
+        source,
+        target_client,
+        CopyContext {
+            dry_run: true,
+            ..context
+        },
+        &mut VerificationDetails::default(),
+        &mut stage,
+        false,
+        &mut built_request,
+    )
+    .await
+    .map_err(|err| MigrationError::staged(stage, err))?;
+    Ok(built_request)
+}
+
+/// Body of [`copy_contract_verification`], advancing `stage` as the copying progresses and
+/// setting `built_request` to the verification request once it is built
+///
+/// If `target_verified` is set the target verified the contract already, so the verification is
+/// only submitted to the additional targets.
+#[allow(clippy::too_many_arguments)]
+async fn copy_stages(
+    contract_address: String,
+    source: MetadataSource<'_>,
+    target_client: &TargetClient,
+    context: CopyContext<'_>,
+    details: &mut VerificationDetails,
+    stage: &mut FailureStage,
+    target_verified: bool,
+    built_request: &mut Option<VerifyContract>,
+) -> Result<VerificationResult> {
+    let address = contract_address.parse()?;
+    let mut target_code = None;
+    if let Some(target_chain) = context.target_chain {
+        let code = target_chain
+            .code_at(&contract_address, context.retry)
+            .await
+            .wrap_err("Failed to read the bytecode on the target chain")?;
+        if code.is_empty() {
+            return Err(VerificationFailure::report(
+                FailureReason::NotDeployed,
+                "Contract is not deployed on the target chain",
+            ));
+        }
+        target_code = Some(code);
+    }
+    if let (true, Some(target_code), Some(runtime_code)) =
+        (context.compare_bytecode, &target_code, context.runtime_code)
+    {
+        let source_code = runtime_code
+            .fetch(&contract_address, context.retry, context.source_limiter)
+            .await
+            .wrap_err("Failed to fetch the bytecode on the source chain")?;
+        if !bytecode::same_contract(&source_code, target_code) {
+            return Err(VerificationFailure::report(
+                FailureReason::BytecodeMismatch,
+                "Bytecode on the target chain differs from the source chain",
+            ));
+        }
+    }
+    // Skip fetching and submitting contracts the target verified already
+    if !target_verified && !context.skip_verified_check {
+        let start = Instant::now();
+        let verified = context
+            .retry
+            .retry(context.target_limiter, || {
+                target_client.is_verified(address)
+            })
+            .await;
+        details.timings.check = start.elapsed();
+        // Contracts whose status is unknown go through the full round trip
+        if verified.unwrap_or(false) {
+            details.target_link = Some(target_client.address_url(address));
+            if !context.dry_run && (!context.additional_targets.is_empty() || context.mark_proxies)
+            {
+                // The verification is still fetched for the additional targets and the
+                // implementation of proxies, but failing to do so does not fail the contract
+                // verified on the target
+                let copied = Box::pin(copy_stages(
+                    contract_address,
+                    source,
+                    target_client,
+                    context,
+                    details,
+                    stage,
+                    true,
+                    built_request,
+                ))
+                .await;
+                if let Err(err) = copied {
+                    details.warnings.push(format!(
+                        "Failed to fetch the verification of the contract verified on the \
+                         target: {:#}",
+                        err
+                    ));
+                }
+            }
+            return Ok(VerificationResult::AlreadyVerified);
+        }
+    }
+    *stage = FailureStage::Fetch;
+    let start = Instant::now();
+    // Implementation of the contract if the source reports it as proxy
+    let mut proxy_implementation = None;
+    let mut verification_request = match source {
+        MetadataSource::Explorer(source_client) => {
+            let metadata = context
+                .retry
+                .retry(context.source_limiter, || {
+                    fetch_verified_metadata(source_client, &contract_address)
+                })
+                .await;
+            details.timings.fetch = start.elapsed();
+            let Some(metadata) = metadata? else {
+                // Clones have no sources of their own, the target matches them to their
+                // implementation once that is verified
+                if let Some(implementation) = minimal_proxy_of(&contract_address, context).await? {
+                    let (clone_details, clone_stage) = (&mut *details, &mut *stage);
+                    let copy = move || async move {
+                        let mut implementation_request = None;
+                        Box::pin(copy_stages(
+                            format!("{:?}", implementation),
+                            source,
+                            target_client,
+                            context,
+                            clone_details,
+                            clone_stage,
+                            false,
+                            &mut implementation_request,
+                        ))
+                        .await
+                        .map(|_| implementation_request)
+                        .map_err(|err| (FailureReason::of(&err), format!("{:#}", err)))
+                    };
+                    let copied: ImplementationCopy = match context.clone_implementations {
+                        Some(clone_implementations) => {
+                            clone_implementations.copy_once(implementation, copy).await
+                        }
+                        None => copy().await,
+                    };
+                    let implementation_request = copied.map_err(|(reason, message)| {
+                        VerificationFailure::report(
+                            reason,
+                            format!(
+                                "Failed to verify the implementation {:?}: {}",
+                                implementation, message
+                            ),
+                        )
+                    })?;
+                    details.similar_to = Some(format!("{:?}", implementation));
+                    if context.dry_run {
+                        // Only the implementation is submitted, the clone is matched to it
+                        *built_request = implementation_request;
+                        return Ok(VerificationResult::Success);
+                    }
+                    *stage = FailureStage::Check;
+                    let verified = context
+                        .retry
+                        .retry(context.target_limiter, || {
+                            target_client.is_verified(address)
+                        })
+                        .await?;
+                    if !verified {
+                        return Err(VerificationFailure::report(
+                            FailureReason::TargetRejected,
+                            format!(
+                                "The target did not match the clone to its verified \
+                                 implementation {:?}",
+                                implementation
+                            ),
+                        ));
+                    }
+                    details.target_link = Some(target_client.address_url(address));
+                    // The additional targets do not match clones to their implementation
+                    if let Some(mut verification_request) = implementation_request {
+                        verification_request.address = address;
+                        submit_to_additional_targets(&verification_request, context, details).await;
+                    }
+                    return Ok(VerificationResult::AlreadyVerified);
+                }
+                return Err(VerificationFailure::report(
+                    FailureReason::SourceNotVerified,
+                    "Contract is not verified on the source explorer",
+                ));
+            };
+            details.contract_name = Some(metadata.contract_name.clone());
+            proxy_implementation = reported_implementation(&metadata);
+            // Some explorers report no constructor arguments for contracts whose constructor
+            // takes parameters, which are recovered from the creation transaction then
+            let recovered_arguments = if metadata.constructor_arguments.is_empty()
+                && has_constructor_inputs(&metadata.abi)
+            {
+                recover_constructor_arguments(&contract_address, context).await
+            } else {
+                None
+            };
+            *stage = FailureStage::Convert;
+            let mut verification_request =
+                convert_metadata_to_verification_request(&contract_address, &metadata)?;
+            if let Some(arguments) = recovered_arguments {
+                let arguments = hex::encode(arguments);
+                verification_request.constructor_arguments = Some(arguments.clone());
+                verification_request.blockscout_constructor_arguments = Some(arguments);
+            }
+            verification_request
+        }
+        MetadataSource::Export(export_dir) => {
+            let contract_dir = export_dir.join(&contract_address);
+            if !contract_dir.exists() {
+                return Err(VerificationFailure::report(
+                    FailureReason::SourceNotVerified,
+                    format!("Contract was not exported to {}", export_dir.display()),
+                ));
+            }
+            let verification_request = verification_request_from_export(contract_dir)?;
+            details.timings.fetch = start.elapsed();
+            // The export contains the fully qualified name (`<path>:<name>`)
+            let contract_name = verification_request.contract_name.rsplit(':').next();
+            details.contract_name = contract_name.map(ToString::to_string);
+            verification_request
+        }
+        MetadataSource::Sourcify(sourcify_client) => {
+            let verification_request = sourcify_client
+                .fetch_verification_request(
+                    &contract_address,
+                    context.retry,
+                    context.source_limiter,
+                )
+                .await;
+            details.timings.fetch = start.elapsed();
+            let verification_request = verification_request?.ok_or_else(|| {
+                VerificationFailure::report(
+                    FailureReason::SourceNotVerified,
+                    "Contract is not verified on Sourcify",
+                )
+            })?;
+            let contract_name = verification_request.contract_name.rsplit(':').next();
+            details.contract_name = contract_name.map(ToString::to_string);
+            verification_request
+        }
+        MetadataSource::Custom(explorer) => {
+            let verification_request = context
+                .retry
+                .retry(context.source_limiter, || {
+                    explorer.fetch_verification_request(&contract_address)
+                })
+                .await;
+            details.timings.fetch = start.elapsed();
+            let verification_request = verification_request?.ok_or_else(|| {
+                VerificationFailure::report(
+                    FailureReason::SourceNotVerified,
+                    "Contract is not verified on the source",
+                )
+            })?;
+            let contract_name = verification_request.contract_name.rsplit(':').next();
+            details.contract_name = contract_name.map(ToString::to_string);
+            verification_request
+        }
+        MetadataSource::ZkSync(zksync_source) => {
+            let metadata = context
+                .retry
+                .retry(context.source_limiter, || {
+                    zksync_source.fetch_metadata(&contract_address)
+                })
+                .await;
+            details.timings.fetch = start.elapsed();
+            let (metadata, zksolc_version) = metadata?.ok_or_else(|| {
+                VerificationFailure::report(
+                    FailureReason::SourceNotVerified,
+                    "Contract is not verified on the source explorer",
+                )
+            })?;
+            details.contract_name = Some(metadata.contract_name.clone());
+            proxy_implementation = reported_implementation(&metadata);
+            *stage = FailureStage::Convert;
+            let mut verification_request =
+                convert_metadata_to_verification_request(&contract_address, &metadata)?;
+            if let Some(zksolc_version) = zksolc_version {
+                verification_request
+                    .other
+                    .insert(ZKSOLC_VERSION_PARAMETER.to_string(), zksolc_version);
+            }
+            verification_request
+        }
+    };
+    *stage = FailureStage::Convert;
+    set_contract_path(&mut verification_request, context.contract_paths)?;
+    link_libraries(&mut verification_request, context.libraries)?;
+    add_remappings(&mut verification_request, context.remappings)?;
+    if let Some(overrides) = context.settings_overrides {
+        override_settings(&mut verification_request, overrides)?;
+    }
+    solc::resolve_compiler_version(
+        &mut verification_request,
+        context
+            .source_http
+            .unwrap_or(&ExplorerHttpOptions::default()),
+    )
+    .await;
+    let compiler_version = verification_request.compiler_version.clone();
+    details.compiler_version = Some(compiler_version.clone());
+    context.emit(VerificationEvent::Fetched {
+        address: contract_address.clone(),
+        contract_name: details.contract_name.clone().unwrap_or_default(),
+        compiler_version,
+    });
+    *built_request = Some(verification_request.clone());
+    let result = if target_verified {
+        VerificationResult::AlreadyVerified
+    } else {
+        let supported = context
+            .retry
+            .retry(context.target_limiter, || {
+                target_client.supports_compiler(&verification_request)
+            })
+            .await;
+        // Targets whose supported versions are unknown get the verification submitted regardless
+        if !supported.unwrap_or(true) {
+            return Err(VerificationFailure::report(
+                FailureReason::CompilerMismatch,
+                format!(
+                    "Compiler {} is not available on the target",
+                    verification_request.compiler_version
+                ),
+            ));
+        }
+        if context.check_constructor_arguments {
+            check_constructor_arguments(&verification_request, context).await?;
+        }
+        if context.compile_locally {
+            check_local_compilation(&verification_request, target_code, context).await?;
+        }
+        if context.dry_run {
+            return Ok(VerificationResult::Success);
+        }
+        *stage = FailureStage::Submit;
+        submit_and_await_verification(
+            verification_request.clone(),
+            target_client,
+            context,
+            details,
+        )
+        .await?
+    };
+    details.target_link = Some(target_client.address_url(address));
+    submit_to_additional_targets(&verification_request, context, details).await;
+    if let (true, Some(implementation)) = (context.mark_proxies, proxy_implementation) {
+        // The contract is verified either way, so failing to mark it does not fail copying
+        let marked = target_client
+            .mark_proxy(
+                address,
+                implementation,
+                context.poll,
+                context.retry,
+                context.target_limiter,
+            )
+            .await;
+        if let Err(err) = marked {
+            details.warnings.push(format!(
+                "Failed to mark the contract as proxy on the target: {:#}",
+                err
+            ));
+        }
+    }
+    Ok(result)
+}
+
+/// Returns whether the constructor of the contract described by the abi takes parameters
+fn has_constructor_inputs(abi: &str) -> bool {
+    let Ok(serde_json::Value::Array(items)) = serde_json::from_str(abi) else {
+        return false;
+    };
+    items.iter().any(|item| {
+        item["type"] == "constructor"
+            && item["inputs"]
+                .as_array()
+                .is_some_and(|inputs| !inputs.is_empty())
+    })
+}
+
+/// Constructor arguments of the contract read from the input of the transaction that created
+/// it, `None` if they can not be determined (e.g. because a factory created it)
+async fn recover_constructor_arguments(address: &str, context: CopyContext<'_>) -> Option<Vec<u8>> {
+    let runtime_code = context.runtime_code?;
+    let creation_input = runtime_code
+        .creation_input(address, context.retry, context.source_limiter)
+        .await
+        .ok()??;
+    let code = runtime_code
+        .fetch(address, context.retry, context.source_limiter)
+        .await
+        .ok()?;
+    bytecode::constructor_arguments(&creation_input, &code)
+        .filter(|arguments| !arguments.is_empty())
+        .map(<[u8]>::to_vec)
+}
+
+/// Fail if the input of the transaction that created the contract does not end with the
+/// constructor arguments of the verification request, skipping contracts created by other
+/// contracts, contracts without constructor arguments and contracts whose creation transaction
+/// is unavailable
+async fn check_constructor_arguments(
+    verification_request: &VerifyContract,
+    context: CopyContext<'_>,
+) -> Result<()> {
+    let Some(runtime_code) = context.runtime_code else {
+        return Ok(());
+    };
+    let constructor_arguments = hex::decode(
+        verification_request
+            .constructor_arguments
+            .as_deref()
+            .unwrap_or_default()
+            .trim_start_matches("0x"),
+    )?;
+    if constructor_arguments.is_empty() {
+        return Ok(());
+    }
+    let address = format!("{:?}", verification_request.address);
+    let Ok(creation_input) = runtime_code
+        .creation_input(&address, context.retry, context.source_limiter)
+        .await
+    else {
+        return Ok(());
+    };
+    match creation_input {
+        Some(input) if !input.ends_with(&constructor_arguments) => {
+            Err(VerificationFailure::report(
+                FailureReason::ConstructorArgumentsMismatch,
+                "Constructor arguments differ from the end of the creation transaction's input",
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Compile the verification request with solc and fail if the bytecode differs from the one
+/// deployed on the target chain (or the source chain if the target chain is not read), skipping
+/// Vyper and zkSync contracts and contracts whose deployed bytecode is unavailable
+async fn check_local_compilation(
+    verification_request: &VerifyContract,
+    target_code: Option<Vec<u8>>,
+    context: CopyContext<'_>,
+) -> Result<()> {
+    if vyper::is_vyper_request(verification_request)
+        || verification_request
+            .other
+            .contains_key(ZKSOLC_VERSION_PARAMETER)
+    {
+        return Ok(());
+    }
+    let address = format!("{:?}", verification_request.address);
+    let deployed_code = match (target_code, context.runtime_code) {
+        (Some(code), _) => code,
+        (None, Some(runtime_code)) => {
+            runtime_code
+                .fetch(&address, context.retry, context.source_limiter)
+                .await?
+        }
+        (None, None) => return Ok(()),
+    };
+    let local_code = compile::compile_runtime_code(verification_request).await?;
+    if !bytecode::same_contract(&local_code, &deployed_code) {
+        return Err(VerificationFailure::report(
+            FailureReason::BytecodeMismatch,
+            "Locally compiled bytecode differs from the deployed bytecode",
+        ));
+    }
+    Ok(())
+}
+
+/// Implementation of the contract if its runtime bytecode is an EIP-1167 minimal proxy
+async fn minimal_proxy_of(address: &str, context: CopyContext<'_>) -> Result<Option<Address>> {
+    let Some(runtime_code) = context.runtime_code else {
+        return Ok(None);
+    };
+    let code = runtime_code
+        .fetch(address, context.retry, context.source_limiter)
+        .await?;
+    Ok(proxy::minimal_proxy_implementation(&code))
+}
+
+/// Implementation of the contract if the metadata reports it as proxy
+fn reported_implementation(metadata: &Metadata) -> Option<Address> {
+    metadata
+        .implementation
+        .filter(|implementation| metadata.proxy == 1 && !implementation.is_zero())
+}
+
+/// Submit the verification to the additional targets one after the other, recording the ones
+/// that do not verify the contract as warnings in `details`
+pub(crate) async fn submit_to_additional_targets(
+    verification_request: &VerifyContract,
+    context: CopyContext<'_>,
+    details: &mut VerificationDetails,
+) {
+    // The hooks and limits only apply to the target block-explorer
+    let context = CopyContext {
+        pending: None,
+        events: None,
+        poll_scheduler: None,
+        target_limiter: None,
+        ..context
+    };
+    for (url, target_client) in context.additional_targets {
+        let verified = submit_and_await_verification(
+            verification_request.clone(),
+            target_client,
+            context,
+            &mut VerificationDetails::default(),
+        )
+        .await;
+        if let Err(err) = verified {
+            details
+                .warnings
+                .push(format!("Failed to verify on {}: {:#}", url, err));
+        }
+    }
+}
+
+/// Submit a verification request to the target block-explorer and wait for its outcome, recording
+/// the GUID and the time spent submitting and polling in `details`
+pub(crate) async fn submit_and_await_verification(
+    verification_request: VerifyContract,
+    target_client: &TargetClient,
+    context: CopyContext<'_>,
+    details: &mut VerificationDetails,
+) -> Result<VerificationResult> {
+    let contract_address = verification_request.address.to_string();
+    let start = Instant::now();
+    let verification_response = context
+        .retry
+        .retry(context.target_limiter, || {
+            target_client.submit(&verification_request)
+        })
+        .await;
+    details.timings.submit = start.elapsed();
+    let verification_response = verification_response?;
+    match verification_response {
+        VerificationRequestResponse::Submitted(id) => {
+            details.guid = Some(id.clone());
+            context.emit(VerificationEvent::Submitted {
+                address: contract_address.clone(),
+                guid: id.clone(),
+            });
+            if let Some(pending) = context.pending {
+                // The additional targets are submitted to once the resumed verification passed
+                let verification_request =
+                    (!context.additional_targets.is_empty()).then_some(&verification_request);
+                pending.insert(&contract_address, &id, verification_request)?;
+            }
+            context.emit(VerificationEvent::Polling {
+                address: contract_address.clone(),
+                guid: id.clone(),
+            });
+            let start = Instant::now();
+            let result = await_contract_verification(id.clone(), target_client, context)
+                .await
+                .map_err(|err| MigrationError::staged(FailureStage::Poll, err));
+            details.timings.poll = start.elapsed();
+            if let (Some(pending), Ok(_)) = (context.pending, &result) {
+                pending.remove(&id)?;
+            }
+            result
+        }
+        VerificationRequestResponse::AlreadyVerified => Ok(VerificationResult::AlreadyVerified),
+    }
+}
+
+pub(crate) fn build_client(
+    api_key: String,
+    url: String,
+    http_options: &ExplorerHttpOptions,
+) -> Result<Client> {
+    // Reading the contracts does not link to the website, so it does not have to be known
+    let browser_url = browser_url_from_api_url(&url).is_err().then(|| url.clone());
+    build_client_with_browser_url(api_key, url, browser_url, http_options)
+}
+
+/// Build a client whose links point to the given website url instead of the one derived from
+/// the api url
+pub(crate) fn build_client_with_browser_url(
+    api_key: String,
+    url: String,
+    browser_url: Option<String>,
+    http_options: &ExplorerHttpOptions,
+) -> Result<Client> {
+    // Pools of api keys are only rotated between when copying, otherwise the first key is used
+    let api_key = api_keys(&api_key).swap_remove(0);
+    let is_oklink = oklink::is_oklink_url(&url);
+    let browser_url = match browser_url {
+        // The address url is appended to the website url, so it has to end with a slash
+        Some(browser_url) => format!("{}/", browser_url.trim_end_matches('/')),
+        None if is_oklink => oklink::browser_url(&url),
+        None => browser_url_from_api_url(&url)?,
+    };
+    let http_client = if is_oklink {
+        oklink::http_client(&api_key, http_options)?
+    } else {
+        http_options.client()?
+    };
+    Ok(Client::builder()
+        .with_client(http_client)
+        .with_api_key(api_key)
+        .with_url(browser_url)?
+        .with_api_url(url)?
+        .build()?)
+}
+
+/// Url of Etherscan's multichain (V2) api, which selects the chain via the `chainid` parameter
+pub const ETHERSCAN_V2_API_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// Websites of the chains served by Etherscan's multichain api, used for the links to verified
+/// contracts
+const ETHERSCAN_V2_BROWSER_URLS: &[(u64, &str)] = &[
+    (1, "https://etherscan.io/"),
+    (10, "https://optimistic.etherscan.io/"),
+    (56, "https://bscscan.com/"),
+    (137, "https://polygonscan.com/"),
+    (8453, "https://basescan.org/"),
+    (17000, "https://holesky.etherscan.io/"),
+    (42161, "https://arbiscan.io/"),
+    (59144, "https://lineascan.build/"),
+    (534352, "https://scrollscan.com/"),
+    (11155111, "https://sepolia.etherscan.io/"),
+];
+
+/// Returns whether Etherscan's multichain api serves the chain
+pub(crate) fn is_etherscan_v2_chain(chain_id: u64) -> bool {
+    ETHERSCAN_V2_BROWSER_URLS
+        .iter()
+        .any(|(id, _)| *id == chain_id)
+}
+
+/// Select the chain of a multichain api (e.g. [`ETHERSCAN_V2_API_URL`]) by setting the `chainid`
+/// parameter of its url, which is sent along with every request
+///
+/// # Examples
+///
+/// ```rust
+/// use contract_verification_migrator_core::{api_url_with_chain_id, ETHERSCAN_V2_API_URL};
+///
+/// let url = api_url_with_chain_id(ETHERSCAN_V2_API_URL, 10).unwrap();
+/// assert_eq!(url, "https://api.etherscan.io/v2/api?chainid=10");
+/// ```
+pub fn api_url_with_chain_id(api_url: &str, chain_id: u64) -> Result<String> {
+    let mut url =
+        reqwest::Url::parse(api_url).wrap_err_with(|| format!("Invalid api url {}", api_url))?;
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "chainid")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("chainid", &chain_id.to_string());
+    Ok(url.to_string())
+}
+
+/// Derive the url of the block-explorer's website from the url of its api
+///
+/// Strips a trailing `/api` (or `/v2/api`) path and an `api.` / `api-` subdomain, e.g.
+/// `https://api-sepolia.etherscan.io/api` becomes `https://sepolia.etherscan.io/`. For
+/// Etherscan's multichain api the website of the chain selected via `chainid` is returned, which
+/// fails for chains whose website is not known.
+pub(crate) fn browser_url_from_api_url(api_url: &str) -> Result<String> {
+    let Ok(mut url) = reqwest::Url::parse(api_url) else {
+        return Ok(api_url.to_string());
+    };
+    let chain_id = url
+        .query_pairs()
+        .find(|(key, _)| key == "chainid")
+        .and_then(|(_, value)| value.parse::<u64>().ok());
+    let path = url.path().trim_end_matches('/');
+    let path = path.strip_suffix("/api").unwrap_or(path).to_string();
+    if path.ends_with("/v2") {
+        // The website differs per chain, guessing it would link to the wrong chain
+        return ETHERSCAN_V2_BROWSER_URLS
+            .iter()
+            .find(|(id, _)| Some(*id) == chain_id)
+            .map(|(_, browser_url)| browser_url.to_string())
+            .ok_or_else(|| {
+                eyre!(
+                    "Unknown website of the chain of {}, set the browser url (e.g. via \
+                     --target-browser-url)",
+                    api_url
+                )
+            });
+    }
+    url.set_path(&format!("{}/", path));
+    url.set_query(None);
+    if let Some(host) = url.host_str() {
+        if let Some(stripped) = host
+            .strip_prefix("api.")
+            .or_else(|| host.strip_prefix("api-"))
+        {
+            let stripped = stripped.to_string();
+            let _ = url.set_host(Some(&stripped));
+        }
+    }
+    Ok(url.to_string())
+}
+
+/// Fetch the verified source code metadata of a contract, returning `None` if it is not verified
+pub(crate) async fn fetch_verified_metadata(
+    client: &Client,
+    contract_address: &str,
+) -> Result<Option<Metadata>> {
+    match client.contract_source_code(contract_address.parse()?).await {
+        Ok(contract_metadata) => Ok(contract_metadata
+            .items
+            .into_iter()
+            .next()
+            .filter(|metadata| !metadata.contract_name.is_empty())),
+        Err(EtherscanError::ContractCodeNotVerified(_)) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns the contract's source files keyed by their path
+///
+/// Contracts verified as a single file are returned as `<ContractName>.sol`.
+pub(crate) fn source_files(metadata: &Metadata) -> BTreeMap<String, String> {
+    match &metadata.source_code {
+        SourceCodeMetadata::SourceCode(source_code) => BTreeMap::from([(
+            format!("{}.sol", metadata.contract_name),
+            source_code.clone(),
+        )]),
+        SourceCodeMetadata::Metadata { sources, .. } | SourceCodeMetadata::Sources(sources) => {
+            sources
+                .iter()
+                .map(|(path, entry)| (path.clone(), entry.content.clone()))
+                .collect()
+        }
+    }
+}
+
+/// Convert the verified metadata reported by an Etherscan compatible api into the verification
+/// request submitted to the target, as done for every contract copied
+///
+/// Solidity contracts are converted into standard-json (with the settings reconstructed if the
+/// metadata lacks them, see [`reconstructed_settings`]), Vyper and Yul contracts into the
+/// standard-json of their language.
+///
+/// # Examples
+///
+/// ```rust
+/// use contract_verification_migrator_core::convert_metadata_to_verification_request;
+/// use foundry_block_explorers::contract::Metadata;
+///
+/// // Metadata of a verified contract, e.g. as returned by `Client::contract_source_code`
+/// let metadata: Metadata =
+///     serde_json::from_str(include_str!("fixtures/metadata.json")).unwrap();
+/// let verification_request = convert_metadata_to_verification_request(
+///     "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+///     &metadata,
+/// )
+/// .unwrap();
+/// assert_eq!(verification_request.contract_name, "Counter.sol:Counter");
+/// ```
+pub fn convert_metadata_to_verification_request(
+    contract_address: &str,
+    metadata: &Metadata,
+) -> Result<VerifyContract> {
+    let mut verification_request = if vyper::is_vyper_metadata(metadata) {
+        vyper::verification_request_from_metadata(contract_address, metadata)?
+    } else if let Some(verification_request) =
+        yul::verification_request_from_metadata(contract_address, metadata)?
+    {
+        verification_request
+    } else {
+        solidity_verification_request(contract_address, metadata)?
+    };
+    // Targets show contracts without the license as "no license"
+    if let Some(code) = license::etherscan_license_code(&metadata.license_type) {
+        verification_request
+            .other
+            .insert(LICENSE_TYPE_PARAMETER.to_string(), code.to_string());
+    }
+    Ok(verification_request)
+}
+
+fn solidity_verification_request(
+    contract_address: &str,
+    metadata: &Metadata,
+) -> Result<VerifyContract> {
+    let mut contract_name = format!("{}.sol:{}", metadata.contract_name, metadata.contract_name);
+    let source = match &metadata.source_code {
+        // Blockscout does not accept "single-file" source code for verificatin so we convert it
+        // into "solidity-standard-json-input" format
+        SourceCodeMetadata::SourceCode(..) => {
+            let mut source_code_entries: HashMap<String, SourceCodeEntry> = HashMap::new();
+            source_code_entries.insert(
+                contract_name.clone(),
+                SourceCodeEntry {
+                    content: metadata.source_code(),
+                },
+            );
+            let source_code = SourceCodeMetadata::Metadata {
+                language: Some(SourceCodeLanguage::Solidity),
+                settings: Some(reconstructed_settings(metadata, &source_code_entries)),
+                sources: source_code_entries,
+            };
+            serde_json::to_string(&source_code)?
+        }
+        SourceCodeMetadata::Metadata {
+            sources, settings, ..
+        } => {
+            let path = settings
+                .as_ref()
+                .and_then(|settings| compilation_target(settings, &metadata.contract_name))
+                .unwrap_or_else(|| contract_source_path(sources, &metadata.contract_name));
+            contract_name = format!("{}:{}", path, metadata.contract_name);
+            serde_json::to_string(&metadata.source_code)?
+        }
+        // Multiple files without settings, which are reconstructed like for a single file
+        SourceCodeMetadata::Sources(sources) => {
+            contract_name = format!(
+                "{}:{}",
+                contract_source_path(sources, &metadata.contract_name),
+                metadata.contract_name
+            );
+            let source_code = SourceCodeMetadata::Metadata {
+                language: Some(SourceCodeLanguage::Solidity),
+                settings: Some(reconstructed_settings(metadata, sources)),
+                sources: sources.clone(),
+            };
+            serde_json::to_string(&source_code)?
+        }
+    };
+    let compiler_version = normalize_compiler_version(&metadata.compiler_version);
+
+    let verification_request = VerifyContract {
+        address: contract_address.parse()?,
+        code_format: CodeFormat::StandardJsonInput,
+        contract_name: contract_name.clone(),
+        compiler_version,
+        runs: Some(metadata.runs.to_string()),
+        optimization_used: Some(metadata.optimization_used.to_string()),
+        constructor_arguments: Some(hex::encode(metadata.constructor_arguments.clone())),
+        blockscout_constructor_arguments: Some(hex::encode(metadata.constructor_arguments.clone())),
+        evm_version: Some(metadata.evm_version.clone()),
+        source,
+        other: std::collections::HashMap::new(),
+    };
+    Ok(verification_request)
+}
+
+/// Compiler settings reconstructed from the fields of the metadata (optimizer, evm version and
+/// linked libraries), for contracts whose sources are not verified as standard-json
+///
+/// The `sources` (keyed by their path) determine the paths of the linked libraries.
+pub fn reconstructed_settings(
+    metadata: &Metadata,
+    sources: &HashMap<String, SourceCodeEntry>,
+) -> serde_json::Value {
+    let libraries = parse_library_field(&metadata.library);
+    let mut settings = json!({
+        "libraries": libraries_setting(&libraries, sources),
+        "optimizer": {
+            "enabled": metadata.optimization_used == 1,
+            "runs": metadata.runs,
+        },
+        "remappings": [],
+    });
+    if let Some(evm_version) = explicit_evm_version(metadata) {
+        settings["evmVersion"] = json!(evm_version);
+    }
+    settings
+}
+
+/// The evm version the contract was compiled for, `None` if it is the compiler's default (which
+/// explorers report as `Default`)
+pub(crate) fn explicit_evm_version(metadata: &Metadata) -> Option<&str> {
+    let evm_version = metadata.evm_version.as_str();
+    (!evm_version.is_empty() && !evm_version.eq_ignore_ascii_case("default")).then_some(evm_version)
+}
+
+/// Path of the contract in the `compilationTarget` of the settings (`{"<path>": "<name>"}`), which
+/// standard-json derived from the solc metadata keeps
+fn compilation_target(settings: &serde_json::Value, contract_name: &str) -> Option<String> {
+    settings["compilationTarget"]
+        .as_object()?
+        .iter()
+        .find(|(_, name)| name.as_str() == Some(contract_name))
+        .map(|(path, _)| path.clone())
+}
+
+/// Path of the source file declaring the contract (or library), the only source or
+/// `<ContractName>.sol` if none of them does
+///
+/// If several files declare a contract of that name the one named after it (e.g.
+/// `contracts/core/Vault.sol`) is preferred.
+pub(crate) fn contract_source_path(
+    sources: &HashMap<String, SourceCodeEntry>,
+    contract_name: &str,
+) -> String {
+    let declarations: Vec<String> = ["contract", "library"]
+        .iter()
+        .flat_map(|kind| {
+            [
+                format!("{} {} ", kind, contract_name),
+                format!("{} {}{{", kind, contract_name),
+            ]
+        })
+        .collect();
+    let mut paths: Vec<&String> = sources
+        .iter()
+        .filter(|(_, entry)| {
+            declarations
+                .iter()
+                .any(|declaration| entry.content.contains(declaration.as_str()))
+        })
+        .map(|(path, _)| path)
+        .collect();
+    // Sorted so the path does not depend on the order of the map
+    paths.sort();
+    let file_name = format!("{}.sol", contract_name);
+    let named_path = paths
+        .iter()
+        .find(|path| path.rsplit('/').next() == Some(file_name.as_str()));
+    match (named_path.or(paths.first()), sources.keys().next()) {
+        (Some(path), _) => path.to_string(),
+        (None, Some(path)) if sources.len() == 1 => path.clone(),
+        _ => file_name,
+    }
+}
+
+/// Normalize the compiler version into the `v0.8.19+commit.7dd6d404` format expected by etherscan
+///
+/// Versions missing the commit are completed from the release list on submission, see
+/// [`solc::resolve_compiler_version`].
+pub(crate) fn normalize_compiler_version(compiler_version: &str) -> String {
+    // Apparently sometimes Blockscout omits the leading v in the contract version
+    let version = compiler_version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    // Drop platform suffixes after the commit (e.g. `+commit.d19bba13.Linux.g++`)
+    match version.split_once("+commit.") {
+        Some((version, commit)) => {
+            let commit = commit.split('.').next().unwrap_or_default();
+            format!("v{}+commit.{}", version, commit)
+        }
+        None => format!("v{}", version),
+    }
+}
+
+pub(crate) async fn send_verification_request(
+    verification_request: &VerifyContract,
+    target_client: &Client,
+) -> Result<VerificationRequestResponse> {
+    let verification_response = target_client
+        .submit_contract_verification(verification_request)
+        .await?;
+    verification_request_response(&verification_response.message, verification_response.result)
+}
+
+/// Interpret the response of an Etherscan compatible api to a verification request, whose
+/// result is the GUID of the verification if the message is `OK`
+pub(crate) fn verification_request_response(
+    message: &str,
+    result: String,
+) -> Result<VerificationRequestResponse> {
+    if message != "OK" {
+        if result.to_lowercase().contains("already verified") {
+            return Ok(VerificationRequestResponse::AlreadyVerified);
+        }
+        return Err(VerificationFailure::report(
+            FailureReason::from_target_message(&result),
+            format!("Verification returned non-ok response: {}", result),
+        ));
+    }
+    Ok(VerificationRequestResponse::Submitted(result))
+}
+
+/// Poll the target until the verification with the given id finished, via the scheduler of the
+/// context if set
+///
+/// Exposed as [`await_verification`](crate::await_verification).
+pub(crate) async fn await_contract_verification(
+    id: String,
+    target_client: &TargetClient,
+    context: CopyContext<'_>,
+) -> Result<VerificationResult> {
+    if let Some(scheduler) = context.poll_scheduler {
+        return scheduler.await_verification(id, context.poll).await;
+    }
+    tokio::time::sleep(context.poll.initial_delay).await;
+    for attempt in 0..context.poll.max_attempts {
+        let status = context
+            .retry
+            .retry(context.target_limiter, || {
+                target_client.verification_status(&id)
+            })
+            .await
+            .wrap_err("Failed to request verification status")?;
+
+        if let Some(result) = verification_status(&status) {
+            return result;
+        }
+
+        // Wait before checking again, backing off the longer the verification takes
+        tokio::time::sleep(context.poll.delay(attempt)).await;
+    }
+    Err(timed_out())
+}
+
+/// Interpret the status of a verification reported by the target block-explorer, returns `None`
+/// while the verification is still pending
+pub(crate) fn verification_status(status: &str) -> Option<Result<VerificationResult>> {
+    if status.contains("Unable to verify") {
+        return Some(Err(VerificationFailure::report(
+            FailureReason::TargetRejected,
+            "Unable to verify.",
+        )));
+    }
+    match status {
+        "Already Verified" => Some(Ok(VerificationResult::AlreadyVerified)),
+        "Pass - Verified" => Some(Ok(VerificationResult::Success)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_url_from_api_url() {
+        assert_eq!(
+            browser_url_from_api_url("https://api.etherscan.io/api").unwrap(),
+            "https://etherscan.io/"
+        );
+        assert_eq!(
+            browser_url_from_api_url("https://api-sepolia.etherscan.io/api").unwrap(),
+            "https://sepolia.etherscan.io/"
+        );
+        assert_eq!(
+            browser_url_from_api_url("https://eth.blockscout.com/api").unwrap(),
+            "https://eth.blockscout.com/"
+        );
+        assert_eq!(
+            browser_url_from_api_url("https://api.etherscan.io/v2/api?chainid=8453").unwrap(),
+            "https://basescan.org/"
+        );
+        assert!(
+            browser_url_from_api_url("https://api.etherscan.io/v2/api?chainid=999999").is_err()
+        );
+        assert!(browser_url_from_api_url("https://api.etherscan.io/v2/api").is_err());
+    }
+
+    #[test]
+    fn test_api_url_with_chain_id() {
+        assert_eq!(
+            api_url_with_chain_id("https://api.etherscan.io/v2/api?chainid=1", 10).unwrap(),
+            "https://api.etherscan.io/v2/api?chainid=10"
+        );
+        assert!(api_url_with_chain_id("not a url", 1).is_err());
+    }
+
+    #[test]
+    fn test_convert_sources_metadata() {
+        let mut metadata = crate::test_utils::metadata("");
+        metadata.runs = 10000;
+        metadata.evm_version = "paris".to_string();
+        metadata.source_code = SourceCodeMetadata::Sources(HashMap::from([
+            (
+                "src/Counter.sol".to_string(),
+                SourceCodeEntry {
+                    content: "import \"./Base.sol\";\ncontract Counter is Base {}".to_string(),
+                },
+            ),
+            (
+                "src/Base.sol".to_string(),
+                SourceCodeEntry {
+                    content: "contract Base {}".to_string(),
+                },
+            ),
+        ]));
+        let verification_request = convert_metadata_to_verification_request(
+            "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            &metadata,
+        )
+        .unwrap();
+        assert_eq!(
+            verification_request.contract_name,
+            "src/Counter.sol:Counter"
+        );
+        assert_eq!(
+            verification_request.other.get(LICENSE_TYPE_PARAMETER),
+            Some(&"3".to_string())
+        );
+        let standard_json: serde_json::Value =
+            serde_json::from_str(&verification_request.source).unwrap();
+        assert_eq!(standard_json["language"], "Solidity");
+        assert_eq!(
+            standard_json["settings"],
+            json!({
+                "evmVersion": "paris",
+                "libraries": {},
+                "optimizer": { "enabled": true, "runs": 10000 },
+                "remappings": [],
+            })
+        );
+        assert_eq!(
+            standard_json["sources"]["src/Base.sol"]["content"],
+            "contract Base {}"
+        );
+
+        metadata.evm_version = "Default".to_string();
+        metadata.library = "Math:0x02".to_string();
+        let settings = reconstructed_settings(&metadata, &HashMap::new());
+        assert!(settings.get("evmVersion").is_none());
+        assert_eq!(
+            settings["libraries"],
+            json!({ "Math.sol": { "Math": "0x02" } })
+        );
+    }
+
+    #[test]
+    fn test_convert_standard_json_metadata() {
+        let mut metadata = crate::test_utils::metadata("");
+        let settings = json!({
+            "optimizer": { "enabled": true, "runs": 200, "details": { "yul": true } },
+            "remappings": ["@oz/=lib/oz/"],
+            "viaIR": true,
+            "metadata": { "bytecodeHash": "none" },
+        });
+        metadata.source_code = SourceCodeMetadata::Metadata {
+            language: Some(SourceCodeLanguage::Solidity),
+            sources: HashMap::from([(
+                "src/Counter.sol".to_string(),
+                SourceCodeEntry {
+                    content: "contract Counter {}".to_string(),
+                },
+            )]),
+            settings: Some(settings.clone()),
+        };
+        let verification_request = convert_metadata_to_verification_request(
+            "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            &metadata,
+        )
+        .unwrap();
+        let standard_json: serde_json::Value =
+            serde_json::from_str(&verification_request.source).unwrap();
+        assert_eq!(standard_json["settings"], settings);
+        assert_eq!(
+            standard_json["settings"]["remappings"],
+            json!(["@oz/=lib/oz/"])
+        );
+        assert_eq!(
+            verification_request.contract_name,
+            "src/Counter.sol:Counter"
+        );
+    }
+
+    #[test]
+    fn test_normalize_compiler_version() {
+        for (version, normalized) in [
+            ("v0.8.19+commit.7dd6d404", "v0.8.19+commit.7dd6d404"),
+            ("0.8.19+commit.7dd6d404", "v0.8.19+commit.7dd6d404"),
+            (
+                "0.5.17+commit.d19bba13.Linux.g++",
+                "v0.5.17+commit.d19bba13",
+            ),
+            (
+                "v0.4.24+commit.e67f0147.Emscripten.clang",
+                "v0.4.24+commit.e67f0147",
+            ),
+            (" 0.8.19 ", "v0.8.19"),
+        ] {
+            assert_eq!(normalize_compiler_version(version), normalized);
+        }
+    }
+
+    #[test]
+    fn test_contract_source_path() {
+        let source = |content: &str| SourceCodeEntry {
+            content: content.to_string(),
+        };
+        let sources = HashMap::from([
+            (
+                "contracts/core/Vault.sol".to_string(),
+                source("contract Vault is Base {}"),
+            ),
+            (
+                "contracts/mocks/MockVault.sol".to_string(),
+                source("contract Vault {}"),
+            ),
+            (
+                "contracts/Base.sol".to_string(),
+                source("abstract contract Base {}"),
+            ),
+        ]);
+        assert_eq!(
+            contract_source_path(&sources, "Vault"),
+            "contracts/core/Vault.sol"
+        );
+        assert_eq!(
+            compilation_target(
+                &json!({ "compilationTarget": { "contracts/mocks/MockVault.sol": "Vault" } }),
+                "Vault"
+            ),
+            Some("contracts/mocks/MockVault.sol".to_string())
+        );
+    }
+
+    /// Source and target counting the fetched and submitted contracts, the target reports every
+    /// contract as verified already
+    #[derive(Debug, Default)]
+    struct Counting {
+        fetched: std::sync::atomic::AtomicUsize,
+        submitted: std::sync::atomic::AtomicUsize,
+    }
+
+    impl SourceExplorer for Counting {
+        fn fetch_verification_request<'a>(
+            &'a self,
+            address: &'a str,
+        ) -> futures::future::BoxFuture<'a, Result<Option<VerifyContract>>> {
+            self.fetched
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async move {
+                Ok(Some(VerifyContract {
+                    address: address.parse()?,
+                    source: "contract Counter {}".to_string(),
+                    code_format: CodeFormat::SingleFile,
+                    contract_name: "Counter".to_string(),
+                    compiler_version: "v0.8.19+commit.7dd6d404".to_string(),
+                    optimization_used: None,
+                    runs: None,
+                    constructor_arguments: None,
+                    blockscout_constructor_arguments: None,
+                    evm_version: None,
+                    other: Default::default(),
+                }))
+            })
+        }
+    }
+
+    impl crate::TargetExplorer for Counting {
+        fn submit<'a>(
+            &'a self,
+            _verification_request: &'a VerifyContract,
+        ) -> futures::future::BoxFuture<'a, Result<Option<String>>> {
+            self.submitted
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async { Ok(None) })
+        }
+
+        fn verification_status<'a>(
+            &'a self,
+            _id: &'a str,
+        ) -> futures::future::BoxFuture<'a, Result<String>> {
+            Box::pin(async { Ok("Already Verified".to_string()) })
+        }
+
+        fn address_url(&self, address: Address) -> String {
+            format!("https://explorer.example/address/{:?}", address)
+        }
+
+        fn is_verified(&self, _address: Address) -> futures::future::BoxFuture<'_, Result<bool>> {
+            Box::pin(async { Ok(true) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skip_contracts_verified_on_target() {
+        let explorer = std::sync::Arc::new(Counting::default());
+        let target_client = &TargetClient::Custom(explorer.clone());
+        let source = MetadataSource::Custom(explorer.as_ref());
+        let copy = |skip_verified_check| async move {
+            copy_contract_verification(
+                "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+                source,
+                target_client,
+                CopyContext {
+                    skip_verified_check,
+                    ..Default::default()
+                },
+                &mut VerificationDetails::default(),
+            )
+            .await
+        };
+        let counts = || {
+            (
+                explorer.fetched.load(std::sync::atomic::Ordering::SeqCst),
+                explorer.submitted.load(std::sync::atomic::Ordering::SeqCst),
+            )
+        };
+        assert!(matches!(
+            copy(false).await.unwrap(),
+            VerificationResult::AlreadyVerified
+        ));
+        assert_eq!(counts(), (0, 0));
+        assert!(matches!(
+            copy(true).await.unwrap(),
+            VerificationResult::AlreadyVerified
+        ));
+        assert_eq!(counts(), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_stops_before_submitting() {
+        let explorer = std::sync::Arc::new(Counting::default());
+        let target_client = &TargetClient::Custom(explorer.clone());
+        let prepare = |skip_verified_check| {
+            prepare_contract_verification(
+                "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+                MetadataSource::Custom(explorer.as_ref()),
+                target_client,
+                CopyContext {
+                    skip_verified_check,
+                    ..Default::default()
+                },
+            )
+        };
+        // Nothing is submitted for contracts the target verified already
+        assert!(prepare(false).await.unwrap().is_none());
+        let verification_request = prepare(true).await.unwrap().unwrap();
+        assert_eq!(verification_request.contract_name, "Counter");
+        assert_eq!(
+            explorer.fetched.load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+        assert_eq!(
+            explorer.submitted.load(std::sync::atomic::Ordering::SeqCst),
+            0
+        );
+    }
+
+    /// Source without any verified contracts
+    #[derive(Debug)]
+    struct Unverified;
+
+    impl SourceExplorer for Unverified {
+        fn fetch_verification_request<'a>(
+            &'a self,
+            _address: &'a str,
+        ) -> futures::future::BoxFuture<'a, Result<Option<VerifyContract>>> {
+            Box::pin(async { Ok(None) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failure_stage() {
+        let err = copy_contract_verification(
+            "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+            MetadataSource::Custom(&Unverified),
+            &TargetClient::Custom(std::sync::Arc::new(Counting::default())),
+            CopyContext {
+                skip_verified_check: true,
+                ..Default::default()
+            },
+            &mut VerificationDetails::default(),
+        )
+        .await
+        .unwrap_err();
+        let err = MigrationError::of(&err).unwrap();
+        assert_eq!(err.stage(), FailureStage::Fetch);
+        assert_eq!(err.reason(), FailureReason::SourceNotVerified);
+    }
+}
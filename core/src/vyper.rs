@@ -0,0 +1,218 @@
+use crate::failure::{FailureReason, VerificationFailure};
+use crate::retry::RetryAfter;
+use crate::verification::{
+    explicit_evm_version, verification_request_response, VerificationRequestResponse,
+};
+use eyre::Result;
+use foundry_block_explorers::contract::{
+    Metadata, SourceCodeEntry, SourceCodeLanguage, SourceCodeMetadata,
+};
+use foundry_block_explorers::verify::{CodeFormat, VerifyContract};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Prefix of the compiler versions of Vyper contracts in the format of Etherscan (e.g.
+/// `vyper:0.3.10`), which is also how Vyper verification requests are told apart
+const VYPER_VERSION_PREFIX: &str = "vyper:";
+
+/// Code format of Vyper standard-json input in the Etherscan compatible api
+const VYPER_JSON_CODE_FORMAT: &str = "vyper-json";
+
+/// Returns whether the metadata belongs to a contract written in Vyper
+pub(crate) fn is_vyper_metadata(metadata: &Metadata) -> bool {
+    metadata
+        .compiler_version
+        .to_lowercase()
+        .starts_with("vyper")
+        || matches!(
+            metadata.source_code,
+            SourceCodeMetadata::Metadata {
+                language: Some(SourceCodeLanguage::Vyper),
+                ..
+            }
+        )
+}
+
+/// Returns whether the verification request is for a contract written in Vyper
+pub(crate) fn is_vyper_request(verification_request: &VerifyContract) -> bool {
+    verification_request
+        .compiler_version
+        .starts_with(VYPER_VERSION_PREFIX)
+}
+
+/// Normalize the Vyper version into the `vyper:0.3.10` format expected by Etherscan
+fn vyper_compiler_version(compiler_version: &str) -> String {
+    let version = compiler_version.trim();
+    let version = version
+        .strip_prefix(VYPER_VERSION_PREFIX)
+        .or_else(|| version.strip_prefix("vyper"))
+        .unwrap_or(version)
+        .trim_start_matches('v');
+    let version = version.split('+').next().unwrap_or_default();
+    format!("{}{}", VYPER_VERSION_PREFIX, version)
+}
+
+/// Vyper version in the `v0.3.10` format expected by Blockscout
+pub(crate) fn blockscout_compiler_version(verification_request: &VerifyContract) -> String {
+    let version = verification_request
+        .compiler_version
+        .trim_start_matches(VYPER_VERSION_PREFIX);
+    format!("v{}", version)
+}
+
+/// Build the Vyper standard-json verification request of a contract
+///
+/// Explorers report Vyper contracts as a single file, multiple files or standard-json, the first
+/// two are converted into standard-json with the evm version as only setting.
+pub(crate) fn verification_request_from_metadata(
+    contract_address: &str,
+    metadata: &Metadata,
+) -> Result<VerifyContract> {
+    let (sources, settings) = match &metadata.source_code {
+        SourceCodeMetadata::SourceCode(source_code) => (
+            HashMap::from([(
+                format!("{}.vy", metadata.contract_name),
+                SourceCodeEntry {
+                    content: source_code.clone(),
+                },
+            )]),
+            None,
+        ),
+        SourceCodeMetadata::Sources(sources) => (sources.clone(), None),
+        SourceCodeMetadata::Metadata {
+            sources, settings, ..
+        } => (sources.clone(), settings.clone()),
+    };
+    let settings = settings.unwrap_or_else(|| {
+        let mut settings = json!({});
+        if let Some(evm_version) = explicit_evm_version(metadata) {
+            settings["evmVersion"] = json!(evm_version);
+        }
+        settings
+    });
+    let path = vyper_source_path(&sources, &metadata.contract_name);
+    let standard_json = json!({
+        "language": "Vyper",
+        "sources": sources,
+        "settings": settings,
+    });
+    let constructor_arguments = hex::encode(&metadata.constructor_arguments);
+    Ok(VerifyContract {
+        address: contract_address.parse()?,
+        source: serde_json::to_string(&standard_json)?,
+        // Replaced by the Vyper code format on submission, which the client can not express
+        code_format: CodeFormat::StandardJsonInput,
+        contract_name: format!("{}:{}", path, metadata.contract_name),
+        compiler_version: vyper_compiler_version(&metadata.compiler_version),
+        optimization_used: None,
+        runs: None,
+        constructor_arguments: Some(constructor_arguments.clone()),
+        blockscout_constructor_arguments: Some(constructor_arguments),
+        evm_version: None,
+        other: HashMap::new(),
+    })
+}
+
+/// Path of the source file named after the contract, the only source or `<ContractName>.vy`
+fn vyper_source_path(sources: &HashMap<String, SourceCodeEntry>, contract_name: &str) -> String {
+    let file_name = format!("{}.vy", contract_name);
+    let mut paths: Vec<&String> = sources
+        .keys()
+        .filter(|path| path.rsplit('/').next() == Some(file_name.as_str()))
+        .collect();
+    paths.sort();
+    match (paths.first(), sources.keys().next()) {
+        (Some(path), _) => path.to_string(),
+        (None, Some(path)) if sources.len() == 1 => path.clone(),
+        _ => file_name,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EtherscanResponse {
+    message: String,
+    result: String,
+}
+
+/// Submit a Vyper verification request to an Etherscan compatible api, which needs the
+/// `vyper-json` code format the `foundry_block_explorers` client does not support
+pub(crate) async fn send_verification_request(
+    verification_request: &VerifyContract,
+    client: &reqwest::Client,
+    api_url: &reqwest::Url,
+    api_key: &str,
+) -> Result<VerificationRequestResponse> {
+    let mut form = vec![
+        ("module".to_string(), "contract".to_string()),
+        ("action".to_string(), "verifysourcecode".to_string()),
+        ("apikey".to_string(), api_key.to_string()),
+    ];
+    let fields = serde_json::to_value(verification_request)?;
+    for (key, value) in fields.as_object().into_iter().flatten() {
+        let value = match (key.as_str(), value) {
+            ("codeformat", _) => VYPER_JSON_CODE_FORMAT.to_string(),
+            (_, Value::String(value)) => value.clone(),
+            (_, value) => value.to_string(),
+        };
+        form.push((key.clone(), value));
+    }
+    let response = client.post(api_url.clone()).form(&form).send().await?;
+    if let Some(retry_after) = RetryAfter::from_response(&response) {
+        return Err(retry_after.into());
+    }
+    let response: EtherscanResponse = response.error_for_status()?.json().await?;
+    verification_request_response(&response.message, response.result)
+}
+
+/// Error for targets that can not verify Vyper contracts
+pub(crate) fn unsupported() -> eyre::Report {
+    VerificationFailure::report(
+        FailureReason::CompilerMismatch,
+        "The target does not support the verification of Vyper contracts",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_request_from_metadata() {
+        let mut metadata =
+            crate::test_utils::metadata("@external\ndef foo() -> uint256:\n    return 1\n");
+        metadata.contract_name = "Vault".to_string();
+        metadata.compiler_version = "vyper:0.3.10".to_string();
+        metadata.optimization_used = 0;
+        metadata.runs = 0;
+        metadata.constructor_arguments = vec![1].into();
+        metadata.evm_version = "shanghai".to_string();
+        assert!(is_vyper_metadata(&metadata));
+        let verification_request = verification_request_from_metadata(
+            "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            &metadata,
+        )
+        .unwrap();
+        assert!(is_vyper_request(&verification_request));
+        assert_eq!(verification_request.contract_name, "Vault.vy:Vault");
+        assert_eq!(verification_request.compiler_version, "vyper:0.3.10");
+        assert_eq!(
+            blockscout_compiler_version(&verification_request),
+            "v0.3.10"
+        );
+        let standard_json: Value = serde_json::from_str(&verification_request.source).unwrap();
+        assert_eq!(
+            standard_json,
+            json!({
+                "language": "Vyper",
+                "sources": { "Vault.vy": { "content": "@external\ndef foo() -> uint256:\n    return 1\n" } },
+                "settings": { "evmVersion": "shanghai" },
+            })
+        );
+
+        assert_eq!(
+            vyper_compiler_version("v0.3.7+commit.6020b8bb"),
+            "vyper:0.3.7"
+        );
+    }
+}
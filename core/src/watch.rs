@@ -0,0 +1,195 @@
+use crate::discovery::list_verified_contracts;
+use crate::failure::FailureReason;
+use crate::http::ExplorerHttpOptions;
+use crate::input::Contract;
+use crate::{copy_etherscan_verification_with_options, CopyOptions};
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Number of contracts requested from the source explorer per page when discovering newly
+/// verified contracts
+const DISCOVERY_PAGE_SIZE: u64 = 100;
+
+/// Contracts handled in previous watch cycles
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WatchState {
+    /// Addresses (lowercase) that are verified on the target block-explorer
+    pub completed: BTreeSet<String>,
+    /// Addresses (lowercase) whose verification failed permanently (e.g. because the target
+    /// rejected it), which are not retried
+    #[serde(default)]
+    pub failed: BTreeSet<String>,
+}
+
+impl WatchState {
+    /// Load the state from the given file, returning an empty state if it does not exist yet
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read state file {}", path.display()))?;
+        serde_json::from_str(&content)
+            .wrap_err_with(|| format!("Failed to parse state file {}", path.display()))
+    }
+
+    /// Write the state to the given file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .wrap_err_with(|| format!("Failed to write state file {}", path.display()))
+    }
+
+    fn is_done(&self, contract_address: &str) -> bool {
+        let address = contract_address.to_lowercase();
+        self.completed.contains(&address) || self.failed.contains(&address)
+    }
+}
+
+/// Whether a contract failing for the given reason is tried again in the next cycle
+///
+/// Contracts that are not verified on the source yet are the ones the watch is waiting for, so
+/// they are retried although the failure is not transient.
+fn is_retried(reason: FailureReason) -> bool {
+    reason.is_transient() || reason == FailureReason::SourceNotVerified
+}
+
+/// All contracts listed as verified by the source explorer
+async fn discover_verified_contracts(
+    source_api_key: &str,
+    source_url: &str,
+    source_http: &ExplorerHttpOptions,
+) -> Result<Vec<String>> {
+    let mut addresses = Vec::new();
+    for page in 1.. {
+        let listed = list_verified_contracts(
+            page,
+            DISCOVERY_PAGE_SIZE,
+            source_api_key.to_string(),
+            source_url.to_string(),
+            source_http,
+        )
+        .await
+        .wrap_err("Failed to discover verified contracts")?;
+        let is_last_page = (listed.len() as u64) < DISCOVERY_PAGE_SIZE;
+        addresses.extend(listed);
+        if is_last_page {
+            break;
+        }
+    }
+    Ok(addresses)
+}
+
+/// Continuously copy contract verification from one block-explorer to another
+///
+/// Every `interval` the watch-list (plus, if `discover_verified` is set, all verified contracts
+/// listed by the source explorer) is checked and the verification of all contracts that have not
+/// been copied yet is copied to the target. Contracts that are not verified on the source yet or
+/// fail transiently (see [`FailureReason::is_transient`]) are retried in the next cycle, the ones
+/// failing for any other reason are not. If a `state_path` is given the completed and failed
+/// contracts are persisted so they are not resubmitted after a restart.
+/// This function only returns on errors building the clients, loading or saving the state or
+/// discovering the verified contracts (after retrying transient failures).
+///
+/// # Arguments
+/// - `contracts` - Watch-list of contracts for which to copy the contract verification
+/// - `source_api_key` - The api key for the source block-explorer's api
+/// - `source_url` - The url of the source block-explorer's api
+/// - `source_http` - Settings of the http clients connecting to the source block-explorer
+/// - `target_api_key` - The api key for the target block-explorer's api
+/// - `target_url` - The url of the target block-explorer's api
+/// - `target_http` - Settings of the http clients connecting to the target block-explorer
+/// - `interval` - Time to wait between two cycles
+/// - `state_path` - Optional file to persist the completed contracts to
+/// - `discover_verified` - Whether to also copy newly verified contracts listed by the source
+///   explorer (requires a Blockscout compatible `listcontracts` endpoint)
+/// - `progress_bar` - Whether to display a progress bar of every cycle
+#[allow(clippy::too_many_arguments)]
+pub async fn watch_etherscan_verification(
+    contracts: Vec<impl Into<Contract>>,
+    source_api_key: String,
+    source_url: String,
+    source_http: &ExplorerHttpOptions,
+    target_api_key: String,
+    target_url: String,
+    target_http: &ExplorerHttpOptions,
+    interval: Duration,
+    state_path: Option<PathBuf>,
+    discover_verified: bool,
+    progress_bar: bool,
+) -> Result<()> {
+    let watch_list: Vec<Contract> = contracts.into_iter().map(Into::into).collect();
+    let options = CopyOptions {
+        progress_bar,
+        source_http: source_http.clone(),
+        target_http: target_http.clone(),
+        ..Default::default()
+    };
+    let mut state = match &state_path {
+        Some(path) => WatchState::load(path)?,
+        None => WatchState::default(),
+    };
+    loop {
+        let mut candidates = watch_list.clone();
+        if discover_verified {
+            let addresses =
+                discover_verified_contracts(&source_api_key, &source_url, source_http).await?;
+            candidates.extend(addresses.into_iter().map(Contract::from));
+        }
+        let mut seen = HashSet::new();
+        let pending: Vec<Contract> = candidates
+            .into_iter()
+            .filter(|contract| {
+                !state.is_done(&contract.address) && seen.insert(contract.address.to_lowercase())
+            })
+            .collect();
+
+        if !pending.is_empty() {
+            let outcomes = copy_etherscan_verification_with_options(
+                pending,
+                source_api_key.clone(),
+                source_url.clone(),
+                target_api_key.clone(),
+                target_url.clone(),
+                &options,
+            )
+            .await?;
+            for outcome in outcomes {
+                let address = outcome.contract.address;
+                match outcome.result {
+                    Ok(_) => {
+                        state.completed.insert(address.to_lowercase());
+                    }
+                    Err(err) if !is_retried(FailureReason::of(&err)) => {
+                        state.failed.insert(address.to_lowercase());
+                    }
+                    Err(_) => {}
+                }
+            }
+            if let Some(path) = &state_path {
+                state.save(path)?;
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_state() {
+        // State files written before failed contracts were recorded
+        let state: WatchState = serde_json::from_str(r#"{"completed": ["0xabc"]}"#).unwrap();
+        assert!(state.is_done("0xABC"));
+        assert!(state.failed.is_empty());
+        assert!(is_retried(FailureReason::SourceNotVerified));
+        assert!(is_retried(FailureReason::RateLimited));
+        assert!(!is_retried(FailureReason::TargetRejected));
+    }
+}
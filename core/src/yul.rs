@@ -0,0 +1,159 @@
+use crate::verification::{explicit_evm_version, normalize_compiler_version};
+use eyre::Result;
+use foundry_block_explorers::contract::{Metadata, SourceCodeMetadata};
+use foundry_block_explorers::verify::{CodeFormat, VerifyContract};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Build the Yul standard-json verification request of a contract, returning `None` if the
+/// contract is not written in Yul
+///
+/// The language of the explorer's metadata is either Solidity or Vyper, so Yul contracts show up
+/// as standard-json that failed to parse (language `Yul`), as single file starting with an
+/// `object` or as sources with a `.yul` extension.
+pub(crate) fn verification_request_from_metadata(
+    contract_address: &str,
+    metadata: &Metadata,
+) -> Result<Option<VerifyContract>> {
+    let Some(standard_json) = yul_standard_json(metadata) else {
+        return Ok(None);
+    };
+    let path = standard_json["sources"]
+        .as_object()
+        .and_then(|sources| {
+            let file_name = format!("{}.yul", metadata.contract_name);
+            sources
+                .keys()
+                .find(|path| path.rsplit('/').next() == Some(file_name.as_str()))
+                .or_else(|| sources.keys().next())
+                .cloned()
+        })
+        .unwrap_or_else(|| format!("{}.yul", metadata.contract_name));
+    Ok(Some(VerifyContract {
+        address: contract_address.parse()?,
+        source: serde_json::to_string(&standard_json)?,
+        code_format: CodeFormat::StandardJsonInput,
+        contract_name: format!("{}:{}", path, metadata.contract_name),
+        compiler_version: normalize_compiler_version(&metadata.compiler_version),
+        optimization_used: Some(metadata.optimization_used.to_string()),
+        runs: Some(metadata.runs.to_string()),
+        constructor_arguments: Some(hex::encode(&metadata.constructor_arguments)),
+        blockscout_constructor_arguments: Some(hex::encode(&metadata.constructor_arguments)),
+        evm_version: Some(metadata.evm_version.clone()),
+        other: HashMap::new(),
+    }))
+}
+
+fn yul_standard_json(metadata: &Metadata) -> Option<Value> {
+    match &metadata.source_code {
+        SourceCodeMetadata::SourceCode(source_code) => {
+            let source_code = source_code.trim();
+            // Etherscan wraps standard-json in an additional pair of braces
+            let standard_json = source_code
+                .strip_prefix('{')
+                .and_then(|source_code| source_code.strip_suffix('}'))
+                .filter(|inner| inner.trim_start().starts_with('{'))
+                .unwrap_or(source_code);
+            if let Ok(standard_json) = serde_json::from_str::<Value>(standard_json) {
+                return (standard_json["language"] == "Yul").then_some(standard_json);
+            }
+            if !source_code.starts_with("object \"") {
+                return None;
+            }
+            Some(json!({
+                "language": "Yul",
+                "sources": { format!("{}.yul", metadata.contract_name): { "content": source_code } },
+                "settings": settings(metadata),
+            }))
+        }
+        SourceCodeMetadata::Sources(sources)
+            if sources.keys().all(|path| path.ends_with(".yul")) && !sources.is_empty() =>
+        {
+            Some(json!({
+                "language": "Yul",
+                "sources": sources,
+                "settings": settings(metadata),
+            }))
+        }
+        SourceCodeMetadata::Metadata {
+            language: None,
+            sources,
+            settings: original_settings,
+        } if sources.keys().all(|path| path.ends_with(".yul")) && !sources.is_empty() => {
+            Some(json!({
+                "language": "Yul",
+                "sources": sources,
+                "settings": original_settings.clone().unwrap_or_else(|| settings(metadata)),
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Compiler settings reconstructed from the fields of the metadata
+fn settings(metadata: &Metadata) -> Value {
+    let mut settings = json!({
+        "optimizer": {
+            "enabled": metadata.optimization_used == 1,
+            "runs": metadata.runs,
+        },
+    });
+    if let Some(evm_version) = explicit_evm_version(metadata) {
+        settings["evmVersion"] = json!(evm_version);
+    }
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(source_code: &str) -> Metadata {
+        let mut metadata = crate::test_utils::metadata(source_code);
+        metadata.contract_name = "Token".to_string();
+        metadata
+    }
+
+    #[test]
+    fn test_verification_request_from_metadata() {
+        let address = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+        let source_code = "object \"Token\" { code { } }";
+        let verification_request =
+            verification_request_from_metadata(address, &metadata(source_code))
+                .unwrap()
+                .unwrap();
+        assert_eq!(verification_request.contract_name, "Token.yul:Token");
+        let standard_json: Value = serde_json::from_str(&verification_request.source).unwrap();
+        assert_eq!(
+            standard_json,
+            json!({
+                "language": "Yul",
+                "sources": { "Token.yul": { "content": source_code } },
+                "settings": { "optimizer": { "enabled": true, "runs": 200 } },
+            })
+        );
+
+        let standard_json = json!({
+            "language": "Yul",
+            "sources": { "src/Token.yul": { "content": source_code } },
+            "settings": { "optimizer": { "enabled": false } },
+        });
+        let verification_request = verification_request_from_metadata(
+            address,
+            &metadata(&format!("{{{}}}", standard_json)),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(verification_request.contract_name, "src/Token.yul:Token");
+        assert_eq!(
+            serde_json::from_str::<Value>(&verification_request.source).unwrap(),
+            standard_json
+        );
+
+        assert!(
+            verification_request_from_metadata(address, &metadata("contract Token {}"))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use crate::failure::{FailureReason, VerificationFailure};
+use crate::http::ExplorerHttpOptions;
+use crate::retry::RetryAfter;
+use alloy_primitives::Address;
+use eyre::{eyre, Result};
+use foundry_block_explorers::contract::Metadata;
+use foundry_block_explorers::verify::VerifyContract;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Key of the zksolc version in [`VerifyContract::other`], sent along as additional parameter to
+/// Etherscan compatible apis (e.g. the one of the zkSync Era block explorer)
+pub(crate) const ZKSOLC_VERSION_PARAMETER: &str = "zksolcVersion";
+
+/// Website of the zkSync Era block explorer, used for the links to verified contracts
+const ZKSYNC_BROWSER_URL: &str = "https://explorer.zksync.io/";
+/// Website of the zkSync Era block explorer for the Sepolia testnet
+const ZKSYNC_SEPOLIA_BROWSER_URL: &str = "https://sepolia.explorer.zksync.io/";
+
+/// Returns whether the url belongs to an api of the zkSync Era block explorer
+pub(crate) fn is_zksync_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(ToString::to_string))
+        .is_some_and(|host| {
+            ["zksync.io", "zksync.dev"].iter().any(|domain| {
+                host == *domain
+                    || host
+                        .strip_suffix(domain)
+                        .is_some_and(|subdomain| subdomain.ends_with('.'))
+            })
+        })
+}
+
+/// Client fetching verified contracts from the Etherscan compatible api of the zkSync Era block
+/// explorer (e.g. `https://block-explorer-api.mainnet.zksync.io/api`)
+///
+/// In addition to the metadata returned by other explorers it reports the zksolc version the
+/// contract was compiled with, which is required to verify it on zkSync again.
+pub(crate) struct ZkSyncSource {
+    client: reqwest::Client,
+    api_key: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceCodeResponse {
+    result: Value,
+}
+
+impl ZkSyncSource {
+    pub(crate) fn new(
+        api_key: String,
+        url: String,
+        http_options: &ExplorerHttpOptions,
+    ) -> Result<Self> {
+        Ok(ZkSyncSource {
+            client: http_options.client()?,
+            api_key,
+            url,
+        })
+    }
+
+    /// Fetch the verified metadata and zksolc version of a contract, returning `None` if it is
+    /// not verified
+    pub(crate) async fn fetch_metadata(
+        &self,
+        contract_address: &str,
+    ) -> Result<Option<(Metadata, Option<String>)>> {
+        let response = self
+            .client
+            .get(&self.url)
+            .query(&[
+                ("module", "contract"),
+                ("action", "getsourcecode"),
+                ("address", contract_address),
+                ("apikey", &self.api_key),
+            ])
+            .send()
+            .await?;
+        if let Some(retry_after) = RetryAfter::from_response(&response) {
+            return Err(retry_after.into());
+        }
+        let response: SourceCodeResponse = response.error_for_status()?.json().await?;
+        let Some(item) = response.result.as_array().and_then(|items| items.first()) else {
+            return Err(eyre!("Unexpected response: {}", response.result));
+        };
+        let zksolc_version = item["ZkCompilerVersion"]
+            .as_str()
+            .filter(|version| !version.is_empty())
+            .map(ToString::to_string);
+        let metadata: Metadata = serde_json::from_value(item.clone())?;
+        if metadata.contract_name.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some((metadata, zksolc_version)))
+    }
+}
+
+/// Client for the verification api of the zkSync Era block explorer (e.g.
+/// `https://zksync2-mainnet-explorer.zksync.io/contract_verification`)
+#[derive(Debug, Clone)]
+pub(crate) struct ZkSyncClient {
+    client: reqwest::Client,
+    /// Url of the api without trailing slash, ending in `/contract_verification`
+    api_url: String,
+    /// Url of the website with trailing slash
+    browser_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerificationStatus {
+    status: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl ZkSyncClient {
+    pub(crate) fn new(
+        api_url: String,
+        browser_url: Option<String>,
+        http_options: &ExplorerHttpOptions,
+    ) -> Result<Self> {
+        let api_url = api_url.trim_end_matches('/').to_string();
+        let browser_url = match browser_url {
+            Some(browser_url) => format!("{}/", browser_url.trim_end_matches('/')),
+            None if api_url.contains("sepolia") => ZKSYNC_SEPOLIA_BROWSER_URL.to_string(),
+            None => ZKSYNC_BROWSER_URL.to_string(),
+        };
+        Ok(ZkSyncClient {
+            client: http_options.client()?,
+            api_url,
+            browser_url,
+        })
+    }
+
+    pub(crate) fn address_url(&self, address: Address) -> String {
+        format!("{}address/{:?}", self.browser_url, address)
+    }
+
+    /// Submit a verification request, returning the id of the verification or `None` if the
+    /// contract is verified already
+    pub(crate) async fn submit(
+        &self,
+        verification_request: &VerifyContract,
+    ) -> Result<Option<String>> {
+        let response = self
+            .client
+            .post(&self.api_url)
+            .json(&verification_payload(verification_request)?)
+            .send()
+            .await?;
+        if let Some(retry_after) = RetryAfter::from_response(&response) {
+            return Err(retry_after.into());
+        }
+        if response.status().is_client_error() {
+            let message = response.text().await?;
+            let lowercase = message.to_lowercase();
+            if lowercase.contains("already verified") || lowercase.contains("already been verified")
+            {
+                return Ok(None);
+            }
+            return Err(VerificationFailure::report(
+                FailureReason::from_target_message(&message),
+                format!("Verification returned non-ok response: {}", message),
+            ));
+        }
+        let id: Value = response.error_for_status()?.json().await?;
+        match id {
+            Value::Number(id) => Ok(Some(id.to_string())),
+            Value::String(id) => Ok(Some(id)),
+            id => Err(eyre!("Unexpected verification id: {}", id)),
+        }
+    }
+
+    /// Request the status of a verification in the format of the Etherscan compatible api
+    pub(crate) async fn verification_status(&self, id: &str) -> Result<String> {
+        let response = self
+            .client
+            .get(format!("{}/{}", self.api_url, id))
+            .send()
+            .await?;
+        if let Some(retry_after) = RetryAfter::from_response(&response) {
+            return Err(retry_after.into());
+        }
+        let status: VerificationStatus = response.error_for_status()?.json().await?;
+        Ok(match status.status.as_str() {
+            "successful" => "Pass - Verified".to_string(),
+            "failed" => format!(
+                "Fail - Unable to verify: {}",
+                status.error.unwrap_or_default()
+            ),
+            _ => "Pending in queue".to_string(),
+        })
+    }
+}
+
+/// Build the payload of the zkSync verification api, which expects the solc version without
+/// commit and the zksolc version in separate fields
+fn verification_payload(verification_request: &VerifyContract) -> Result<Value> {
+    let zksolc_version = verification_request
+        .other
+        .get(ZKSOLC_VERSION_PARAMETER)
+        .ok_or_else(|| {
+            VerificationFailure::report(
+                FailureReason::CompilerMismatch,
+                "The zksolc version of the contract is unknown",
+            )
+        })?;
+    let solc_version = verification_request
+        .compiler_version
+        .trim_start_matches('v')
+        .split('+')
+        .next()
+        .unwrap_or_default();
+    let source_code: Value = serde_json::from_str(&verification_request.source)?;
+    let constructor_arguments = verification_request
+        .constructor_arguments
+        .as_deref()
+        .unwrap_or_default();
+    Ok(json!({
+        "contractAddress": format!("{:?}", verification_request.address),
+        "sourceCode": source_code,
+        "codeFormat": "solidity-standard-json-input",
+        "contractName": verification_request.contract_name,
+        "compilerZksolcVersion": zksolc_version,
+        "compilerSolcVersion": solc_version,
+        "optimizationUsed": verification_request.optimization_used.as_deref() == Some("1"),
+        "constructorArguments": format!("0x{}", constructor_arguments),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_block_explorers::verify::CodeFormat;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_verification_payload() {
+        let mut verification_request = VerifyContract {
+            address: Address::ZERO,
+            source: r#"{"language":"Solidity","sources":{},"settings":{"isSystem":false}}"#
+                .to_string(),
+            code_format: CodeFormat::StandardJsonInput,
+            contract_name: "src/Counter.sol:Counter".to_string(),
+            compiler_version: "v0.8.19+commit.7dd6d404".to_string(),
+            optimization_used: Some("1".to_string()),
+            runs: Some("200".to_string()),
+            constructor_arguments: Some("01".to_string()),
+            blockscout_constructor_arguments: None,
+            evm_version: None,
+            other: HashMap::new(),
+        };
+        let err = verification_payload(&verification_request).unwrap_err();
+        assert_eq!(FailureReason::of(&err), FailureReason::CompilerMismatch);
+
+        verification_request
+            .other
+            .insert(ZKSOLC_VERSION_PARAMETER.to_string(), "v1.3.14".to_string());
+        assert_eq!(
+            verification_payload(&verification_request).unwrap(),
+            json!({
+                "contractAddress": "0x0000000000000000000000000000000000000000",
+                "sourceCode": {"language": "Solidity", "sources": {}, "settings": {"isSystem": false}},
+                "codeFormat": "solidity-standard-json-input",
+                "contractName": "src/Counter.sol:Counter",
+                "compilerZksolcVersion": "v1.3.14",
+                "compilerSolcVersion": "0.8.19",
+                "optimizationUsed": true,
+                "constructorArguments": "0x01",
+            })
+        );
+        assert!(is_zksync_url(
+            "https://block-explorer-api.mainnet.zksync.io/api"
+        ));
+        assert!(!is_zksync_url("https://api.etherscan.io/api"));
+        assert!(!is_zksync_url("https://notzksync.io/api"));
+    }
+}